@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE LinkCodes;
+DROP TABLE UserIdentities;
+DROP TABLE Users;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TABLE Users(
+    id INTEGER PRIMARY KEY NOT NULL,
+    created BIGINT NOT NULL
+);
+
+CREATE TABLE UserIdentities(
+    id INTEGER PRIMARY KEY NOT NULL,
+    account_id INTEGER NOT NULL REFERENCES Users(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    UNIQUE(user_id, delivery_method)
+);
+
+CREATE TABLE LinkCodes(
+    code TEXT PRIMARY KEY NOT NULL,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    created BIGINT NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE VahtiDestinations(
+    id INTEGER PRIMARY KEY NOT NULL,
+    vahti_id INTEGER NOT NULL REFERENCES Vahdit(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    UNIQUE(vahti_id, user_id, delivery_method)
+);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE Vahdit
+DROP COLUMN resume_at;
+
+ALTER TABLE Vahdit
+DROP COLUMN paused;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE Vahdit
+ADD paused BOOLEAN NOT NULL DEFAULT 0;
+
+ALTER TABLE Vahdit
+ADD resume_at BIGINT;
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE Vahdit
+DROP COLUMN reminded;
+
+ALTER TABLE Vahdit
+DROP COLUMN empty_since;
+
+ALTER TABLE Vahdit
+DROP COLUMN expires_at;
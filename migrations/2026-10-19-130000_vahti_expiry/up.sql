@@ -0,0 +1,9 @@
+-- Your SQL goes here
+ALTER TABLE Vahdit
+ADD expires_at BIGINT;
+
+ALTER TABLE Vahdit
+ADD empty_since BIGINT;
+
+ALTER TABLE Vahdit
+ADD reminded BOOLEAN NOT NULL DEFAULT 0;
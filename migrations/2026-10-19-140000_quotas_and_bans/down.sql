@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE Bans;
+
+ALTER TABLE Vahdit
+DROP COLUMN created;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+ALTER TABLE Vahdit
+ADD created BIGINT NOT NULL DEFAULT 0;
+
+CREATE TABLE Bans(
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    created BIGINT NOT NULL,
+    UNIQUE(user_id, delivery_method)
+);
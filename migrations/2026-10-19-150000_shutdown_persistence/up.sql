@@ -0,0 +1,17 @@
+-- Your SQL goes here
+CREATE TABLE PendingDeliveries(
+    id INTEGER PRIMARY KEY NOT NULL,
+    deliver_to BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    item TEXT NOT NULL,
+    created BIGINT NOT NULL
+);
+
+CREATE TABLE ItemHistories(
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    item_id BIGINT NOT NULL,
+    site_id INTEGER NOT NULL,
+    timestamp BIGINT NOT NULL
+);
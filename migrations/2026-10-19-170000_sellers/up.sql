@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE Sellers(
+    id INTEGER PRIMARY KEY NOT NULL,
+    seller_id INTEGER NOT NULL,
+    site_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    account_created TEXT,
+    ad_count INTEGER,
+    updated BIGINT NOT NULL,
+    UNIQUE(seller_id, site_id)
+);
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE BlockRules(
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    created BIGINT NOT NULL
+);
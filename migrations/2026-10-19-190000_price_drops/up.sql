@@ -0,0 +1,13 @@
+-- Your SQL goes here
+ALTER TABLE Vahdit
+ADD price_drops BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE TABLE ItemPrices(
+    id INTEGER PRIMARY KEY NOT NULL,
+    vahti_id INTEGER NOT NULL,
+    item_id BIGINT NOT NULL,
+    site_id INTEGER NOT NULL,
+    price BIGINT NOT NULL,
+    updated BIGINT NOT NULL,
+    UNIQUE(vahti_id, item_id, site_id)
+);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE MatchedItems(
+    id INTEGER PRIMARY KEY NOT NULL,
+    vahti_id INTEGER NOT NULL,
+    item_id BIGINT NOT NULL,
+    site_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    price BIGINT NOT NULL,
+    published BIGINT NOT NULL,
+    UNIQUE(vahti_id, item_id, site_id)
+);
+
+CREATE INDEX matched_items_vahti_published ON MatchedItems(vahti_id, published);
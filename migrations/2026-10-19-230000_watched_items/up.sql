@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE WatchedItems(
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    site_id INTEGER NOT NULL,
+    listing_id BIGINT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    price BIGINT NOT NULL,
+    created BIGINT NOT NULL,
+    checked BIGINT NOT NULL,
+    UNIQUE(user_id, delivery_method, site_id, listing_id)
+);
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE "ItemHistories";
+DROP TABLE "PendingDeliveries";
+DROP TABLE "Bans";
+DROP TABLE "VahtiDestinations";
+DROP TABLE "LinkCodes";
+DROP TABLE "UserIdentities";
+DROP TABLE "Users";
+DROP TABLE "Blacklists";
+DROP TABLE "Vahdit";
@@ -0,0 +1,75 @@
+-- Your SQL goes here
+CREATE TABLE "Vahdit"(
+    id SERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    user_id BIGINT NOT NULL,
+    last_updated BIGINT NOT NULL,
+    site_id INTEGER NOT NULL DEFAULT 1,
+    delivery_method INTEGER NOT NULL DEFAULT 1,
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    resume_at BIGINT,
+    expires_at BIGINT,
+    empty_since BIGINT,
+    reminded BOOLEAN NOT NULL DEFAULT FALSE,
+    created BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE TABLE "Blacklists"(
+    id SERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    seller_id INTEGER NOT NULL,
+    site_id INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE "Users"(
+    id SERIAL PRIMARY KEY,
+    created BIGINT NOT NULL
+);
+
+CREATE TABLE "UserIdentities"(
+    id SERIAL PRIMARY KEY,
+    account_id INTEGER NOT NULL REFERENCES "Users"(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    UNIQUE(user_id, delivery_method)
+);
+
+CREATE TABLE "LinkCodes"(
+    code TEXT PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    created BIGINT NOT NULL
+);
+
+CREATE TABLE "VahtiDestinations"(
+    id SERIAL PRIMARY KEY,
+    vahti_id INTEGER NOT NULL REFERENCES "Vahdit"(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    UNIQUE(vahti_id, user_id, delivery_method)
+);
+
+CREATE TABLE "Bans"(
+    id SERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    created BIGINT NOT NULL,
+    UNIQUE(user_id, delivery_method)
+);
+
+CREATE TABLE "PendingDeliveries"(
+    id SERIAL PRIMARY KEY,
+    deliver_to BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    item TEXT NOT NULL,
+    created BIGINT NOT NULL
+);
+
+CREATE TABLE "ItemHistories"(
+    id SERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    item_id BIGINT NOT NULL,
+    site_id INTEGER NOT NULL,
+    timestamp BIGINT NOT NULL
+);
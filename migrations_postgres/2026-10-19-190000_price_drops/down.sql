@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE "ItemPrices";
+
+ALTER TABLE "Vahdit"
+DROP COLUMN price_drops;
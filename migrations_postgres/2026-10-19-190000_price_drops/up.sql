@@ -0,0 +1,13 @@
+-- Your SQL goes here
+ALTER TABLE "Vahdit"
+ADD price_drops BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE "ItemPrices"(
+    id SERIAL PRIMARY KEY,
+    vahti_id INTEGER NOT NULL REFERENCES "Vahdit"(id) ON DELETE CASCADE,
+    item_id BIGINT NOT NULL,
+    site_id INTEGER NOT NULL,
+    price BIGINT NOT NULL,
+    updated BIGINT NOT NULL,
+    UNIQUE(vahti_id, item_id, site_id)
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE "RepostHistory";
+
+ALTER TABLE "Vahdit"
+DROP COLUMN reposts;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+ALTER TABLE "Vahdit"
+ADD reposts INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE "RepostHistory"(
+    id SERIAL PRIMARY KEY,
+    vahti_id INTEGER NOT NULL REFERENCES "Vahdit"(id) ON DELETE CASCADE,
+    item_id BIGINT NOT NULL,
+    site_id INTEGER NOT NULL,
+    seller_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    price BIGINT NOT NULL,
+    img_url TEXT NOT NULL,
+    seen BIGINT NOT NULL,
+    UNIQUE(vahti_id, item_id, site_id)
+);
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TABLE "DeliveredItems"(
+    id SERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    site_id INTEGER NOT NULL,
+    item_id BIGINT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    vahti_url TEXT NOT NULL,
+    price BIGINT NOT NULL,
+    location TEXT NOT NULL,
+    seller_name TEXT NOT NULL,
+    published BIGINT NOT NULL,
+    delivered BIGINT NOT NULL
+);
+
+CREATE INDEX delivered_items_user_delivered ON "DeliveredItems"(user_id, delivered);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+ALTER TABLE "Vahdit"
+ADD max_distance INTEGER;
+
+CREATE TABLE "HomeLocations"(
+    id SERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    delivery_method INTEGER NOT NULL,
+    postal_code TEXT NOT NULL,
+    UNIQUE(user_id, delivery_method)
+);
@@ -1,2 +1 @@
 rm -rf database.sqlite*
-diesel setup
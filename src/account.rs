@@ -0,0 +1,52 @@
+use rand::distributions::Uniform;
+use rand::Rng;
+
+use crate::database::Database;
+use crate::error::Error;
+
+/// How long a link code stays valid, in seconds
+const LINK_CODE_VALIDITY: i64 = 600;
+const LINK_CODE_CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const LINK_CODE_LENGTH: usize = 8;
+
+fn generate_link_code() -> String {
+    rand::thread_rng()
+        .sample_iter(Uniform::from(0..LINK_CODE_CHARSET.len()))
+        .take(LINK_CODE_LENGTH)
+        .map(|i| LINK_CODE_CHARSET[i] as char)
+        .collect()
+}
+
+pub async fn new_link_code(
+    db: Database,
+    userid: u64,
+    delivery_method: i32,
+) -> Result<String, Error> {
+    let code = generate_link_code();
+    db.add_link_code(&code, userid as i64, delivery_method)
+        .await?;
+    Ok(code)
+}
+
+pub async fn link_account(
+    db: Database,
+    code: &str,
+    userid: u64,
+    delivery_method: i32,
+) -> Result<String, Error> {
+    let valid_after = chrono::Local::now().timestamp() - LINK_CODE_VALIDITY;
+    let Some(other) = db
+        .take_link_code(&code.trim().to_uppercase(), valid_after)
+        .await?
+    else {
+        return Err(Error::InvalidLinkCode);
+    };
+
+    if other == (userid as i64, delivery_method) {
+        return Err(Error::InvalidLinkCode);
+    }
+
+    db.link_identities(other, (userid as i64, delivery_method))
+        .await?;
+    Ok(String::from("Accounts linked successfully"))
+}
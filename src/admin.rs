@@ -0,0 +1,116 @@
+use itertools::Itertools;
+
+use crate::database::Database;
+use crate::error::Error;
+
+/// Parses an admin entry of the form `platform:user_id`
+pub fn parse_admin(admin: &str) -> Option<(u64, i32)> {
+    let (platform, userid) = admin.trim().split_once(':')?;
+    Some((userid.parse().ok()?, parse_platform(platform)?))
+}
+
+/// Whether the (user_id, delivery_method) pair is one of the admins configured in `admins`
+pub fn is_admin(userid: u64, delivery_method: i32) -> bool {
+    crate::config::get()
+        .admins
+        .iter()
+        .filter_map(|a| parse_admin(a))
+        .any(|a| a == (userid, delivery_method))
+}
+
+/// Converts a platform name into the corresponding delivery method
+pub fn parse_platform(platform: &str) -> Option<i32> {
+    match platform.to_lowercase().as_str() {
+        #[cfg(feature = "discord-delivery")]
+        crate::delivery::discord::NAME => Some(crate::delivery::discord::ID),
+        #[cfg(feature = "telegram-delivery")]
+        crate::delivery::telegram::NAME => Some(crate::delivery::telegram::ID),
+        _ => None,
+    }
+}
+
+/// Requests a reload of the configuration, handled in the background
+pub fn request_reload(admin: (u64, i32)) -> Result<String, Error> {
+    if !is_admin(admin.0, admin.1) {
+        return Err(Error::NotAdmin);
+    }
+
+    info!("Admin {} requested a configuration reload", admin.0);
+    crate::config::RELOAD_REQUESTED.notify_one();
+    Ok(String::from("Configuration reload requested"))
+}
+
+pub enum AdminCommand {
+    ListVahtis,
+    RemoveVahti(String),
+    Ban,
+    Unban,
+}
+
+/// Runs an admin command against the target (user_id, delivery_method)
+pub async fn run_admin_command(
+    db: Database,
+    admin: (u64, i32),
+    target: (u64, i32),
+    cmd: AdminCommand,
+) -> Result<String, Error> {
+    if !is_admin(admin.0, admin.1) {
+        return Err(Error::NotAdmin);
+    }
+
+    let (userid, delivery_method) = (target.0 as i64, target.1);
+    info!(
+        "Admin {} running a command against the user {}",
+        admin.0, userid
+    );
+
+    match cmd {
+        AdminCommand::ListVahtis => {
+            let vahdit = db
+                .fetch_vahti_entries_by_user_id(userid)
+                .await?
+                .into_iter()
+                .filter(|v| v.delivery_method == delivery_method)
+                .map(|v| {
+                    if v.paused {
+                        format!("{} (paused)", v.url)
+                    } else {
+                        v.url
+                    }
+                })
+                .join("\n");
+
+            if vahdit.is_empty() {
+                Ok(String::from("The user has no Vahtis"))
+            } else {
+                Ok(format!("Vahtis of the user {}:\n{}", userid, vahdit))
+            }
+        }
+        AdminCommand::RemoveVahti(url) => {
+            if db.remove_vahti_entry(&url, userid, delivery_method).await? == 0 {
+                Ok(String::from("The user has no Vahti with that url"))
+            } else {
+                Ok(String::from("Vahti removed!"))
+            }
+        }
+        AdminCommand::Ban => {
+            if db.is_banned(userid, delivery_method).await? {
+                return Ok(String::from("The user is already banned"));
+            }
+            db.add_ban(userid, delivery_method).await?;
+            for (uid, dm) in db.fetch_linked_identities(userid, delivery_method).await? {
+                db.pause_vahtis_of_recipient(uid, dm).await?;
+            }
+            Ok(String::from("User banned and their Vahtis paused"))
+        }
+        AdminCommand::Unban => {
+            if db.remove_ban(userid, delivery_method).await? == 0 {
+                Ok(String::from("The user is not banned"))
+            } else {
+                Ok(String::from(
+                    "User unbanned, their Vahtis can be resumed with /jatka",
+                ))
+            }
+        }
+    }
+}
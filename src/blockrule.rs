@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::database::Database;
+use crate::error::Error;
+use crate::models::DbBlockRule;
+use crate::vahti::VahtiItem;
+
+/// A per-user rule filtering out items from all of the user's Vahtis
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockRule {
+    /// Ads posted by companies and shops
+    Company,
+    /// Sellers whose name matches the pattern, `*` matches any string
+    SellerName(String),
+    /// Items whose location contains the given text
+    Location(String),
+    /// Items whose title contains the given keyword
+    Keyword(String),
+}
+
+impl BlockRule {
+    /// Parses a rule given as a command argument, e.g. `myyja kauppa*`
+    pub fn parse(kind: &str, value: &str) -> Result<Self, Error> {
+        let value = value.trim().to_string();
+        match (kind, value.is_empty()) {
+            ("yritykset", _) => Ok(Self::Company),
+            ("myyja", false) => Ok(Self::SellerName(value)),
+            ("sijainti", false) => Ok(Self::Location(value)),
+            ("hakusana", false) => Ok(Self::Keyword(value)),
+            _ => Err(Error::InvalidBlockRule),
+        }
+    }
+
+    pub fn from_db(rule: &DbBlockRule) -> Option<Self> {
+        match rule.kind.as_str() {
+            "company" => Some(Self::Company),
+            "seller" => Some(Self::SellerName(rule.value.clone())),
+            "location" => Some(Self::Location(rule.value.clone())),
+            "keyword" => Some(Self::Keyword(rule.value.clone())),
+            k => {
+                warn!("Unknown block rule kind {}", k);
+                None
+            }
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Company => "company",
+            Self::SellerName(_) => "seller",
+            Self::Location(_) => "location",
+            Self::Keyword(_) => "keyword",
+        }
+    }
+
+    fn value(&self) -> &str {
+        match self {
+            Self::Company => "",
+            Self::SellerName(v) | Self::Location(v) | Self::Keyword(v) => v,
+        }
+    }
+
+    pub fn matches(&self, item: &VahtiItem) -> bool {
+        match self {
+            Self::Company => item.company_ad,
+            Self::SellerName(pattern) => {
+                wildcard_match(&pattern.to_lowercase(), &item.seller_name.to_lowercase())
+            }
+            Self::Location(location) => item
+                .location
+                .to_lowercase()
+                .contains(&location.to_lowercase()),
+            Self::Keyword(keyword) => item.title.to_lowercase().contains(&keyword.to_lowercase()),
+        }
+    }
+}
+
+impl fmt::Display for BlockRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Company => write!(f, "Company ads"),
+            Self::SellerName(p) => write!(f, "Seller: {}", p),
+            Self::Location(l) => write!(f, "Location: {}", l),
+            Self::Keyword(k) => write!(f, "Keyword: {}", k),
+        }
+    }
+}
+
+/// Matches the whole text against the pattern, where `*` matches any string
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let (mut p, mut t) = (0, 0);
+    // The position of the latest `*` in the pattern and the text position it was tried at
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((sp, st)) = star {
+            // Let the latest `*` consume one more character
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+pub async fn add_block_rule(
+    db: Database,
+    userid: u64,
+    delivery_method: i32,
+    rule: BlockRule,
+) -> Result<String, Error> {
+    db.add_block_rule(userid as i64, delivery_method, rule.kind(), rule.value())
+        .await?;
+    Ok(format!("Block rule added: {}", rule))
+}
+
+/// Returns the (id, rule) pairs of the user's block rules
+pub async fn list_block_rules(
+    db: &Database,
+    userid: u64,
+    delivery_method: i32,
+) -> Result<Vec<(i32, BlockRule)>, Error> {
+    Ok(db
+        .fetch_user_block_rules(userid as i64, delivery_method)
+        .await?
+        .iter()
+        .filter_map(|r| BlockRule::from_db(r).map(|b| (r.id, b)))
+        .collect())
+}
+
+pub async fn remove_block_rule(
+    db: Database,
+    ruleid: i32,
+    userid: u64,
+    delivery_method: i32,
+) -> Result<String, Error> {
+    if db
+        .remove_block_rule(ruleid, userid as i64, delivery_method)
+        .await?
+        == 0
+    {
+        return Ok(String::from("No block rule with that id"));
+    }
+    Ok(String::from("Block rule removed!"))
+}
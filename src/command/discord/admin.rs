@@ -0,0 +1,107 @@
+use serenity::all::CommandDataOptionValue;
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::admin::{parse_platform, request_reload, run_admin_command, AdminCommand};
+
+fn target_options(subcommand: CreateCommandOption) -> CreateCommandOption {
+    subcommand
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "kayttaja", "Käyttäjän id")
+                .required(true),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "alusta", "Käyttäjän alusta")
+                .add_string_choice("Discord", "discord")
+                .add_string_choice("Telegram", "telegram"),
+        )
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("admin")
+        .description("Ylläpitäjän komennot")
+        .add_option(target_options(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "vahdit",
+            "Näytä käyttäjän vahdit",
+        )))
+        .add_option(
+            target_options(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "poistavahti",
+                "Poista käyttäjän vahti",
+            ))
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "url", "Hakusivun linkki")
+                    .required(true),
+            ),
+        )
+        .add_option(target_options(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "esta",
+            "Estä käyttäjää käyttämästä bottia",
+        )))
+        .add_option(target_options(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "poistaesto",
+            "Salli estetyn käyttäjän käyttää bottia",
+        )))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "lataa",
+            "Lataa asetukset uudelleen",
+        ))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let Some(subcommand) = command.data.options.first() else {
+        unreachable!()
+    };
+    let CommandDataOptionValue::SubCommand(options) = &subcommand.value else {
+        unreachable!()
+    };
+
+    if subcommand.name == "lataa" {
+        return request_reload((u64::from(command.user.id), crate::delivery::discord::ID))
+            .unwrap_or_else(|e| e.to_string());
+    }
+
+    let mut userid = None;
+    let mut platform = crate::delivery::discord::ID;
+    let mut url = String::new();
+    for a in options {
+        match a.name.as_str() {
+            "kayttaja" => userid = a.value.as_str().and_then(|u| u.parse::<u64>().ok()),
+            "alusta" => {
+                platform = parse_platform(a.value.as_str().unwrap()).unwrap_or(platform);
+            }
+            "url" => url = String::from(a.value.as_str().unwrap()),
+            _ => unreachable!(),
+        }
+    }
+
+    let Some(userid) = userid else {
+        return String::from("Virheellinen käyttäjän id");
+    };
+
+    let cmd = match subcommand.name.as_str() {
+        "vahdit" => AdminCommand::ListVahtis,
+        "poistavahti" => AdminCommand::RemoveVahti(url),
+        "esta" => AdminCommand::Ban,
+        "poistaesto" => AdminCommand::Unban,
+        _ => unreachable!(),
+    };
+
+    let db = ctx.get_db().await.unwrap();
+
+    run_admin_command(
+        db,
+        (u64::from(command.user.id), crate::delivery::discord::ID),
+        (userid, platform),
+        cmd,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string())
+}
@@ -0,0 +1,85 @@
+use serenity::all::CommandDataOptionValue;
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::blockrule::{add_block_rule, BlockRule};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("esto")
+        .description("Estä ilmoituksia kaikista vahdeistasi")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "yritykset",
+            "Estä yritysten ilmoitukset",
+        ))
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "myyja",
+                "Estä myyjät nimen perusteella",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "arvo",
+                    "Myyjän nimi, * vastaa mitä tahansa merkkijonoa",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "sijainti",
+                "Estä ilmoitukset sijainnin perusteella",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "arvo", "Sijainti")
+                    .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "hakusana",
+                "Estä ilmoitukset, joiden otsikossa on hakusana",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "arvo", "Hakusana")
+                    .required(true),
+            ),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let Some(subcommand) = command.data.options.first() else {
+        unreachable!()
+    };
+    let CommandDataOptionValue::SubCommand(options) = &subcommand.value else {
+        unreachable!()
+    };
+
+    let value = options
+        .iter()
+        .find(|a| a.name == "arvo")
+        .and_then(|a| a.value.as_str())
+        .unwrap_or_default();
+
+    let rule = match BlockRule::parse(&subcommand.name, value) {
+        Ok(r) => r,
+        Err(e) => return e.to_string(),
+    };
+
+    let db = ctx.get_db().await.unwrap();
+
+    add_block_rule(
+        db,
+        u64::from(command.user.id),
+        crate::delivery::discord::ID,
+        rule,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string())
+}
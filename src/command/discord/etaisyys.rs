@@ -0,0 +1,48 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::vahti::set_max_distance;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("etaisyys")
+        .description("Lähetä vain ilmoitukset, jotka ovat enintään annetun matkan päässä kotoasi")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "url", "Hakusivun linkki")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "km",
+                "Suurin etäisyys kilometreinä, ilman sitä rajoitus poistetaan",
+            )
+            .min_int_value(1)
+            .max_int_value(2000),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let mut url = String::new();
+    let mut distance = None;
+    for a in &command.data.options {
+        match a.name.as_str() {
+            "url" => url = String::from(a.value.as_str().unwrap()),
+            "km" => distance = a.value.as_i64().map(|d| d as i32),
+            _ => unreachable!(),
+        }
+    }
+
+    let db = ctx.get_db().await.unwrap();
+
+    set_max_distance(
+        db,
+        &url,
+        u64::from(command.user.id),
+        crate::delivery::discord::ID,
+        distance,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string())
+}
@@ -0,0 +1,47 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::vahti::set_price_drops;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("hinnanlasku")
+        .description("Ilmoita, kun jo lähetetyn ilmoituksen hinta laskee")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "url", "Hakusivun linkki")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "paalla",
+                "Ilmoitetaanko hinnan laskuista",
+            )
+            .required(true),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let mut url = String::new();
+    let mut enabled = false;
+    for a in &command.data.options {
+        match a.name.as_str() {
+            "url" => url = String::from(a.value.as_str().unwrap()),
+            "paalla" => enabled = a.value.as_bool().unwrap_or_default(),
+            _ => unreachable!(),
+        }
+    }
+
+    let db = ctx.get_db().await.unwrap();
+
+    set_price_drops(
+        db,
+        &url,
+        u64::from(command.user.id),
+        crate::delivery::discord::ID,
+        enabled,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string())
+}
@@ -0,0 +1,42 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::stats::price_stats;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("hinnat")
+        .description("Näytä vahdin löytämien ilmoitusten hintatilastot")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "url", "Hakusivun linkki")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "paivat",
+                "Tilastoihin otettavien päivien määrä, oletuksena 30",
+            )
+            .min_int_value(1)
+            .max_int_value(365),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let mut url = String::new();
+    let mut days = 30;
+    for a in &command.data.options {
+        match a.name.as_str() {
+            "url" => url = String::from(a.value.as_str().unwrap()),
+            "paivat" => days = a.value.as_i64().map(|d| d as u32).unwrap_or(days),
+            _ => unreachable!(),
+        }
+    }
+
+    let db = ctx.get_db().await.unwrap();
+
+    price_stats(db, &url, u64::from(command.user.id), days)
+        .await
+        .unwrap_or_else(|e| e.to_string())
+}
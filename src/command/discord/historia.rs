@@ -0,0 +1,80 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::error::Error;
+use crate::history::{parse_date, parse_site, search_history, HistoryQuery};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("historia")
+        .description("Hae vahtien aiemmin lähettämiä ilmoituksia")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "hakusana",
+            "Ilmoituksen otsikossa oleva sana",
+        ))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Integer, "min_hinta", "Vähimmäishinta")
+                .min_int_value(0),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Integer, "max_hinta", "Enimmäishinta")
+                .min_int_value(0),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "sivusto",
+            "Sivusto, esim. tori, huutonet, nettiauto tai muusikoiden",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "alkaen",
+            "Lähetetty aikaisintaan, pp.kk.vvvv",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "asti",
+            "Lähetetty viimeistään, pp.kk.vvvv",
+        ))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Integer, "sivu", "Tulossivu")
+                .min_int_value(1),
+        )
+}
+
+fn parse_query(command: &CommandInteraction) -> Result<HistoryQuery, Error> {
+    let mut query = HistoryQuery::default();
+    for a in &command.data.options {
+        match a.name.as_str() {
+            "hakusana" => query.keyword = a.value.as_str().map(String::from),
+            "min_hinta" => query.min_price = a.value.as_i64(),
+            "max_hinta" => query.max_price = a.value.as_i64(),
+            "sivusto" => query.site_id = Some(parse_site(a.value.as_str().unwrap())?),
+            "alkaen" => query.since = Some(parse_date(a.value.as_str().unwrap(), false)?),
+            "asti" => query.until = Some(parse_date(a.value.as_str().unwrap(), true)?),
+            "sivu" => query.page = a.value.as_i64().unwrap_or(1) as u32,
+            _ => unreachable!(),
+        }
+    }
+    Ok(query)
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let query = match parse_query(command) {
+        Ok(q) => q,
+        Err(e) => return e.to_string(),
+    };
+
+    let db = ctx.get_db().await.unwrap();
+
+    search_history(
+        &db,
+        u64::from(command.user.id),
+        crate::delivery::discord::ID,
+        query,
+    )
+    .await
+    .map(|p| p.format_markdown())
+    .unwrap_or_else(|e| e.to_string())
+}
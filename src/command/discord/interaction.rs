@@ -4,6 +4,7 @@ use serenity::builder::{
     CreateActionRow, CreateSelectMenu, CreateSelectMenuOption, EditInteractionResponse,
 };
 use serenity::model::application::Interaction;
+use serenity::model::user::User;
 use serenity::prelude::*;
 
 use super::extensions::ClientContextExt;
@@ -25,15 +26,37 @@ pub fn menu_from_options(
     vec![CreateActionRow::SelectMenu(menu)]
 }
 
+async fn is_banned(ctx: &Context, user: &User) -> bool {
+    let db = ctx.get_db().await.unwrap();
+    db.is_banned(u64::from(user.id) as i64, crate::delivery::discord::ID)
+        .await
+        .unwrap_or(false)
+}
+
 pub async fn handle_interaction(ctx: Context, interaction: Interaction) {
     match interaction {
         Interaction::Command(command) => {
             command.defer_ephemeral(&ctx.http).await.unwrap();
 
             let content = match command.data.name.as_str() {
+                _ if is_banned(&ctx, &command.user).await => {
+                    crate::error::Error::Banned.to_string()
+                }
                 "vahti" => super::vahti::run(&ctx, &command).await,
                 "poistavahti" => super::poistavahti::run(&ctx, &command).await,
                 "poistaesto" => super::poistaesto::run(&ctx, &command).await,
+                "esto" => super::esto::run(&ctx, &command).await,
+                "yhdista" => super::yhdista::run(&ctx, &command).await,
+                "siirravahti" => super::siirravahti::run(&ctx, &command).await,
+                "pysayta" => super::pysayta::run(&ctx, &command).await,
+                "jatka" => super::jatka::run(&ctx, &command).await,
+                "hinnanlasku" => super::hinnanlasku::run(&ctx, &command).await,
+                "uudelleenjulkaisut" => super::uudelleenjulkaisut::run(&ctx, &command).await,
+                "hinnat" => super::hinnat::run(&ctx, &command).await,
+                "historia" => super::historia::run(&ctx, &command).await,
+                "koti" => super::koti::run(&ctx, &command).await,
+                "etaisyys" => super::etaisyys::run(&ctx, &command).await,
+                "admin" => super::admin::run(&ctx, &command).await,
                 _ => unreachable!(),
             };
 
@@ -45,7 +68,19 @@ pub async fn handle_interaction(ctx: Context, interaction: Interaction) {
             }
         }
         Interaction::Component(button) => {
-            if button.data.custom_id == "remove_vahti" {
+            if is_banned(&ctx, &button.user).await {
+                button.defer_ephemeral(&ctx.http).await.unwrap();
+                button
+                    .edit_response(
+                        &ctx.http,
+                        EditInteractionResponse::new()
+                            .content(crate::error::Error::Banned.to_string()),
+                    )
+                    .await
+                    .unwrap();
+            } else if button.data.custom_id == "remove_vahti"
+                || button.data.custom_id == "pause_vahti"
+            {
                 button.defer_ephemeral(&ctx.http).await.unwrap();
                 let message = button.message.clone();
                 let urls: Vec<_> = message
@@ -60,7 +95,7 @@ pub async fn handle_interaction(ctx: Context, interaction: Interaction) {
                         .edit_response(
                             &ctx.http,
                             EditInteractionResponse::new().components(menu_from_options(
-                                "remove_vahti_menu",
+                                &format!("{}_menu", button.data.custom_id),
                                 urls.iter().zip(urls.iter()).collect::<Vec<_>>(),
                             )),
                         )
@@ -69,7 +104,7 @@ pub async fn handle_interaction(ctx: Context, interaction: Interaction) {
                 } else {
                     button
                         .edit_response(&ctx.http,
-                            EditInteractionResponse::new().content("Creating Vahti deletion menu failed, try deleting the Vahti manually with /poistavahti")
+                            EditInteractionResponse::new().content("Creating Vahti menu failed, try using /poistavahti or /pysayta manually")
                         )
                     .await.unwrap();
                 }
@@ -92,14 +127,18 @@ pub async fn handle_interaction(ctx: Context, interaction: Interaction) {
                     .filter_map(|f| f.map(|ff| ff.value.clone()))
                     .filter_map(|s| match s {
                         #[cfg(feature = "tori")]
-                        _ if s.contains("https://www.tori.fi/li?&aid=") => Some((
-                            s[1..s.find(']').unwrap()].to_string(),
-                            format!(
-                                "{},{}",
-                                &s[s.rfind('=').unwrap() + 1..s.find(')').unwrap()],
-                                crate::tori::ID
-                            ),
-                        )),
+                        _ if s.contains("https://www.tori.fi/li?&aid=")
+                            || s.contains("https://www.tori.fi/profile/ads?userId=") =>
+                        {
+                            Some((
+                                s[1..s.find(']').unwrap()].to_string(),
+                                format!(
+                                    "{},{}",
+                                    &s[s.rfind('=').unwrap() + 1..s.find(')').unwrap()],
+                                    crate::tori::ID
+                                ),
+                            ))
+                        }
                         #[cfg(feature = "huutonet")]
                         _ if s.contains("https://www.huuto.net/kayttaja/") => Some((
                             s[1..s.find(']').unwrap()].to_string(),
@@ -109,20 +148,33 @@ pub async fn handle_interaction(ctx: Context, interaction: Interaction) {
                                 crate::huutonet::ID
                             ),
                         )),
+                        #[cfg(feature = "muusikoiden")]
+                        _ if s.contains("https://muusikoiden.net/jasenet/profiili.php?id=") => {
+                            Some((
+                                s[1..s.find(']').unwrap()].to_string(),
+                                format!(
+                                    "{},{}",
+                                    &s[s.rfind('=').unwrap() + 1..s.find(')').unwrap()],
+                                    crate::muusikoiden::ID
+                                ),
+                            ))
+                        }
                         _ => None,
                     })
                     .unique()
-                    .collect::<Vec<_>>();
+                    .collect::<Vec<(String, String)>>();
 
-                button
-                    .edit_response(
-                        &ctx.http,
-                        EditInteractionResponse::new()
-                            .content("Choose the seller to block")
-                            .components(menu_from_options("block_seller_menu", sellers)),
+                // NOTE: Discord rejects select menus without options
+                let response = if sellers.is_empty() {
+                    EditInteractionResponse::new().content(
+                        "The sellers of these items can't be blocked, try using /esto to block them by name",
                     )
-                    .await
-                    .unwrap();
+                } else {
+                    EditInteractionResponse::new()
+                        .content("Choose the seller to block")
+                        .components(menu_from_options("block_seller_menu", sellers))
+                };
+                button.edit_response(&ctx.http, response).await.unwrap();
             } else if button.data.custom_id == "unblock_seller" {
                 button.defer_ephemeral(&ctx.http).await.unwrap();
                 let db = ctx.get_db().await.unwrap();
@@ -133,12 +185,28 @@ pub async fn handle_interaction(ctx: Context, interaction: Interaction) {
                     }
                     _ => unreachable!(),
                 };
-                let sellerid = ids[0].parse::<i32>().unwrap();
-                let siteid = ids[1].parse::<i32>().unwrap();
+                if ids[0] == "rule" {
+                    crate::blockrule::remove_block_rule(
+                        db,
+                        ids[1].parse::<i32>().unwrap(),
+                        userid,
+                        crate::delivery::discord::ID,
+                    )
+                    .await
+                    .unwrap();
+                } else {
+                    let sellerid = ids[0].parse::<i32>().unwrap();
+                    let siteid = ids[1].parse::<i32>().unwrap();
 
-                db.remove_seller_from_blacklist(userid.try_into().unwrap(), sellerid, siteid)
+                    db.remove_seller_from_blacklist(
+                        userid.try_into().unwrap(),
+                        crate::delivery::discord::ID,
+                        sellerid,
+                        siteid,
+                    )
                     .await
                     .unwrap();
+                }
                 button
                     .edit_response(
                         &ctx.http,
@@ -166,6 +234,77 @@ pub async fn handle_interaction(ctx: Context, interaction: Interaction) {
                     )
                     .await
                     .unwrap();
+            } else if button.data.custom_id == "pause_vahti_menu" {
+                button.defer_ephemeral(&ctx.http).await.unwrap();
+                let userid = u64::from(button.user.id);
+                let url = match button.data.kind.clone() {
+                    ComponentInteractionDataKind::StringSelect { values } => values[0].to_string(),
+                    _ => unreachable!(),
+                };
+                let db = ctx.get_db().await.unwrap();
+
+                crate::vahti::pause_vahti(db, &url, userid, crate::delivery::discord::ID, None)
+                    .await
+                    .unwrap();
+                button
+                    .edit_response(
+                        &ctx.http,
+                        EditInteractionResponse::new().content(format!(
+                            "Pysäytetty vahti: `{}`. Jatka komennolla `/jatka`",
+                            url
+                        )),
+                    )
+                    .await
+                    .unwrap();
+            } else if let Some(id) = button.data.custom_id.strip_prefix("extend_vahti_") {
+                button.defer_ephemeral(&ctx.http).await.unwrap();
+                let db = ctx.get_db().await.unwrap();
+                let content = crate::vahti::extend_vahti(
+                    db,
+                    id.parse().unwrap(),
+                    u64::from(button.user.id),
+                    crate::delivery::discord::ID,
+                )
+                .await
+                .unwrap_or_else(|e| e.to_string());
+                button
+                    .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+                    .await
+                    .unwrap();
+            } else if let Some((siteid, listingid, recommerce)) =
+                crate::watch::parse_watch_action_id(&button.data.custom_id)
+            {
+                button.defer_ephemeral(&ctx.http).await.unwrap();
+                let db = ctx.get_db().await.unwrap();
+                let content = crate::watch::watch_item(
+                    &db,
+                    u64::from(button.user.id),
+                    crate::delivery::discord::ID,
+                    siteid,
+                    listingid,
+                    recommerce,
+                )
+                .await
+                .unwrap_or_else(|e| e.to_string());
+                button
+                    .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+                    .await
+                    .unwrap();
+            } else if let Some(id) = button.data.custom_id.strip_prefix("unwatch_item_") {
+                button.defer_ephemeral(&ctx.http).await.unwrap();
+                let db = ctx.get_db().await.unwrap();
+                let content = crate::watch::unwatch_item(
+                    &db,
+                    id.parse().unwrap(),
+                    u64::from(button.user.id),
+                    crate::delivery::discord::ID,
+                )
+                .await
+                .unwrap_or_else(|e| e.to_string());
+                button
+                    .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+                    .await
+                    .unwrap();
             } else if button.data.custom_id.starts_with("remove_vahti_menu_page_") {
                 let page_number: usize = button
                     .data
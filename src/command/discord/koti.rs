@@ -0,0 +1,37 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::geo::set_home;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("koti")
+        .description("Aseta kotisi postinumero ilmoitusten etäisyyksiä varten")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "postinumero",
+            "Kotisi postinumero, ilman sitä postinumero poistetaan",
+        ))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let mut code = None;
+    for a in &command.data.options {
+        match a.name.as_str() {
+            "postinumero" => code = a.value.as_str().map(|c| c.trim().to_string()),
+            _ => unreachable!(),
+        }
+    }
+
+    let db = ctx.get_db().await.unwrap();
+
+    set_home(
+        db,
+        u64::from(command.user.id),
+        crate::delivery::discord::ID,
+        code.as_deref(),
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string())
+}
@@ -1,8 +1,20 @@
+mod admin;
+mod esto;
+mod etaisyys;
 mod extensions;
+mod hinnanlasku;
+mod hinnat;
+mod historia;
 mod interaction;
+mod jatka;
+mod koti;
 mod poistaesto;
 mod poistavahti;
+mod pysayta;
+mod siirravahti;
+mod uudelleenjulkaisut;
 mod vahti;
+mod yhdista;
 
 use std::sync::Arc;
 
@@ -35,6 +47,18 @@ impl EventHandler for Handler {
                 vahti::register(),
                 poistavahti::register(),
                 poistaesto::register(),
+                esto::register(),
+                yhdista::register(),
+                siirravahti::register(),
+                pysayta::register(),
+                jatka::register(),
+                hinnanlasku::register(),
+                uudelleenjulkaisut::register(),
+                hinnat::register(),
+                historia::register(),
+                koti::register(),
+                etaisyys::register(),
+                admin::register(),
             ],
         )
         .await;
@@ -54,13 +78,18 @@ pub struct Manager {
 
 impl Discord {
     pub async fn init(db: &Database) -> Result<Self, Error> {
-        let token =
-            std::env::var("DISCORD_TOKEN").expect("Expected DISCORD_TOKEN in the environment");
-
-        let application_id: u64 = std::env::var("APPLICATION_ID")
-            .expect("Expected APPLICATION_ID in the environment")
-            .parse()
-            .expect("Invalid APPLICATION_ID");
+        let config = crate::config::get();
+        let token = config
+            .discord
+            .token
+            .clone()
+            .ok_or(Error::Config(String::from("Discord token is not set")))?;
+        let application_id = config
+            .discord
+            .application_id
+            .ok_or(Error::Config(String::from(
+                "Discord application_id is not set",
+            )))?;
 
         let client = Client::builder(&token, GatewayIntents::non_privileged())
             .application_id(application_id.into())
@@ -6,38 +6,40 @@ use super::extensions::ClientContextExt;
 use super::interaction::menu_from_options;
 
 pub fn register() -> CreateCommand {
-    CreateCommand::new("poistaesto").description("Salli aiemmin estetty myyjä")
+    CreateCommand::new("poistaesto").description("Poista aiemmin lisätty esto")
 }
 
 pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
     let db = ctx.get_db().await.unwrap();
     let blacklist = db
-        .fetch_user_blacklist(u64::from(command.user.id) as i64)
+        .fetch_user_blacklist(
+            u64::from(command.user.id) as i64,
+            crate::delivery::discord::ID,
+        )
         .await
         .unwrap();
 
-    let mut blacklist_names = vec![];
-    for entry in &blacklist {
-        blacklist_names.push(match entry.1 {
-            #[cfg(feature = "tori")]
-            crate::tori::ID => crate::tori::seller::get_seller_name_from_id(entry.0)
-                .await
-                .unwrap_or(String::from("Unknown Seller")),
-            #[cfg(feature = "huutonet")]
-            crate::huutonet::ID => crate::huutonet::seller::get_seller_name_from_id(entry.0)
-                .await
-                .unwrap_or(String::from("Unknown Seller")),
-            _ => String::from("Unknown Seller"),
-        });
-    }
+    let blacklist_names = crate::seller::seller_names(&db, &blacklist).await;
+    let rules = crate::blockrule::list_block_rules(
+        &db,
+        u64::from(command.user.id),
+        crate::delivery::discord::ID,
+    )
+    .await
+    .unwrap();
 
     let options = blacklist_names
-        .iter()
+        .into_iter()
         .zip(blacklist.iter().map(|ids| format!("{},{}", ids.0, ids.1)))
+        .chain(
+            rules
+                .iter()
+                .map(|(id, rule)| (rule.to_string(), format!("rule,{}", id))),
+        )
         .collect::<Vec<_>>();
 
     let mut edit = EditInteractionResponse::new().content("Valitse poistettava(t) esto/estot");
-    if blacklist.is_empty() {
+    if options.is_empty() {
         edit = edit.content("Ei estettyjä myyjiä!");
     } else {
         edit = edit.components(menu_from_options("unblock_seller", options));
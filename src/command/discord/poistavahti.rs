@@ -56,10 +56,15 @@ pub async fn update_message(
 ) -> serenity::builder::CreateInteractionResponseMessage {
     let db = ctx.get_db().await.unwrap();
 
-    let vahtilist = db
+    let mut vahtilist = db
         .fetch_vahti_entries_by_user_id(user_id as i64)
         .await
         .unwrap();
+    vahtilist.extend(
+        db.fetch_vahti_entries_by_destination(user_id as i64, crate::delivery::discord::ID)
+            .await
+            .unwrap(),
+    );
 
     let mut urls = vahtilist.iter().cloned().map(|v| v.url).collect::<Vec<_>>();
     urls.sort();
@@ -94,10 +99,18 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
         .unwrap()
     } else {
         let db = ctx.get_db().await.unwrap();
-        let vahtilist = db
+        let mut vahtilist = db
             .fetch_vahti_entries_by_user_id(u64::from(command.user.id) as i64)
             .await
             .unwrap();
+        vahtilist.extend(
+            db.fetch_vahti_entries_by_destination(
+                u64::from(command.user.id) as i64,
+                crate::delivery::discord::ID,
+            )
+            .await
+            .unwrap(),
+        );
 
         let urls = vahtilist.iter().cloned().map(|v| v.url).collect::<Vec<_>>();
 
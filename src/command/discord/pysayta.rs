@@ -0,0 +1,47 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::vahti::pause_vahti;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("pysayta")
+        .description("Pysäytä vahti poistamatta sitä")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "url", "Hakusivun linkki")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "paivat",
+                "Jatka vahtia automaattisesti näin monen päivän kuluttua",
+            )
+            .min_int_value(1),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let mut url = String::new();
+    let mut days = None;
+    for a in &command.data.options {
+        match a.name.as_str() {
+            "url" => url = String::from(a.value.as_str().unwrap()),
+            "paivat" => days = a.value.as_i64().map(|d| d as u32),
+            _ => unreachable!(),
+        }
+    }
+
+    let db = ctx.get_db().await.unwrap();
+
+    pause_vahti(
+        db,
+        &url,
+        u64::from(command.user.id),
+        crate::delivery::discord::ID,
+        days,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string())
+}
@@ -0,0 +1,51 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::repost::RepostMode;
+use crate::vahti::set_repost_mode;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("uudelleenjulkaisut")
+        .description("Valitse, miten uudelleen julkaistut ilmoitukset käsitellään")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "url", "Hakusivun linkki")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "tila",
+                "Uudelleen julkaistujen ilmoitusten käsittely",
+            )
+            .add_string_choice("Lähetä uusina ilmoituksina", "pois")
+            .add_string_choice("Merkitse uudelleen julkaistuiksi", "merkitse")
+            .add_string_choice("Älä lähetä", "piilota")
+            .required(true),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let mut url = String::new();
+    let mut mode = RepostMode::Off;
+    for a in &command.data.options {
+        match a.name.as_str() {
+            "url" => url = String::from(a.value.as_str().unwrap()),
+            "tila" => mode = RepostMode::parse(a.value.as_str().unwrap()).unwrap_or_default(),
+            _ => unreachable!(),
+        }
+    }
+
+    let db = ctx.get_db().await.unwrap();
+
+    set_repost_mode(
+        db,
+        &url,
+        u64::from(command.user.id),
+        crate::delivery::discord::ID,
+        mode,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string())
+}
@@ -3,26 +3,74 @@ use serenity::client::Context;
 use serenity::model::application::{CommandInteraction, CommandOptionType};
 
 use super::extensions::ClientContextExt;
+use crate::search::query::KeywordQuery;
 use crate::vahti::new_vahti;
 
 pub fn register() -> CreateCommand {
     CreateCommand::new("vahti")
         .description("Luo uusi vahti")
         .add_option(
-            CreateCommandOption::new(CommandOptionType::String, "url", "Hakusivun linkki")
-                .required(true),
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "url",
+                "Hakusivun linkki tai hakusanat",
+            )
+            .required(true),
         )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "voimassa",
+                "Vahdin voimassaoloaika päivinä",
+            )
+            .min_int_value(1),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "min_hinta",
+                "Hakusanojen vähimmäishinta",
+            )
+            .min_int_value(0),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "max_hinta",
+                "Hakusanojen enimmäishinta",
+            )
+            .min_int_value(0),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "sijainti",
+            "Hakusanojen sijainti",
+        ))
 }
 
 pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
     let mut url = String::new();
+    let mut days = None;
+    let mut query = KeywordQuery::default();
     for a in &command.data.options {
         match a.name.as_str() {
             "url" => url = String::from(a.value.as_str().unwrap()),
+            "voimassa" => days = a.value.as_i64().map(|d| d as u32),
+            "min_hinta" => query.min_price = a.value.as_i64(),
+            "max_hinta" => query.max_price = a.value.as_i64(),
+            "sijainti" => query.location = a.value.as_str().map(|l| l.to_lowercase()),
             _ => unreachable!(),
         }
     }
 
+    if query != KeywordQuery::default() {
+        if KeywordQuery::parse(&url).is_none() {
+            return String::from("Hinta ja sijainti voidaan antaa vain hakusanoille");
+        }
+        query.keywords = url;
+        url = query.to_string();
+    }
+
     info!("New vahti {}", &url);
 
     let db = ctx.get_db().await.unwrap();
@@ -32,6 +80,7 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
         &url,
         u64::from(command.user.id),
         crate::delivery::discord::ID,
+        days,
     )
     .await
     .unwrap_or_else(|e| e.to_string())
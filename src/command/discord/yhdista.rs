@@ -0,0 +1,43 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::client::Context;
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+
+use super::extensions::ClientContextExt;
+use crate::account::{link_account, new_link_code};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("yhdista")
+        .description("Yhdistä Discord- ja Telegram-tilisi")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "koodi",
+            "Toisella alustalla luotu yhdistämiskoodi",
+        ))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> String {
+    let mut code = String::new();
+    for a in &command.data.options {
+        match a.name.as_str() {
+            "koodi" => code = String::from(a.value.as_str().unwrap()),
+            _ => unreachable!(),
+        }
+    }
+
+    let db = ctx.get_db().await.unwrap();
+    let userid = u64::from(command.user.id);
+
+    if code.is_empty() {
+        match new_link_code(db, userid, crate::delivery::discord::ID).await {
+            Ok(code) => format!(
+                "Yhdistämiskoodisi on `{}`. Syötä se toisella alustalla komennolla `/yhdista {}` 10 minuutin kuluessa",
+                code, code
+            ),
+            Err(e) => e.to_string(),
+        }
+    } else {
+        link_account(db, &code, userid, crate::delivery::discord::ID)
+            .await
+            .unwrap_or_else(|e| e.to_string())
+    }
+}
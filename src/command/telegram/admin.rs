@@ -0,0 +1,43 @@
+use teloxide::prelude::*;
+
+use crate::admin::{parse_platform, request_reload, run_admin_command, AdminCommand};
+use crate::database::Database;
+
+const USAGE: &str = "Usage: /admin [vahdit|poistavahti|esta|poistaesto] [discord|telegram] \
+                     [user_id] [url] or /admin lataa";
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let mut args = args.split_whitespace();
+    if args.clone().next() == Some("lataa") {
+        return Ok(
+            request_reload((msg.chat.id.0 as u64, crate::delivery::telegram::ID))
+                .unwrap_or_else(|e| e.to_string()),
+        );
+    }
+
+    let (Some(subcommand), Some(platform), Some(userid)) = (args.next(), args.next(), args.next())
+    else {
+        return Ok(String::from(USAGE));
+    };
+
+    let (Some(platform), Ok(userid)) = (parse_platform(platform), userid.parse::<u64>()) else {
+        return Ok(String::from(USAGE));
+    };
+
+    let cmd = match (subcommand, args.next()) {
+        ("vahdit", _) => AdminCommand::ListVahtis,
+        ("poistavahti", Some(url)) => AdminCommand::RemoveVahti(url.to_string()),
+        ("esta", _) => AdminCommand::Ban,
+        ("poistaesto", _) => AdminCommand::Unban,
+        _ => return Ok(String::from(USAGE)),
+    };
+
+    Ok(run_admin_command(
+        db,
+        (msg.chat.id.0 as u64, crate::delivery::telegram::ID),
+        (userid, platform),
+        cmd,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string()))
+}
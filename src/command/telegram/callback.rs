@@ -0,0 +1,58 @@
+use teloxide::adaptors::throttle::Limits;
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::vahti::extend_vahti;
+use crate::watch::{parse_watch_action_id, unwatch_item, watch_item, WATCH_ITEM_PREFIX};
+
+/// Handles the inline keyboard buttons attached to notifications
+pub async fn handle(bot: Bot, q: CallbackQuery, db: Database) -> ResponseResult<()> {
+    let userid = q.from.id.0;
+    let banned = db
+        .is_banned(userid as i64, crate::delivery::telegram::ID)
+        .await
+        .unwrap_or(false);
+
+    let response = match q.data.as_deref() {
+        _ if banned => crate::error::Error::Banned.to_string(),
+        Some(data) if data.starts_with("extend_vahti_") => {
+            match data["extend_vahti_".len()..].parse() {
+                Ok(id) => extend_vahti(db, id, userid, crate::delivery::telegram::ID)
+                    .await
+                    .unwrap_or_else(|e| e.to_string()),
+                Err(_) => String::from("Invalid Vahti id"),
+            }
+        }
+        Some(data) if data.starts_with("unwatch_item_") => {
+            match data["unwatch_item_".len()..].parse() {
+                Ok(id) => unwatch_item(&db, id, userid, crate::delivery::telegram::ID)
+                    .await
+                    .unwrap_or_else(|e| e.to_string()),
+                Err(_) => String::from("Invalid item id"),
+            }
+        }
+        Some(data) if data.starts_with(WATCH_ITEM_PREFIX) => match parse_watch_action_id(data) {
+            Some((siteid, listingid, recommerce)) => watch_item(
+                &db,
+                userid,
+                crate::delivery::telegram::ID,
+                siteid,
+                listingid,
+                recommerce,
+            )
+            .await
+            .unwrap_or_else(|e| e.to_string()),
+            None => String::from("Invalid item id"),
+        },
+        _ => String::from("Unknown action"),
+    };
+
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.message {
+        bot.throttle(Limits::default())
+            .send_message(msg.chat.id, response)
+            .disable_web_page_preview(true)
+            .await?;
+    }
+    Ok(())
+}
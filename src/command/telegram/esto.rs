@@ -0,0 +1,22 @@
+use teloxide::prelude::*;
+
+use crate::blockrule::{add_block_rule, BlockRule};
+use crate::database::Database;
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let (kind, value) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+
+    let rule = match BlockRule::parse(kind, value) {
+        Ok(r) => r,
+        Err(e) => return Ok(e.to_string()),
+    };
+
+    Ok(add_block_rule(
+        db,
+        msg.chat.id.0 as u64,
+        crate::delivery::telegram::ID,
+        rule,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string()))
+}
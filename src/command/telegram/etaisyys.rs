@@ -0,0 +1,27 @@
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::vahti::set_max_distance;
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let mut args = args.split_whitespace();
+    let Some(vahti) = args.next() else {
+        return Ok(String::from("No url provided"));
+    };
+
+    let distance = match args.next().map(|d| d.parse::<i32>()) {
+        Some(Ok(d)) if (1..=2000).contains(&d) => Some(d),
+        Some(_) => return Ok(String::from("Invalid distance")),
+        None => None,
+    };
+
+    Ok(set_max_distance(
+        db,
+        vahti,
+        msg.chat.id.0 as u64,
+        crate::delivery::telegram::ID,
+        distance,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string()))
+}
@@ -0,0 +1,27 @@
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::vahti::set_price_drops;
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let mut args = args.split_whitespace();
+    let Some(vahti) = args.next() else {
+        return Ok(String::from("No url provided"));
+    };
+
+    let enabled = match args.next() {
+        Some("on") | None => true,
+        Some("off") => false,
+        Some(_) => return Ok(String::from("Expected `on` or `off`")),
+    };
+
+    Ok(set_price_drops(
+        db,
+        vahti,
+        msg.chat.id.0 as u64,
+        crate::delivery::telegram::ID,
+        enabled,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string()))
+}
@@ -0,0 +1,21 @@
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::stats::price_stats;
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let mut args = args.split_whitespace();
+    let Some(vahti) = args.next() else {
+        return Ok(String::from("No url provided"));
+    };
+
+    let days = match args.next().map(|d| d.parse::<u32>()) {
+        Some(Ok(d)) if (1..=365).contains(&d) => d,
+        Some(_) => return Ok(String::from("Invalid amount of days")),
+        None => 30,
+    };
+
+    Ok(price_stats(db, vahti, msg.chat.id.0 as u64, days)
+        .await
+        .unwrap_or_else(|e| e.to_string()))
+}
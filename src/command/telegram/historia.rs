@@ -0,0 +1,21 @@
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::history::{search_history, HistoryQuery};
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let query = match HistoryQuery::parse(&args) {
+        Ok(q) => q,
+        Err(e) => return Ok(e.to_string()),
+    };
+
+    Ok(search_history(
+        &db,
+        msg.chat.id.0 as u64,
+        crate::delivery::telegram::ID,
+        query,
+    )
+    .await
+    .map(|p| p.format_plain())
+    .unwrap_or_else(|e| e.to_string()))
+}
@@ -0,0 +1,23 @@
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::vahti::resume_vahti;
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let mut args = args.split_whitespace();
+    let Some(vahti) = args.next() else {
+        return Ok(String::from("No url provided"));
+    };
+
+    let catch_up = matches!(args.next(), Some("all" | "kaikki"));
+
+    Ok(resume_vahti(
+        db,
+        vahti,
+        msg.chat.id.0 as u64,
+        crate::delivery::telegram::ID,
+        catch_up,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string()))
+}
@@ -0,0 +1,17 @@
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::geo::set_home;
+
+pub async fn run(msg: Message, code: String, db: Database) -> ResponseResult<String> {
+    let code = Some(code.trim()).filter(|c| !c.is_empty());
+
+    Ok(set_home(
+        db,
+        msg.chat.id.0 as u64,
+        crate::delivery::telegram::ID,
+        code,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string()))
+}
@@ -1,7 +1,21 @@
+mod admin;
+mod callback;
+mod esto;
+mod etaisyys;
 mod help;
+mod hinnanlasku;
+mod hinnat;
+mod historia;
+mod jatka;
+mod koti;
+mod poistaesto;
 mod poistavahti;
+mod pysayta;
+mod siirravahti;
 mod start;
+mod uudelleenjulkaisut;
 mod vahti;
+mod yhdista;
 
 use async_trait::async_trait;
 use teloxide::adaptors::throttle::Limits;
@@ -27,18 +41,26 @@ pub struct Manager {
 
 impl Telegram {
     pub async fn init(db: &Database) -> Result<Self, Error> {
-        let token =
-            std::env::var("TELOXIDE_TOKEN").expect("Expected TELOXIDE_TOKEN in the environment");
+        let config = crate::config::get();
+        let token = config
+            .telegram
+            .token
+            .clone()
+            .ok_or(Error::Config(String::from("Telegram token is not set")))?;
 
         let bot = Bot::new(token);
 
         let _ = bot.set_my_commands(TelegramCommand::bot_commands()).await;
 
-        let handler = Update::filter_message().branch(
-            dptree::entry()
-                .filter_command::<TelegramCommand>()
-                .endpoint(handle),
-        );
+        let handler = dptree::entry()
+            .branch(
+                Update::filter_message().branch(
+                    dptree::entry()
+                        .filter_command::<TelegramCommand>()
+                        .endpoint(handle),
+                ),
+            )
+            .branch(Update::filter_callback_query().endpoint(callback::handle));
 
         let dispatcher = Dispatcher::builder(bot.clone(), handler)
             .dependencies(dptree::deps![db.clone()])
@@ -58,16 +80,83 @@ enum TelegramCommand {
     Start,
     #[command(description = "Display help message")]
     Help,
-    #[command(description = "Add new vahti with `/vahti [url]`")]
+    #[command(
+        description = "Add new vahti with `/vahti [url] [days]`, optionally expiring after the given amount of days, or search every site with `/vahti [keywords] [hinta:min-max] [sijainti:location]`"
+    )]
     Vahti(String),
     #[command(description = "Remove a vahti with `/poistavahti [url]`")]
     PoistaVahti(String),
+    #[command(description = "Link your Telegram and Discord accounts with `/yhdista [code]`")]
+    Yhdista(String),
+    #[command(description = "Move a Vahti from a linked account here with `/siirravahti [url]`")]
+    SiirraVahti(String),
+    #[command(
+        description = "Pause a Vahti with `/pysayta [url] [days]`, optionally resuming it after the given amount of days"
+    )]
+    Pysayta(String),
+    #[command(
+        description = "Resume a paused Vahti with `/jatka [url] [all]`, `all` delivers the items posted while paused"
+    )]
+    Jatka(String),
+    #[command(
+        description = "Get notified when the price of an already delivered item drops with `/hinnanlasku [url] [on|off]`"
+    )]
+    Hinnanlasku(String),
+    #[command(
+        description = "Choose how reposted ads are handled with `/uudelleenjulkaisut [url] [pois|merkitse|piilota]`"
+    )]
+    Uudelleenjulkaisut(String),
+    #[command(
+        description = "Show the price statistics of a Vahti with `/hinnat [url] [days]`, defaults to the last 30 days"
+    )]
+    Hinnat(String),
+    #[command(
+        description = "Search the delivered items with `/historia [keyword] [min=price] [max=price] [sivusto=site] [alkaen=dd.mm.yyyy] [asti=dd.mm.yyyy] [sivu=page]`"
+    )]
+    Historia(String),
+    #[command(
+        description = "Set your home postal code for the distances of the items with `/koti [postal_code]`, without a code it is removed"
+    )]
+    Koti(String),
+    #[command(
+        description = "Only deliver the items of a Vahti within the given distance from your home with `/etaisyys [url] [km]`, without a distance the limit is removed"
+    )]
+    Etaisyys(String),
+    #[command(
+        description = "Block items from all your Vahtis with `/esto [yritykset|myyja|sijainti|hakusana] [value]`, `*` in a seller name matches anything"
+    )]
+    Esto(String),
+    #[command(description = "List your block rules or remove one with `/poistaesto [id]`")]
+    PoistaEsto(String),
+    #[command(
+        description = "Admin commands `/admin [vahdit|poistavahti|esta|poistaesto] [discord|telegram] [user_id] [url]`"
+    )]
+    Admin(String),
 }
 
 async fn handle(bot: Bot, msg: Message, cmd: TelegramCommand, db: Database) -> ResponseResult<()> {
+    let banned = db
+        .is_banned(msg.chat.id.0, crate::delivery::telegram::ID)
+        .await
+        .unwrap_or(false);
+
     let response = match cmd {
+        _ if banned => Ok(crate::error::Error::Banned.to_string()),
         TelegramCommand::Vahti(v) => vahti::run(msg.clone(), v, db).await,
         TelegramCommand::PoistaVahti(v) => poistavahti::run(msg.clone(), v, db).await,
+        TelegramCommand::Yhdista(c) => yhdista::run(msg.clone(), c, db).await,
+        TelegramCommand::SiirraVahti(v) => siirravahti::run(msg.clone(), v, db).await,
+        TelegramCommand::Pysayta(v) => pysayta::run(msg.clone(), v, db).await,
+        TelegramCommand::Jatka(v) => jatka::run(msg.clone(), v, db).await,
+        TelegramCommand::Hinnanlasku(v) => hinnanlasku::run(msg.clone(), v, db).await,
+        TelegramCommand::Uudelleenjulkaisut(v) => uudelleenjulkaisut::run(msg.clone(), v, db).await,
+        TelegramCommand::Hinnat(v) => hinnat::run(msg.clone(), v, db).await,
+        TelegramCommand::Historia(h) => historia::run(msg.clone(), h, db).await,
+        TelegramCommand::Koti(k) => koti::run(msg.clone(), k, db).await,
+        TelegramCommand::Etaisyys(v) => etaisyys::run(msg.clone(), v, db).await,
+        TelegramCommand::Esto(e) => esto::run(msg.clone(), e, db).await,
+        TelegramCommand::PoistaEsto(e) => poistaesto::run(msg.clone(), e, db).await,
+        TelegramCommand::Admin(a) => admin::run(msg.clone(), a, db).await,
         TelegramCommand::Help => help::run().await,
         TelegramCommand::Start => start::run().await,
     }
@@ -86,7 +175,10 @@ async fn handle(bot: Bot, msg: Message, cmd: TelegramCommand, db: Database) -> R
 impl super::Manager for Manager {
     async fn shutdown(&self) {
         info!("Telegram destroy");
-        self.shutdown_token.shutdown().unwrap().await;
+        // The dispatcher is not running while it is being restarted
+        if let Ok(f) = self.shutdown_token.shutdown() {
+            f.await;
+        }
         info!("Telegram destroy done");
     }
 }
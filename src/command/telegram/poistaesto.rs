@@ -0,0 +1,37 @@
+use teloxide::prelude::*;
+
+use crate::blockrule::{list_block_rules, remove_block_rule};
+use crate::database::Database;
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let userid = msg.chat.id.0 as u64;
+
+    if args.trim().is_empty() {
+        let Ok(rules) = list_block_rules(&db, userid, crate::delivery::telegram::ID).await else {
+            return Ok(String::from("Fetching the block rules failed"));
+        };
+
+        if rules.is_empty() {
+            return Ok(String::from("You have no block rules"));
+        }
+
+        return Ok(format!(
+            "Remove a block rule with `/poistaesto [id]`\n{}",
+            rules
+                .iter()
+                .map(|(id, rule)| format!("{}: {}", id, rule))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ));
+    }
+
+    let Ok(ruleid) = args.trim().parse() else {
+        return Ok(String::from("Invalid block rule id"));
+    };
+
+    Ok(
+        remove_block_rule(db, ruleid, userid, crate::delivery::telegram::ID)
+            .await
+            .unwrap_or_else(|e| e.to_string()),
+    )
+}
@@ -10,6 +10,12 @@ pub async fn run(msg: Message, vahti: String, db: Database) -> ResponseResult<St
             .await
             .unwrap_or(Vec::new())
             .iter()
+            .chain(
+                db.fetch_vahti_entries_by_destination(msg.chat.id.0, crate::delivery::telegram::ID)
+                    .await
+                    .unwrap_or(Vec::new())
+                    .iter(),
+            )
             .map(|v| v.url.clone())
             .collect::<Vec<_>>();
 
@@ -0,0 +1,27 @@
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::vahti::pause_vahti;
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let mut args = args.split_whitespace();
+    let Some(vahti) = args.next() else {
+        return Ok(String::from("No url provided"));
+    };
+
+    let days = match args.next().map(|d| d.parse::<u32>()) {
+        Some(Ok(d)) if d > 0 => Some(d),
+        Some(_) => return Ok(String::from("Invalid amount of days")),
+        None => None,
+    };
+
+    Ok(pause_vahti(
+        db,
+        vahti,
+        msg.chat.id.0 as u64,
+        crate::delivery::telegram::ID,
+        days,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string()))
+}
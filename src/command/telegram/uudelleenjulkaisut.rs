@@ -0,0 +1,26 @@
+use teloxide::prelude::*;
+
+use crate::database::Database;
+use crate::repost::RepostMode;
+use crate::vahti::set_repost_mode;
+
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let mut args = args.split_whitespace();
+    let Some(vahti) = args.next() else {
+        return Ok(String::from("No url provided"));
+    };
+
+    let Some(mode) = args.next().and_then(RepostMode::parse) else {
+        return Ok(String::from("Expected `pois`, `merkitse` or `piilota`"));
+    };
+
+    Ok(set_repost_mode(
+        db,
+        vahti,
+        msg.chat.id.0 as u64,
+        crate::delivery::telegram::ID,
+        mode,
+    )
+    .await
+    .unwrap_or_else(|e| e.to_string()))
+}
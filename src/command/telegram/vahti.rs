@@ -1,18 +1,32 @@
 use teloxide::prelude::*;
 
 use crate::database::Database;
+use crate::search::query::KeywordQuery;
 use crate::vahti::new_vahti;
 
-pub async fn run(msg: Message, vahti: String, db: Database) -> ResponseResult<String> {
-    if vahti.is_empty() {
-        return Ok(String::from("No url provided"));
-    }
+pub async fn run(msg: Message, args: String, db: Database) -> ResponseResult<String> {
+    let mut words = args.split_whitespace();
+    let Some(vahti) = words.next() else {
+        return Ok(String::from("No url or keywords provided"));
+    };
+
+    // Keyword searches take the whole message, so they can not be given an expiry
+    let (vahti, days) = if KeywordQuery::parse(vahti).is_some() {
+        (args.trim(), None)
+    } else {
+        match words.next().map(|d| d.parse::<u32>()) {
+            Some(Ok(d)) if d > 0 => (vahti, Some(d)),
+            Some(_) => return Ok(String::from("Invalid amount of days")),
+            None => (vahti, None),
+        }
+    };
 
     Ok(new_vahti(
         db,
-        &vahti,
+        vahti,
         msg.chat.id.0 as u64,
         crate::delivery::telegram::ID,
+        days,
     )
     .await
     .unwrap_or_else(|e| e.to_string()))
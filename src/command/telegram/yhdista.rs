@@ -0,0 +1,26 @@
+use teloxide::prelude::*;
+
+use crate::account::{link_account, new_link_code};
+use crate::database::Database;
+
+pub async fn run(msg: Message, code: String, db: Database) -> ResponseResult<String> {
+    let userid = msg.chat.id.0 as u64;
+
+    if code.is_empty() {
+        return Ok(
+            match new_link_code(db, userid, crate::delivery::telegram::ID).await {
+                Ok(code) => format!(
+                    "Your link code is {}. Enter it on the other platform with `/yhdista {}` within 10 minutes",
+                    code, code
+                ),
+                Err(e) => e.to_string(),
+            },
+        );
+    }
+
+    Ok(
+        link_account(db, &code, userid, crate::delivery::telegram::ID)
+            .await
+            .unwrap_or_else(|e| e.to_string()),
+    )
+}
@@ -0,0 +1,386 @@
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use serde::Deserialize;
+use tokio::sync::Notify;
+
+use crate::error::Error;
+
+static CONFIG: LazyLock<RwLock<Arc<Config>>> = LazyLock::new(Default::default);
+
+/// The default location of the configuration file, overridable with `CONFIG_FILE`
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Notified after the configuration has been reloaded
+pub static RELOADED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Notified when a reload of the configuration is requested, e.g. with an admin command
+pub static RELOAD_REQUESTED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Returns the currently active configuration
+pub fn get() -> Arc<Config> {
+    CONFIG.read().unwrap().clone()
+}
+
+/// Replaces the currently active configuration
+pub fn set(config: Config) {
+    *CONFIG.write().unwrap() = Arc::new(config);
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub database_url: String,
+    /// The directory the SQLite database is backed up to before migrations,
+    /// the directory of the database if not set
+    pub backup_dir: Option<String>,
+    /// The interval at which the vahtis are updated in seconds
+    pub update_interval: u64,
+    /// The amount of concurrent futures used while updating and delivering
+    pub futures_max_buffer_size: usize,
+    /// The address the HTTP server listens on, disabled if not set
+    pub http_address: Option<String>,
+    /// The bearer token required by the `/history` endpoint, which is disabled if not set
+    pub http_token: Option<String>,
+    /// The time in seconds deliveries are given to finish during shutdown before
+    /// they are persisted and retried after a restart
+    pub shutdown_timeout: u64,
+    /// A list of `platform:user_id` entries
+    pub admins: Vec<String>,
+    /// The amount of days delivered items are kept in the history, 0 disables the history
+    pub history_retention_days: u32,
+    pub vahti: VahtiConfig,
+    pub discord: DiscordConfig,
+    pub telegram: TelegramConfig,
+    pub tori: SiteConfig,
+    pub huutonet: SiteConfig,
+    pub nettiauto: NettiautoConfig,
+    pub muusikoiden: SiteConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VahtiConfig {
+    pub empty_pause_days: u32,
+    pub expiry_reminder_days: u32,
+    pub extend_days: u32,
+    pub max_per_user: Option<usize>,
+    /// The minimum time between adding vahtis in seconds
+    pub min_interval: i64,
+    /// Items priced at most this percentage of the median price of the vahti are marked as bargains
+    pub bargain_percent: u32,
+    /// How often the watched items are checked for changes, in seconds
+    pub watch_interval: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordConfig {
+    pub enabled: bool,
+    pub token: Option<String>,
+    pub application_id: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelegramConfig {
+    pub enabled: bool,
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SiteConfig {
+    pub enabled: bool,
+}
+
+/// The Nettix API used for Nettiauto requires credentials, so the site is disabled by default
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NettiautoConfig {
+    pub enabled: bool,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_url: String::new(),
+            backup_dir: None,
+            update_interval: 120,
+            futures_max_buffer_size: 50,
+            http_address: None,
+            http_token: None,
+            shutdown_timeout: 10,
+            admins: vec![],
+            history_retention_days: 90,
+            vahti: VahtiConfig::default(),
+            discord: DiscordConfig::default(),
+            telegram: TelegramConfig::default(),
+            tori: SiteConfig::default(),
+            huutonet: SiteConfig::default(),
+            nettiauto: NettiautoConfig::default(),
+            muusikoiden: SiteConfig::default(),
+        }
+    }
+}
+
+impl Default for VahtiConfig {
+    fn default() -> Self {
+        Self {
+            empty_pause_days: 14,
+            expiry_reminder_days: 3,
+            extend_days: 30,
+            max_per_user: None,
+            min_interval: 0,
+            bargain_percent: 70,
+            watch_interval: 3600,
+        }
+    }
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            token: None,
+            application_id: None,
+        }
+    }
+}
+
+impl Default for TelegramConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            token: None,
+        }
+    }
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parse_env<T: FromStr>(name: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::Config(format!("Invalid {}: {}", name, value)))
+}
+
+/// Looks up a non-empty environment variable
+type Env<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+/// Overrides the value with the environment variable `name` if it is set and non-empty
+fn env_override<T: FromStr>(env: Env, name: &str, value: &mut T) -> Result<(), Error> {
+    if let Some(v) = env(name) {
+        *value = parse_env(name, &v)?;
+    }
+    Ok(())
+}
+
+fn env_override_opt<T: FromStr>(env: Env, name: &str, value: &mut Option<T>) -> Result<(), Error> {
+    if let Some(v) = env(name) {
+        *value = Some(parse_env(name, &v)?);
+    }
+    Ok(())
+}
+
+impl Config {
+    /// Reads the configuration file given in `CONFIG_FILE` (or `config.toml` if it exists),
+    /// applies the environment variable overrides and validates the result
+    pub fn load() -> Result<Self, Error> {
+        Self::load_with(&env_var)
+    }
+
+    /// Loads the configuration like `load`, looking up the environment variables with `env`
+    pub fn load_with(env: Env) -> Result<Self, Error> {
+        let explicit = env("CONFIG_FILE");
+        let path = explicit.as_deref().unwrap_or(DEFAULT_CONFIG_FILE);
+
+        let mut config = if explicit.is_some() || Path::new(path).exists() {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| Error::Config(format!("Failed to read {}: {}", path, e)))?;
+            toml::from_str(&contents)
+                .map_err(|e| Error::Config(format!("Failed to parse {}: {}", path, e)))?
+        } else {
+            Config::default()
+        };
+
+        config.apply_env(env)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn apply_env(&mut self, env: Env) -> Result<(), Error> {
+        env_override(env, "DATABASE_URL", &mut self.database_url)?;
+        env_override_opt(env, "BACKUP_DIR", &mut self.backup_dir)?;
+        env_override(env, "UPDATE_INTERVAL", &mut self.update_interval)?;
+        env_override(
+            env,
+            "FUTURES_MAX_BUFFER_SIZE",
+            &mut self.futures_max_buffer_size,
+        )?;
+        env_override_opt(env, "HTTP_ADDRESS", &mut self.http_address)?;
+        env_override_opt(env, "HTTP_TOKEN", &mut self.http_token)?;
+        env_override(env, "SHUTDOWN_TIMEOUT", &mut self.shutdown_timeout)?;
+        if let Some(admins) = env("ADMINS") {
+            self.admins = admins.split(',').map(|a| a.trim().to_string()).collect();
+        }
+
+        env_override(
+            env,
+            "HISTORY_RETENTION_DAYS",
+            &mut self.history_retention_days,
+        )?;
+
+        env_override(
+            env,
+            "EMPTY_VAHTI_PAUSE_DAYS",
+            &mut self.vahti.empty_pause_days,
+        )?;
+        env_override(
+            env,
+            "VAHTI_EXPIRY_REMINDER_DAYS",
+            &mut self.vahti.expiry_reminder_days,
+        )?;
+        env_override(env, "VAHTI_EXTEND_DAYS", &mut self.vahti.extend_days)?;
+        env_override_opt(env, "MAX_VAHTIS_PER_USER", &mut self.vahti.max_per_user)?;
+        env_override(env, "MIN_VAHTI_INTERVAL", &mut self.vahti.min_interval)?;
+        env_override(
+            env,
+            "VAHTI_BARGAIN_PERCENT",
+            &mut self.vahti.bargain_percent,
+        )?;
+        env_override(env, "VAHTI_WATCH_INTERVAL", &mut self.vahti.watch_interval)?;
+
+        env_override(env, "DISCORD_ENABLED", &mut self.discord.enabled)?;
+        env_override_opt(env, "DISCORD_TOKEN", &mut self.discord.token)?;
+        env_override_opt(env, "APPLICATION_ID", &mut self.discord.application_id)?;
+        env_override(env, "TELEGRAM_ENABLED", &mut self.telegram.enabled)?;
+        env_override_opt(env, "TELOXIDE_TOKEN", &mut self.telegram.token)?;
+        env_override(env, "TORI_ENABLED", &mut self.tori.enabled)?;
+        env_override(env, "HUUTONET_ENABLED", &mut self.huutonet.enabled)?;
+        env_override(env, "NETTIAUTO_ENABLED", &mut self.nettiauto.enabled)?;
+        env_override(env, "MUUSIKOIDEN_ENABLED", &mut self.muusikoiden.enabled)?;
+        env_override_opt(env, "NETTIX_CLIENT_ID", &mut self.nettiauto.client_id)?;
+        env_override_opt(
+            env,
+            "NETTIX_CLIENT_SECRET",
+            &mut self.nettiauto.client_secret,
+        )?;
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.database_url.is_empty() {
+            return Err(Error::Config(String::from(
+                "database_url (DATABASE_URL) must be set",
+            )));
+        }
+
+        if self.update_interval == 0 {
+            return Err(Error::Config(String::from(
+                "update_interval (UPDATE_INTERVAL) must be positive",
+            )));
+        }
+
+        if self.futures_max_buffer_size == 0 {
+            return Err(Error::Config(String::from(
+                "futures_max_buffer_size (FUTURES_MAX_BUFFER_SIZE) must be positive",
+            )));
+        }
+
+        if self.vahti.bargain_percent > 100 {
+            return Err(Error::Config(String::from(
+                "vahti.bargain_percent (VAHTI_BARGAIN_PERCENT) must be at most 100",
+            )));
+        }
+
+        if let Some(a) = self
+            .admins
+            .iter()
+            .find(|a| crate::admin::parse_admin(a).is_none())
+        {
+            return Err(Error::Config(format!(
+                "Invalid admin `{}`, expected `platform:user_id`",
+                a
+            )));
+        }
+
+        if self.discord_enabled() && self.discord.token.is_none() {
+            return Err(Error::Config(String::from(
+                "discord.token (DISCORD_TOKEN) must be set when Discord is enabled",
+            )));
+        }
+
+        if cfg!(feature = "discord-command")
+            && self.discord.enabled
+            && self.discord.application_id.is_none()
+        {
+            return Err(Error::Config(String::from(
+                "discord.application_id (APPLICATION_ID) must be set when Discord is enabled",
+            )));
+        }
+
+        if self.telegram_enabled() && self.telegram.token.is_none() {
+            return Err(Error::Config(String::from(
+                "telegram.token (TELOXIDE_TOKEN) must be set when Telegram is enabled",
+            )));
+        }
+
+        if cfg!(feature = "nettiauto")
+            && self.nettiauto.enabled
+            && (self.nettiauto.client_id.is_none() || self.nettiauto.client_secret.is_none())
+        {
+            return Err(Error::Config(String::from(
+                "nettiauto.client_id (NETTIX_CLIENT_ID) and nettiauto.client_secret \
+                 (NETTIX_CLIENT_SECRET) must be set when Nettiauto is enabled",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Whether Discord is both compiled in and enabled
+    pub fn discord_enabled(&self) -> bool {
+        cfg!(any(
+            feature = "discord-delivery",
+            feature = "discord-command"
+        )) && self.discord.enabled
+    }
+
+    /// Whether Telegram is both compiled in and enabled
+    pub fn telegram_enabled(&self) -> bool {
+        cfg!(any(
+            feature = "telegram-delivery",
+            feature = "telegram-command"
+        )) && self.telegram.enabled
+    }
+
+    /// Whether new vahtis can be added and existing ones updated for the site
+    pub fn site_enabled(&self, site_id: i32) -> bool {
+        match site_id {
+            crate::search::ID => crate::search::query::SITES
+                .iter()
+                .any(|s| self.site_enabled(*s)),
+            #[cfg(feature = "tori")]
+            crate::tori::ID => self.tori.enabled,
+            #[cfg(feature = "huutonet")]
+            crate::huutonet::ID => self.huutonet.enabled,
+            #[cfg(feature = "nettiauto")]
+            crate::nettiauto::ID => self.nettiauto.enabled,
+            #[cfg(feature = "muusikoiden")]
+            crate::muusikoiden::ID => self.muusikoiden.enabled,
+            _ => false,
+        }
+    }
+}
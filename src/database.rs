@@ -1,19 +1,29 @@
 use core::time::Duration;
-use std::collections::BTreeMap;
-use std::env;
+use std::collections::{BTreeMap, HashMap};
 
 use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
-use diesel::sqlite::SqliteConnection;
 use serenity::prelude::TypeMapKey;
 
 use crate::error::Error;
+use crate::history::HistoryQuery;
+use crate::metrics;
 use crate::models::*;
 
+sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
+#[cfg(not(feature = "postgres"))]
+no_arg_sql_function!(last_insert_rowid, diesel::sql_types::Integer);
+
+/// The connection type of the database backend selected with the `postgres` feature
+#[cfg(feature = "postgres")]
+pub type DbConnection = diesel::pg::PgConnection;
+#[cfg(not(feature = "postgres"))]
+pub type DbConnection = diesel::sqlite::SqliteConnection;
+
 #[derive(Clone)]
 pub struct Database {
-    database: Pool<ConnectionManager<SqliteConnection>>,
+    database: Pool<ConnectionManager<DbConnection>>,
 }
 
 impl TypeMapKey for Database {
@@ -27,8 +37,9 @@ pub struct ConnectionOptions {
     pub busy_timeout: Option<Duration>,
 }
 
-impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for ConnectionOptions {
-    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+#[cfg(not(feature = "postgres"))]
+impl CustomizeConnection<DbConnection, diesel::r2d2::Error> for ConnectionOptions {
+    fn on_acquire(&self, conn: &mut DbConnection) -> Result<(), diesel::r2d2::Error> {
         (|| {
             if self.enable_wal {
                 conn.batch_execute("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
@@ -45,24 +56,81 @@ impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for ConnectionOp
     }
 }
 
+#[cfg(feature = "postgres")]
+impl CustomizeConnection<DbConnection, diesel::r2d2::Error> for ConnectionOptions {
+    fn on_acquire(&self, conn: &mut DbConnection) -> Result<(), diesel::r2d2::Error> {
+        if let Some(d) = self.busy_timeout {
+            conn.batch_execute(&format!("SET lock_timeout = {};", d.as_millis()))
+                .map_err(diesel::r2d2::Error::QueryError)?;
+        }
+        Ok(())
+    }
+}
 impl Database {
-    pub async fn new() -> Database {
-        dotenv::dotenv().ok();
-
-        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-
-        let manager = ConnectionManager::<SqliteConnection>::new(database_url);
-        let database = Pool::builder()
-            .max_size(16)
-            .connection_customizer(Box::new(ConnectionOptions {
-                enable_wal: true,
-                enable_foreign_keys: false,
-                busy_timeout: Some(Duration::from_secs(30)),
-            }))
-            .build(manager)
-            .expect("Failed to create connection pool");
+    pub async fn new() -> Result<Database, Error> {
+        Self::connect(&crate::config::get().database_url).await
+    }
+
+    pub async fn connect(database_url: &str) -> Result<Database, Error> {
+        let database_url = database_url.to_string();
+        tokio::task::spawn_blocking(move || {
+            // NOTE: The migrations are run on a dedicated connection before creating the pool,
+            // which also switches a new database into WAL mode before the pooled connections race for it
+            let conn = DbConnection::establish(&database_url)?;
+            #[cfg(not(feature = "postgres"))]
+            conn.batch_execute("PRAGMA journal_mode = WAL;")?;
+            crate::migrations::run_migrations(&conn, &database_url)?;
+            drop(conn);
+
+            let manager = ConnectionManager::<DbConnection>::new(database_url);
+            let database = Pool::builder()
+                .max_size(16)
+                .connection_customizer(Box::new(ConnectionOptions {
+                    enable_wal: true,
+                    enable_foreign_keys: false,
+                    busy_timeout: Some(Duration::from_secs(30)),
+                }))
+                .build(manager)?;
+
+            Ok(Self { database })
+        })
+        .await?
+    }
 
-        Self { database }
+    /// Runs the query on the blocking thread pool, so that waiting for a connection
+    /// or a busy database does not stall the async runtime
+    async fn run<T, F>(&self, query: &'static str, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&DbConnection) -> Result<T, Error> + Send + 'static,
+    {
+        let pool = self.database.clone();
+        tokio::task::spawn_blocking(move || {
+            let timer = metrics::DB_QUERY_DURATION
+                .with_label_values(&[query])
+                .start_timer();
+
+            let wait = metrics::DB_CONNECTION_WAIT.start_timer();
+            let conn = pool.get();
+            wait.observe_duration();
+
+            let res = conn.map_err(Error::from).and_then(|c| f(&c));
+            timer.observe_duration();
+            if res.is_err() {
+                metrics::DB_ERRORS.with_label_values(&[query]).inc();
+            }
+            res
+        })
+        .await?
+    }
+
+    /// Checks that a connection can be acquired and a query executed
+    pub async fn ping(&self) -> Result<(), Error> {
+        self.run("ping", |conn| {
+            diesel::sql_query("SELECT 1").execute(conn)?;
+            Ok(())
+        })
+        .await
     }
 
     pub async fn add_vahti_entry(
@@ -71,6 +139,7 @@ impl Database {
         userid: i64,
         site_id: i32,
         delivery_method: i32,
+        expires_at: Option<i64>,
     ) -> Result<usize, Error> {
         let time = chrono::Local::now().timestamp();
         info!("Adding Vahti `{}` for the user {}", arg_url, userid);
@@ -81,10 +150,15 @@ impl Database {
             user_id: userid,
             site_id,
             delivery_method,
+            expires_at,
+            created: time,
         };
-        Ok(diesel::insert_into(Vahdit::table)
-            .values(&new_vahti)
-            .execute(&self.database.get()?)?)
+        self.run("add_vahti_entry", move |conn| {
+            Ok(diesel::insert_into(Vahdit::table)
+                .values(&new_vahti)
+                .execute(conn)?)
+        })
+        .await
     }
 
     pub async fn remove_vahti_entry(
@@ -95,44 +169,536 @@ impl Database {
     ) -> Result<usize, Error> {
         info!("Removing Vahti `{}` from the user {}", arg_url, userid);
         use crate::schema::Vahdit::dsl::*;
-        Ok(diesel::delete(
-            Vahdit.filter(
-                url.eq(arg_url)
-                    .and(user_id.eq(userid))
-                    .and(delivery_method.eq(delivery)),
-            ),
-        )
-        .execute(&self.database.get()?)?)
+        use crate::schema::{ItemPrices, MatchedItems, RepostHistory, VahtiDestinations};
+        let arg_url = arg_url.to_string();
+        self.run("remove_vahti_entry", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                let ids = Vahdit
+                    .filter(
+                        url.eq(&arg_url)
+                            .and(user_id.eq(userid))
+                            .and(delivery_method.eq(delivery)),
+                    )
+                    .select(id)
+                    .load::<i32>(conn)?;
+                diesel::delete(
+                    VahtiDestinations::table.filter(VahtiDestinations::vahti_id.eq_any(&ids)),
+                )
+                .execute(conn)?;
+                diesel::delete(ItemPrices::table.filter(ItemPrices::vahti_id.eq_any(&ids)))
+                    .execute(conn)?;
+                diesel::delete(RepostHistory::table.filter(RepostHistory::vahti_id.eq_any(&ids)))
+                    .execute(conn)?;
+                diesel::delete(MatchedItems::table.filter(MatchedItems::vahti_id.eq_any(&ids)))
+                    .execute(conn)?;
+                Ok(diesel::delete(Vahdit.filter(id.eq_any(&ids))).execute(conn)?)
+            })
+        })
+        .await
+    }
+
+    pub async fn add_vahti_destination(
+        &self,
+        vahtiid: i32,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<usize, Error> {
+        info!(
+            "Adding the user {} as a destination of Vahti {}",
+            userid, vahtiid
+        );
+        use crate::schema::VahtiDestinations;
+        let new_destination = NewVahtiDestination {
+            vahti_id: vahtiid,
+            user_id: userid,
+            delivery_method: delivery,
+        };
+        self.run("add_vahti_destination", move |conn| {
+            Ok(diesel::insert_into(VahtiDestinations::table)
+                .values(&new_destination)
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    pub async fn remove_vahti_destination(
+        &self,
+        vahtiid: i32,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<usize, Error> {
+        info!(
+            "Removing the user {} from the destinations of Vahti {}",
+            userid, vahtiid
+        );
+        use crate::schema::VahtiDestinations::dsl::*;
+        self.run("remove_vahti_destination", move |conn| {
+            Ok(diesel::delete(
+                VahtiDestinations.filter(
+                    vahti_id
+                        .eq(vahtiid)
+                        .and(user_id.eq(userid))
+                        .and(delivery_method.eq(delivery)),
+                ),
+            )
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Stops delivering the items of other users' Vahtis to the given user
+    pub async fn remove_destinations_of_recipient(
+        &self,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<usize, Error> {
+        info!("Removing the user {} from all Vahti destinations", userid);
+        use crate::schema::VahtiDestinations::dsl::*;
+        self.run("remove_destinations_of_recipient", move |conn| {
+            Ok(diesel::delete(
+                VahtiDestinations.filter(user_id.eq(userid).and(delivery_method.eq(delivery))),
+            )
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Returns the additional destinations of every Vahti, keyed by the Vahti id
+    pub async fn fetch_all_vahti_destinations(
+        &self,
+    ) -> Result<HashMap<i32, Vec<(i64, i32)>>, Error> {
+        debug!("Fetching all Vahti destinations...");
+        use crate::schema::VahtiDestinations::dsl::*;
+        let destinations = self
+            .run("fetch_all_vahti_destinations", |conn| {
+                Ok(VahtiDestinations
+                    .select((vahti_id, user_id, delivery_method))
+                    .load::<(i32, i64, i32)>(conn)?)
+            })
+            .await?;
+        Ok(destinations
+            .into_iter()
+            .fold(HashMap::new(), |mut acc, (vid, uid, dm)| {
+                acc.entry(vid).or_insert_with(Vec::new).push((uid, dm));
+                acc
+            }))
+    }
+
+    /// Returns the Vahtis the given user receives items from as an additional destination
+    pub async fn fetch_vahti_entries_by_destination(
+        &self,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<Vec<DbVahti>, Error> {
+        info!("Fetching the destination Vahtis of user {}...", userid);
+        use crate::schema::{Vahdit, VahtiDestinations};
+        self.run("fetch_vahti_entries_by_destination", move |conn| {
+            Ok(Vahdit::table
+                .inner_join(VahtiDestinations::table.on(VahtiDestinations::vahti_id.eq(Vahdit::id)))
+                .filter(
+                    VahtiDestinations::user_id
+                        .eq(userid)
+                        .and(VahtiDestinations::delivery_method.eq(delivery)),
+                )
+                .select(Vahdit::all_columns)
+                .load::<DbVahti>(conn)?)
+        })
+        .await
+    }
+
+    pub async fn move_vahti_entry(
+        &self,
+        arg_url: &str,
+        from: (i64, i32),
+        to: (i64, i32),
+    ) -> Result<usize, Error> {
+        info!(
+            "Moving Vahti `{}` from the user {} to the user {}",
+            arg_url, from.0, to.0
+        );
+        use crate::schema::Vahdit::dsl::*;
+        let arg_url = arg_url.to_string();
+        self.run("move_vahti_entry", move |conn| {
+            Ok(diesel::update(
+                Vahdit.filter(
+                    url.eq(&arg_url)
+                        .and(user_id.eq(from.0))
+                        .and(delivery_method.eq(from.1)),
+                ),
+            )
+            .set((user_id.eq(to.0), delivery_method.eq(to.1)))
+            .execute(conn)?)
+        })
+        .await
     }
 
     pub async fn fetch_vahti_entries_by_url(&self, arg_url: &str) -> Result<Vec<DbVahti>, Error> {
         info!("Fetching Vahtis {}...", arg_url);
         use crate::schema::Vahdit::dsl::*;
-        Ok(Vahdit
-            .filter(url.eq(arg_url))
-            .load::<DbVahti>(&self.database.get()?)?)
+        let arg_url = arg_url.to_string();
+        self.run("fetch_vahti_entries_by_url", move |conn| {
+            Ok(Vahdit.filter(url.eq(&arg_url)).load::<DbVahti>(conn)?)
+        })
+        .await
     }
 
     pub async fn fetch_vahti_entries_by_user_id(&self, userid: i64) -> Result<Vec<DbVahti>, Error> {
         info!("Fetching the Vahtis of user {}...", userid);
         use crate::schema::Vahdit::dsl::*;
-        Ok(Vahdit
-            .filter(user_id.eq(userid))
-            .load::<DbVahti>(&self.database.get()?)?)
+        self.run("fetch_vahti_entries_by_user_id", move |conn| {
+            Ok(Vahdit.filter(user_id.eq(userid)).load::<DbVahti>(conn)?)
+        })
+        .await
     }
 
     pub async fn fetch_vahti(&self, arg_url: &str, userid: i64) -> Result<DbVahti, Error> {
         info!("Fetching the user {}'s Vahti {}...", userid, arg_url);
         use crate::schema::Vahdit::dsl::*;
-        Ok(Vahdit
-            .filter(user_id.eq(userid).and(url.eq(arg_url)))
-            .first::<DbVahti>(&self.database.get()?)?)
+        let arg_url = arg_url.to_string();
+        self.run("fetch_vahti", move |conn| {
+            Ok(Vahdit
+                .filter(user_id.eq(userid).and(url.eq(&arg_url)))
+                .first::<DbVahti>(conn)?)
+        })
+        .await
     }
 
+    /// Fetches all Vahtis that are not paused
     pub async fn fetch_all_vahtis(&self) -> Result<Vec<DbVahti>, Error> {
         info!("Fetching all Vahtis...");
         use crate::schema::Vahdit::dsl::*;
-        Ok(Vahdit.load::<DbVahti>(&self.database.get()?)?)
+        self.run("fetch_all_vahtis", |conn| {
+            Ok(Vahdit.filter(paused.eq(false)).load::<DbVahti>(conn)?)
+        })
+        .await
+    }
+
+    /// Fetches all Vahtis, including the paused ones
+    pub async fn fetch_all_vahtis_with_paused(&self) -> Result<Vec<DbVahti>, Error> {
+        info!("Fetching all Vahtis with the paused ones...");
+        use crate::schema::Vahdit::dsl::*;
+        self.run("fetch_all_vahtis_with_paused", |conn| {
+            Ok(Vahdit.load::<DbVahti>(conn)?)
+        })
+        .await
+    }
+
+    pub async fn pause_vahti(
+        &self,
+        arg_url: &str,
+        userid: i64,
+        delivery: i32,
+        resume: Option<i64>,
+    ) -> Result<usize, Error> {
+        info!("Pausing Vahti `{}` of the user {}", arg_url, userid);
+        use crate::schema::Vahdit::dsl::*;
+        let arg_url = arg_url.to_string();
+        self.run("pause_vahti", move |conn| {
+            Ok(diesel::update(
+                Vahdit.filter(
+                    url.eq(&arg_url)
+                        .and(user_id.eq(userid))
+                        .and(delivery_method.eq(delivery)),
+                ),
+            )
+            .set((paused.eq(true), resume_at.eq(resume)))
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Resumes a paused Vahti. Unless `catch_up` is set, the items
+    /// posted while the Vahti was paused are skipped
+    pub async fn resume_vahti(
+        &self,
+        arg_url: &str,
+        userid: i64,
+        delivery: i32,
+        catch_up: bool,
+    ) -> Result<usize, Error> {
+        info!("Resuming Vahti `{}` of the user {}", arg_url, userid);
+        use crate::schema::Vahdit::dsl::*;
+        let arg_url = arg_url.to_string();
+        self.run("resume_vahti", move |conn| {
+            let target = Vahdit.filter(
+                url.eq(&arg_url)
+                    .and(user_id.eq(userid))
+                    .and(delivery_method.eq(delivery)),
+            );
+            if catch_up {
+                Ok(diesel::update(target)
+                    .set((
+                        paused.eq(false),
+                        resume_at.eq(None::<i64>),
+                        empty_since.eq(None::<i64>),
+                    ))
+                    .execute(conn)?)
+            } else {
+                Ok(diesel::update(target)
+                    .set((
+                        paused.eq(false),
+                        resume_at.eq(None::<i64>),
+                        empty_since.eq(None::<i64>),
+                        last_updated.eq(chrono::Local::now().timestamp()),
+                    ))
+                    .execute(conn)?)
+            }
+        })
+        .await
+    }
+
+    /// Resumes the paused Vahtis whose resume date has passed, skipping
+    /// the items posted while they were paused
+    pub async fn resume_due_vahtis(&self) -> Result<usize, Error> {
+        use crate::schema::Vahdit::dsl::*;
+        let now = chrono::Local::now().timestamp();
+        let count = self
+            .run("resume_due_vahtis", move |conn| {
+                Ok(
+                    diesel::update(Vahdit.filter(paused.eq(true).and(resume_at.le(now))))
+                        .set((
+                            paused.eq(false),
+                            resume_at.eq(None::<i64>),
+                            empty_since.eq(None::<i64>),
+                            last_updated.eq(now),
+                        ))
+                        .execute(conn)?,
+                )
+            })
+            .await?;
+        if count > 0 {
+            info!("Automatically resumed {} Vahtis", count);
+        }
+        Ok(count)
+    }
+
+    pub async fn fetch_vahti_by_id(&self, vahtiid: i32) -> Result<DbVahti, Error> {
+        debug!("Fetching Vahti {}...", vahtiid);
+        use crate::schema::Vahdit::dsl::*;
+        self.run("fetch_vahti_by_id", move |conn| {
+            Ok(Vahdit.filter(id.eq(vahtiid)).first::<DbVahti>(conn)?)
+        })
+        .await
+    }
+
+    /// Pauses the Vahtis with the given ids, returning the amount of paused Vahtis
+    pub async fn pause_vahtis_by_id(&self, ids: &[i32]) -> Result<usize, Error> {
+        info!("Pausing {} Vahtis", ids.len());
+        use crate::schema::Vahdit::dsl::*;
+        let ids = ids.to_vec();
+        self.run("pause_vahtis_by_id", move |conn| {
+            Ok(diesel::update(Vahdit.filter(id.eq_any(ids)))
+                .set((paused.eq(true), resume_at.eq(None::<i64>)))
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Pauses all Vahtis owned by the given recipient
+    pub async fn pause_vahtis_of_recipient(
+        &self,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<usize, Error> {
+        info!("Pausing all Vahtis of the user {}", userid);
+        use crate::schema::Vahdit::dsl::*;
+        self.run("pause_vahtis_of_recipient", move |conn| {
+            Ok(diesel::update(
+                Vahdit.filter(
+                    user_id
+                        .eq(userid)
+                        .and(delivery_method.eq(delivery))
+                        .and(paused.eq(false)),
+                ),
+            )
+            .set((paused.eq(true), resume_at.eq(None::<i64>)))
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Records whether the search of the Vahti returned any results at `time`.
+    /// Only writes to the database when the state changes
+    pub async fn vahti_searched(
+        &self,
+        vahtiid: i32,
+        has_results: bool,
+        time: i64,
+    ) -> Result<usize, Error> {
+        use crate::schema::Vahdit::dsl::*;
+        self.run("vahti_searched", move |conn| {
+            if has_results {
+                Ok(
+                    diesel::update(Vahdit.filter(id.eq(vahtiid).and(empty_since.is_not_null())))
+                        .set(empty_since.eq(None::<i64>))
+                        .execute(conn)?,
+                )
+            } else {
+                Ok(
+                    diesel::update(Vahdit.filter(id.eq(vahtiid).and(empty_since.is_null())))
+                        .set(empty_since.eq(time))
+                        .execute(conn)?,
+                )
+            }
+        })
+        .await
+    }
+
+    /// Fetches the active Vahtis whose search has returned no results since `since`
+    pub async fn fetch_empty_vahtis(&self, since: i64) -> Result<Vec<DbVahti>, Error> {
+        use crate::schema::Vahdit::dsl::*;
+        self.run("fetch_empty_vahtis", move |conn| {
+            Ok(Vahdit
+                .filter(paused.eq(false).and(empty_since.le(since)))
+                .load::<DbVahti>(conn)?)
+        })
+        .await
+    }
+
+    /// Fetches the active Vahtis that expire before `before`
+    pub async fn fetch_expiring_vahtis(&self, before: i64) -> Result<Vec<DbVahti>, Error> {
+        use crate::schema::Vahdit::dsl::*;
+        self.run("fetch_expiring_vahtis", move |conn| {
+            Ok(Vahdit
+                .filter(paused.eq(false).and(expires_at.le(before)))
+                .load::<DbVahti>(conn)?)
+        })
+        .await
+    }
+
+    pub async fn set_vahti_reminded(&self, vahtiid: i32) -> Result<usize, Error> {
+        use crate::schema::Vahdit::dsl::*;
+        self.run("set_vahti_reminded", move |conn| {
+            Ok(diesel::update(Vahdit.filter(id.eq(vahtiid)))
+                .set(reminded.eq(true))
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    pub async fn set_vahti_expiry(
+        &self,
+        vahtiid: i32,
+        expiry: Option<i64>,
+    ) -> Result<usize, Error> {
+        info!("Setting the expiry of Vahti {} to {:?}", vahtiid, expiry);
+        use crate::schema::Vahdit::dsl::*;
+        self.run("set_vahti_expiry", move |conn| {
+            Ok(diesel::update(Vahdit.filter(id.eq(vahtiid)))
+                .set((expires_at.eq(expiry), reminded.eq(false)))
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    pub async fn set_vahti_price_drops(
+        &self,
+        arg_url: &str,
+        userid: i64,
+        delivery: i32,
+        enabled: bool,
+    ) -> Result<usize, Error> {
+        info!(
+            "Setting price drop alerts of Vahti `{}` of the user {} to {}",
+            arg_url, userid, enabled
+        );
+        use crate::schema::Vahdit::dsl::*;
+        let arg_url = arg_url.to_string();
+        self.run("set_vahti_price_drops", move |conn| {
+            Ok(diesel::update(
+                Vahdit.filter(
+                    url.eq(&arg_url)
+                        .and(user_id.eq(userid))
+                        .and(delivery_method.eq(delivery)),
+                ),
+            )
+            .set(price_drops.eq(enabled))
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Sets the maximum distance of the items of the Vahti from the home of the user in kilometers,
+    /// `None` removes the limit
+    pub async fn set_vahti_max_distance(
+        &self,
+        arg_url: &str,
+        userid: i64,
+        delivery: i32,
+        distance: Option<i32>,
+    ) -> Result<usize, Error> {
+        info!(
+            "Setting the maximum distance of Vahti `{}` of the user {} to {:?}",
+            arg_url, userid, distance
+        );
+        use crate::schema::Vahdit::dsl::*;
+        let arg_url = arg_url.to_string();
+        self.run("set_vahti_max_distance", move |conn| {
+            Ok(diesel::update(
+                Vahdit.filter(
+                    url.eq(&arg_url)
+                        .and(user_id.eq(userid))
+                        .and(delivery_method.eq(delivery)),
+                ),
+            )
+            .set(max_distance.eq(distance))
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    pub async fn set_vahti_reposts(
+        &self,
+        arg_url: &str,
+        userid: i64,
+        delivery: i32,
+        mode: i32,
+    ) -> Result<usize, Error> {
+        info!(
+            "Setting the repost mode of Vahti `{}` of the user {} to {}",
+            arg_url, userid, mode
+        );
+        use crate::schema::Vahdit::dsl::*;
+        let arg_url = arg_url.to_string();
+        self.run("set_vahti_reposts", move |conn| {
+            Ok(diesel::update(
+                Vahdit.filter(
+                    url.eq(&arg_url)
+                        .and(user_id.eq(userid))
+                        .and(delivery_method.eq(delivery)),
+                ),
+            )
+            .set(reposts.eq(mode))
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Changes the url of the Vahti, unless its owner already has a Vahti with the new url
+    pub async fn set_vahti_url(&self, vahtiid: i32, new_url: &str) -> Result<usize, Error> {
+        info!("Changing the url of Vahti {} to `{}`", vahtiid, new_url);
+        use crate::schema::Vahdit::dsl::*;
+        let new_url = new_url.to_string();
+        self.run("set_vahti_url", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                let vahti = Vahdit.filter(id.eq(vahtiid)).first::<DbVahti>(conn)?;
+                let existing = Vahdit
+                    .filter(
+                        url.eq(&new_url)
+                            .and(user_id.eq(vahti.user_id))
+                            .and(delivery_method.eq(vahti.delivery_method)),
+                    )
+                    .count()
+                    .get_result::<i64>(conn)?;
+                if existing > 0 {
+                    return Ok(0);
+                }
+                Ok(diesel::update(Vahdit.filter(id.eq(vahtiid)))
+                    .set(url.eq(&new_url))
+                    .execute(conn)?)
+            })
+        })
+        .await
     }
 
     pub async fn fetch_all_vahtis_group(&self) -> Result<BTreeMap<String, Vec<DbVahti>>, Error> {
@@ -147,38 +713,52 @@ impl Database {
         Ok(ret)
     }
 
-    pub async fn vahti_updated(
-        &self,
-        vahti: DbVahti,
-        timestamp: Option<i64>,
-    ) -> Result<usize, Error> {
-        info!(
-            "Updating Vahti {} for the user {}",
-            vahti.url, vahti.user_id
-        );
+    /// Moves the `last_updated` timestamps of the Vahtis forward to the newest
+    /// items found during an update round, given as (vahti_id, timestamp) pairs.
+    /// All the updates are written in a single transaction
+    pub async fn vahtis_updated(&self, updates: Vec<(i32, i64)>) -> Result<usize, Error> {
+        if updates.is_empty() {
+            return Ok(0);
+        }
+
+        info!("Updating the timestamps of {} Vahtis", updates.len());
         use crate::schema::Vahdit::dsl::*;
-        let time = timestamp.unwrap_or_else(|| chrono::Local::now().timestamp());
-        info!(
-            "Newest item {}s ago",
-            chrono::Local::now().timestamp() - time
-        );
-        Ok(diesel::update(
-            Vahdit.filter(
-                url.eq(vahti.url)
-                    .and(user_id.eq(vahti.user_id).and(last_updated.lt(time))),
-            ),
-        )
-        .set(last_updated.eq(time))
-        .execute(&self.database.get()?)?)
+        self.run("vahtis_updated", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                let mut count = 0;
+                for (vahtiid, time) in updates {
+                    count +=
+                        diesel::update(Vahdit.filter(id.eq(vahtiid).and(last_updated.lt(time))))
+                            .set(last_updated.eq(time))
+                            .execute(conn)?;
+                }
+                Ok(count)
+            })
+        })
+        .await
     }
 
-    pub async fn fetch_user_blacklist(&self, userid: i64) -> Result<Vec<(i32, i32)>, Error> {
+    pub async fn fetch_user_blacklist(
+        &self,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<Vec<(i32, i32)>, Error> {
         debug!("Fetching the blacklist for user {}...", userid);
+        let userids = self
+            .fetch_linked_identities(userid, delivery)
+            .await?
+            .into_iter()
+            .map(|(uid, _)| uid)
+            .collect::<Vec<_>>();
         use crate::schema::Blacklists::dsl::*;
-        Ok(Blacklists
-            .filter(user_id.eq(userid))
-            .select((seller_id, site_id))
-            .load::<(i32, i32)>(&self.database.get()?)?)
+        self.run("fetch_user_blacklist", move |conn| {
+            Ok(Blacklists
+                .filter(user_id.eq_any(userids))
+                .select((seller_id, site_id))
+                .distinct()
+                .load::<(i32, i32)>(conn)?)
+        })
+        .await
     }
 
     pub async fn add_seller_to_blacklist(
@@ -197,14 +777,18 @@ impl Database {
             seller_id: sellerid,
             site_id: siteid,
         };
-        Ok(diesel::insert_into(Blacklists::table)
-            .values(new_entry)
-            .execute(&self.database.get()?)?)
+        self.run("add_seller_to_blacklist", move |conn| {
+            Ok(diesel::insert_into(Blacklists::table)
+                .values(new_entry)
+                .execute(conn)?)
+        })
+        .await
     }
 
     pub async fn remove_seller_from_blacklist(
         &self,
         userid: i64,
+        delivery: i32,
         sellerid: i32,
         siteid: i32,
     ) -> Result<usize, Error> {
@@ -212,15 +796,851 @@ impl Database {
             "Removing seller {} from the blacklist of user {}",
             sellerid, userid
         );
+        let userids = self
+            .fetch_linked_identities(userid, delivery)
+            .await?
+            .into_iter()
+            .map(|(uid, _)| uid)
+            .collect::<Vec<_>>();
         use crate::schema::Blacklists::dsl::*;
-        Ok(diesel::delete(
-            Blacklists.filter(
-                user_id
-                    .eq(userid)
-                    .and(seller_id.eq(sellerid))
-                    .and(site_id.eq(siteid)),
-            ),
-        )
-        .execute(&self.database.get()?)?)
+        self.run("remove_seller_from_blacklist", move |conn| {
+            Ok(diesel::delete(
+                Blacklists.filter(
+                    user_id
+                        .eq_any(userids)
+                        .and(seller_id.eq(sellerid))
+                        .and(site_id.eq(siteid)),
+                ),
+            )
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    pub async fn add_block_rule(
+        &self,
+        userid: i64,
+        delivery: i32,
+        rule_kind: &str,
+        rule_value: &str,
+    ) -> Result<usize, Error> {
+        info!("Adding a {} block rule for the user {}", rule_kind, userid);
+        use crate::schema::BlockRules;
+        let new_rule = NewBlockRule {
+            user_id: userid,
+            delivery_method: delivery,
+            kind: rule_kind.to_string(),
+            value: rule_value.to_string(),
+            created: chrono::Local::now().timestamp(),
+        };
+        self.run("add_block_rule", move |conn| {
+            Ok(diesel::insert_into(BlockRules::table)
+                .values(&new_rule)
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Fetches the block rules of the user and all of its linked identities
+    pub async fn fetch_user_block_rules(
+        &self,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<Vec<DbBlockRule>, Error> {
+        debug!("Fetching the block rules for user {}...", userid);
+        let identities = self.fetch_linked_identities(userid, delivery).await?;
+        let userids = identities.iter().map(|(uid, _)| *uid).collect::<Vec<_>>();
+        use crate::schema::BlockRules::dsl::*;
+        Ok(self
+            .run("fetch_user_block_rules", move |conn| {
+                Ok(BlockRules
+                    .filter(user_id.eq_any(userids))
+                    .order(id)
+                    .load::<DbBlockRule>(conn)?)
+            })
+            .await?
+            .into_iter()
+            .filter(|r| identities.contains(&(r.user_id, r.delivery_method)))
+            .collect())
+    }
+
+    /// Removes the block rule if it belongs to the user or one of its linked identities
+    pub async fn remove_block_rule(
+        &self,
+        ruleid: i32,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<usize, Error> {
+        info!("Removing block rule {} of the user {}", ruleid, userid);
+        if !self
+            .fetch_user_block_rules(userid, delivery)
+            .await?
+            .iter()
+            .any(|r| r.id == ruleid)
+        {
+            return Ok(0);
+        }
+
+        use crate::schema::BlockRules::dsl::*;
+        self.run("remove_block_rule", move |conn| {
+            Ok(diesel::delete(BlockRules.filter(id.eq(ruleid))).execute(conn)?)
+        })
+        .await
+    }
+
+    /// Whether the user or any of its linked identities is banned
+    pub async fn is_banned(&self, userid: i64, delivery: i32) -> Result<bool, Error> {
+        let identities = self.fetch_linked_identities(userid, delivery).await?;
+        let userids = identities.iter().map(|(uid, _)| *uid).collect::<Vec<_>>();
+        use crate::schema::Bans::dsl::*;
+        Ok(self
+            .run("is_banned", move |conn| {
+                Ok(Bans
+                    .filter(user_id.eq_any(userids))
+                    .select((user_id, delivery_method))
+                    .load::<(i64, i32)>(conn)?)
+            })
+            .await?
+            .iter()
+            .any(|b| identities.contains(b)))
+    }
+
+    pub async fn add_ban(&self, userid: i64, delivery: i32) -> Result<usize, Error> {
+        info!("Banning the user {}", userid);
+        use crate::schema::Bans;
+        let new_ban = NewBan {
+            user_id: userid,
+            delivery_method: delivery,
+            created: chrono::Local::now().timestamp(),
+        };
+        self.run("add_ban", move |conn| {
+            Ok(diesel::insert_into(Bans::table)
+                .values(&new_ban)
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Removes the bans of the user and all of its linked identities
+    pub async fn remove_ban(&self, userid: i64, delivery: i32) -> Result<usize, Error> {
+        info!("Unbanning the user {}", userid);
+        let identities = self.fetch_linked_identities(userid, delivery).await?;
+        use crate::schema::Bans::dsl::*;
+        self.run("remove_ban", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                let mut removed = 0;
+                for (uid, dm) in identities {
+                    removed +=
+                        diesel::delete(Bans.filter(user_id.eq(uid).and(delivery_method.eq(dm))))
+                            .execute(conn)?;
+                }
+                Ok(removed)
+            })
+        })
+        .await
+    }
+
+    /// Returns the (user_id, delivery_method) pairs of all identities linked
+    /// to the given one, including the identity itself
+    pub async fn fetch_linked_identities(
+        &self,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<Vec<(i64, i32)>, Error> {
+        debug!("Fetching the linked identities of user {}...", userid);
+        use crate::schema::UserIdentities::dsl::*;
+        self.run("fetch_linked_identities", move |conn| {
+            let Some(account) = UserIdentities
+                .filter(user_id.eq(userid).and(delivery_method.eq(delivery)))
+                .select(account_id)
+                .first::<i32>(conn)
+                .optional()?
+            else {
+                return Ok(vec![(userid, delivery)]);
+            };
+
+            Ok(UserIdentities
+                .filter(account_id.eq(account))
+                .select((user_id, delivery_method))
+                .load::<(i64, i32)>(conn)?)
+        })
+        .await
+    }
+
+    pub async fn add_link_code(
+        &self,
+        link_code: &str,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<usize, Error> {
+        info!("Adding a link code for the user {}", userid);
+        use crate::schema::LinkCodes::dsl::*;
+        let new_code = NewLinkCode {
+            code: link_code.to_string(),
+            user_id: userid,
+            delivery_method: delivery,
+            created: chrono::Local::now().timestamp(),
+        };
+        self.run("add_link_code", move |conn| {
+            diesel::delete(LinkCodes.filter(user_id.eq(userid).and(delivery_method.eq(delivery))))
+                .execute(conn)?;
+            Ok(diesel::insert_into(LinkCodes)
+                .values(&new_code)
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Consumes the link code and returns the identity that created it
+    pub async fn take_link_code(
+        &self,
+        link_code: &str,
+        valid_after: i64,
+    ) -> Result<Option<(i64, i32)>, Error> {
+        use crate::schema::LinkCodes::dsl::*;
+        let link_code = link_code.to_string();
+        self.run("take_link_code", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                let entry = LinkCodes
+                    .filter(code.eq(&link_code))
+                    .first::<LinkCode>(conn)
+                    .optional()?;
+                diesel::delete(LinkCodes.filter(code.eq(&link_code).or(created.le(valid_after))))
+                    .execute(conn)?;
+                Ok(entry
+                    .filter(|e| e.created > valid_after)
+                    .map(|e| (e.user_id, e.delivery_method)))
+            })
+        })
+        .await
+    }
+
+    /// Links the two identities into the same account, merging their
+    /// existing accounts if necessary
+    pub async fn link_identities(
+        &self,
+        first: (i64, i32),
+        second: (i64, i32),
+    ) -> Result<(), Error> {
+        info!("Linking user {} with user {}", first.0, second.0);
+        use crate::schema::UserIdentities::dsl::*;
+        use crate::schema::Users;
+        self.run("link_identities", move |conn| {
+            write_transaction(conn, || {
+                let find_account = |(uid, dm): (i64, i32)| {
+                    UserIdentities
+                        .filter(user_id.eq(uid).and(delivery_method.eq(dm)))
+                        .select(account_id)
+                        .first::<i32>(conn)
+                        .optional()
+                };
+
+                let account = match find_account(first)? {
+                    Some(a) => a,
+                    None => {
+                        let a = insert_user(conn)?;
+                        diesel::insert_into(UserIdentities)
+                            .values(&NewUserIdentity {
+                                account_id: a,
+                                user_id: first.0,
+                                delivery_method: first.1,
+                            })
+                            .execute(conn)?;
+                        a
+                    }
+                };
+
+                match find_account(second)? {
+                    Some(a) if a == account => {}
+                    Some(a) => {
+                        diesel::update(UserIdentities.filter(account_id.eq(a)))
+                            .set(account_id.eq(account))
+                            .execute(conn)?;
+                        diesel::delete(Users::table.filter(Users::id.eq(a))).execute(conn)?;
+                    }
+                    None => {
+                        diesel::insert_into(UserIdentities)
+                            .values(&NewUserIdentity {
+                                account_id: account,
+                                user_id: second.0,
+                                delivery_method: second.1,
+                            })
+                            .execute(conn)?;
+                    }
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Sets the home postal code of the user, `None` removes it
+    pub async fn set_home_location(
+        &self,
+        userid: i64,
+        delivery: i32,
+        code: Option<String>,
+    ) -> Result<usize, Error> {
+        info!("Setting the home postal code of the user {}", userid);
+        use crate::schema::HomeLocations::dsl::*;
+        self.run("set_home_location", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                let owner =
+                    HomeLocations.filter(user_id.eq(userid).and(delivery_method.eq(delivery)));
+                let Some(code) = code else {
+                    return Ok(diesel::delete(owner).execute(conn)?);
+                };
+
+                let updated_rows = diesel::update(owner)
+                    .set(postal_code.eq(&code))
+                    .execute(conn)?;
+                if updated_rows > 0 {
+                    return Ok(updated_rows);
+                }
+                Ok(diesel::insert_into(HomeLocations)
+                    .values(&NewHomeLocation {
+                        user_id: userid,
+                        delivery_method: delivery,
+                        postal_code: code,
+                    })
+                    .execute(conn)?)
+            })
+        })
+        .await
+    }
+
+    /// Fetches the home postal code of the user, falling back to the
+    /// ones set by its linked identities
+    pub async fn fetch_home_location(
+        &self,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<Option<String>, Error> {
+        let identities = self.fetch_linked_identities(userid, delivery).await?;
+        let uids = identities.iter().map(|(uid, _)| *uid).collect::<Vec<_>>();
+        use crate::schema::HomeLocations::dsl::*;
+        let homes = self
+            .run("fetch_home_location", move |conn| {
+                Ok(HomeLocations
+                    .filter(user_id.eq_any(uids))
+                    .select((user_id, delivery_method, postal_code))
+                    .load::<(i64, i32, String)>(conn)?)
+            })
+            .await?
+            .into_iter()
+            .filter(|(uid, dm, _)| identities.contains(&(*uid, *dm)))
+            .collect::<Vec<_>>();
+
+        Ok(homes
+            .iter()
+            .find(|(uid, dm, _)| (*uid, *dm) == (userid, delivery))
+            .or_else(|| homes.first())
+            .map(|(_, _, code)| code.clone()))
+    }
+
+    pub async fn fetch_seller(&self, sellerid: i32, siteid: i32) -> Result<Option<Seller>, Error> {
+        use crate::schema::Sellers::dsl::*;
+        self.run("fetch_seller", move |conn| {
+            Ok(Sellers
+                .filter(seller_id.eq(sellerid).and(site_id.eq(siteid)))
+                .first::<Seller>(conn)
+                .optional()?)
+        })
+        .await
+    }
+
+    /// Fetches the cached sellers of the given (seller_id, site_id) pairs
+    pub async fn fetch_sellers(&self, sellers: Vec<(i32, i32)>) -> Result<Vec<Seller>, Error> {
+        use crate::schema::Sellers::dsl::*;
+        let ids = sellers.iter().map(|(sid, _)| *sid).collect::<Vec<_>>();
+        Ok(self
+            .run("fetch_sellers", move |conn| {
+                Ok(Sellers.filter(seller_id.eq_any(ids)).load::<Seller>(conn)?)
+            })
+            .await?
+            .into_iter()
+            .filter(|s| sellers.contains(&(s.seller_id, s.site_id)))
+            .collect())
     }
+
+    /// Inserts or refreshes the cached sellers. Unknown account creation dates
+    /// and ad counts do not overwrite the cached ones
+    pub async fn update_sellers(&self, sellers: Vec<NewSeller>) -> Result<usize, Error> {
+        if sellers.is_empty() {
+            return Ok(0);
+        }
+
+        debug!("Caching {} sellers", sellers.len());
+        use crate::schema::Sellers::dsl::*;
+        self.run("update_sellers", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                for seller in &sellers {
+                    let updated_rows = diesel::update(
+                        Sellers.filter(
+                            seller_id
+                                .eq(seller.seller_id)
+                                .and(site_id.eq(seller.site_id)),
+                        ),
+                    )
+                    .set(seller)
+                    .execute(conn)?;
+                    if updated_rows == 0 {
+                        diesel::insert_into(Sellers).values(seller).execute(conn)?;
+                    }
+                }
+                Ok(sellers.len())
+            })
+        })
+        .await
+    }
+
+    /// Returns the last seen prices of the items delivered by the Vahti,
+    /// keyed by (item_id, site_id)
+    pub async fn fetch_item_prices(&self, vahtiid: i32) -> Result<HashMap<(i64, i32), i64>, Error> {
+        use crate::schema::ItemPrices::dsl::*;
+        self.run("fetch_item_prices", move |conn| {
+            Ok(ItemPrices
+                .filter(vahti_id.eq(vahtiid))
+                .select((item_id, site_id, price))
+                .load::<(i64, i32, i64)>(conn)?
+                .into_iter()
+                .map(|(item, site, p)| ((item, site), p))
+                .collect())
+        })
+        .await
+    }
+
+    /// Inserts or updates the last seen prices of the items
+    pub async fn update_item_prices(&self, prices: Vec<NewItemPrice>) -> Result<usize, Error> {
+        if prices.is_empty() {
+            return Ok(0);
+        }
+
+        debug!("Recording the prices of {} items", prices.len());
+        use crate::schema::ItemPrices::dsl::*;
+        self.run("update_item_prices", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                for p in &prices {
+                    let updated_rows = diesel::update(
+                        ItemPrices.filter(
+                            vahti_id
+                                .eq(p.vahti_id)
+                                .and(item_id.eq(p.item_id))
+                                .and(site_id.eq(p.site_id)),
+                        ),
+                    )
+                    .set((price.eq(p.price), updated.eq(p.updated)))
+                    .execute(conn)?;
+                    if updated_rows == 0 {
+                        diesel::insert_into(ItemPrices).values(p).execute(conn)?;
+                    }
+                }
+                Ok(prices.len())
+            })
+        })
+        .await
+    }
+
+    /// Removes the prices that have not changed since `before`
+    pub async fn purge_item_prices(&self, before: i64) -> Result<usize, Error> {
+        use crate::schema::ItemPrices::dsl::*;
+        self.run("purge_item_prices", move |conn| {
+            Ok(diesel::delete(ItemPrices.filter(updated.lt(before))).execute(conn)?)
+        })
+        .await
+    }
+
+    pub async fn fetch_repost_history(
+        &self,
+        vahtiid: i32,
+    ) -> Result<Vec<RepostHistoryEntry>, Error> {
+        use crate::schema::RepostHistory::dsl::*;
+        self.run("fetch_repost_history", move |conn| {
+            Ok(RepostHistory
+                .filter(vahti_id.eq(vahtiid))
+                .load::<RepostHistoryEntry>(conn)?)
+        })
+        .await
+    }
+
+    /// Records the items seen by a Vahti, refreshing the already known ones
+    pub async fn add_repost_history(
+        &self,
+        entries: Vec<NewRepostHistoryEntry>,
+    ) -> Result<usize, Error> {
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        debug!("Recording {} items in the repost history", entries.len());
+        use crate::schema::RepostHistory::dsl::*;
+        self.run("add_repost_history", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                for e in &entries {
+                    let updated_rows = diesel::update(
+                        RepostHistory.filter(
+                            vahti_id
+                                .eq(e.vahti_id)
+                                .and(item_id.eq(e.item_id))
+                                .and(site_id.eq(e.site_id)),
+                        ),
+                    )
+                    .set((
+                        title.eq(&e.title),
+                        price.eq(e.price),
+                        img_url.eq(&e.img_url),
+                        seen.eq(e.seen),
+                    ))
+                    .execute(conn)?;
+                    if updated_rows == 0 {
+                        diesel::insert_into(RepostHistory).values(e).execute(conn)?;
+                    }
+                }
+                Ok(entries.len())
+            })
+        })
+        .await
+    }
+
+    /// Removes the items last seen before `before` from the repost history
+    pub async fn purge_repost_history(&self, before: i64) -> Result<usize, Error> {
+        use crate::schema::RepostHistory::dsl::*;
+        self.run("purge_repost_history", move |conn| {
+            Ok(diesel::delete(RepostHistory.filter(seen.lt(before))).execute(conn)?)
+        })
+        .await
+    }
+
+    /// Records the items matched by the Vahtis, refreshing the already known ones
+    pub async fn add_matched_items(&self, items: Vec<NewMatchedItem>) -> Result<usize, Error> {
+        if items.is_empty() {
+            return Ok(0);
+        }
+
+        debug!("Recording {} matched items", items.len());
+        use crate::schema::MatchedItems::dsl::*;
+        self.run("add_matched_items", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                for i in &items {
+                    let updated_rows = diesel::update(
+                        MatchedItems.filter(
+                            vahti_id
+                                .eq(i.vahti_id)
+                                .and(item_id.eq(i.item_id))
+                                .and(site_id.eq(i.site_id)),
+                        ),
+                    )
+                    .set((title.eq(&i.title), price.eq(i.price)))
+                    .execute(conn)?;
+                    if updated_rows == 0 {
+                        diesel::insert_into(MatchedItems).values(i).execute(conn)?;
+                    }
+                }
+                Ok(items.len())
+            })
+        })
+        .await
+    }
+
+    /// Fetches the items matched by the Vahti that were published after `since`
+    pub async fn fetch_matched_items(
+        &self,
+        vahtiid: i32,
+        since: i64,
+    ) -> Result<Vec<MatchedItem>, Error> {
+        use crate::schema::MatchedItems::dsl::*;
+        self.run("fetch_matched_items", move |conn| {
+            Ok(MatchedItems
+                .filter(vahti_id.eq(vahtiid).and(published.ge(since)))
+                .load::<MatchedItem>(conn)?)
+        })
+        .await
+    }
+
+    /// Removes the matched items published before `before`
+    pub async fn purge_matched_items(&self, before: i64) -> Result<usize, Error> {
+        use crate::schema::MatchedItems::dsl::*;
+        self.run("purge_matched_items", move |conn| {
+            Ok(diesel::delete(MatchedItems.filter(published.lt(before))).execute(conn)?)
+        })
+        .await
+    }
+
+    pub async fn add_delivered_items(&self, items: Vec<NewDeliveredItem>) -> Result<usize, Error> {
+        if items.is_empty() {
+            return Ok(0);
+        }
+
+        debug!("Recording {} delivered items", items.len());
+        use crate::schema::DeliveredItems;
+        self.run("add_delivered_items", move |conn| {
+            Ok(diesel::insert_into(DeliveredItems::table)
+                .values(&items)
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Searches the items delivered to the user or its linked identities,
+    /// newest first. Returns the requested page and the total amount of matches
+    pub async fn search_delivered_items(
+        &self,
+        userid: i64,
+        delivery: i32,
+        query: HistoryQuery,
+        page_size: i64,
+    ) -> Result<(Vec<DeliveredItem>, i64), Error> {
+        debug!("Searching the delivery history of user {}...", userid);
+        let identities = self.fetch_linked_identities(userid, delivery).await?;
+        use crate::schema::DeliveredItems::dsl::*;
+        self.run("search_delivered_items", move |conn| {
+            let filtered = || {
+                let mut q = DeliveredItems
+                    .filter(false.into_sql::<diesel::sql_types::Bool>())
+                    .into_boxed();
+                for (uid, dm) in &identities {
+                    q = q.or_filter(user_id.eq(*uid).and(delivery_method.eq(*dm)));
+                }
+                if let Some(keyword) = &query.keyword {
+                    let pattern = format!(
+                        "%{}%",
+                        keyword
+                            .to_lowercase()
+                            .replace('\\', "\\\\")
+                            .replace('%', "\\%")
+                            .replace('_', "\\_")
+                    );
+                    q = q.filter(lower(title).like(pattern).escape('\\'));
+                }
+                if let Some(min) = query.min_price {
+                    q = q.filter(price.ge(min));
+                }
+                if let Some(max) = query.max_price {
+                    q = q.filter(price.le(max));
+                }
+                if let Some(site) = query.site_id {
+                    q = q.filter(site_id.eq(site));
+                }
+                if let Some(since) = query.since {
+                    q = q.filter(delivered.ge(since));
+                }
+                if let Some(until) = query.until {
+                    q = q.filter(delivered.lt(until));
+                }
+                q
+            };
+
+            let total = filtered().count().get_result::<i64>(conn)?;
+            let items = filtered()
+                .order((delivered.desc(), id.desc()))
+                .limit(page_size)
+                .offset(i64::from(query.page.saturating_sub(1)) * page_size)
+                .load::<DeliveredItem>(conn)?;
+            Ok((items, total))
+        })
+        .await
+    }
+
+    /// Removes the items delivered before `before` from the history
+    pub async fn purge_delivered_items(&self, before: i64) -> Result<usize, Error> {
+        use crate::schema::DeliveredItems::dsl::*;
+        self.run("purge_delivered_items", move |conn| {
+            Ok(diesel::delete(DeliveredItems.filter(delivered.lt(before))).execute(conn)?)
+        })
+        .await
+    }
+
+    /// Starts watching the item, returns 0 if the user is already watching it
+    pub async fn add_watched_item(&self, item: NewWatchedItem) -> Result<usize, Error> {
+        info!(
+            "Watching item {} of site {} for the user {}",
+            item.listing_id, item.site_id, item.user_id
+        );
+        use crate::schema::WatchedItems::dsl::*;
+        self.run("add_watched_item", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                let existing = WatchedItems
+                    .filter(
+                        user_id
+                            .eq(item.user_id)
+                            .and(delivery_method.eq(item.delivery_method))
+                            .and(site_id.eq(item.site_id))
+                            .and(listing_id.eq(item.listing_id)),
+                    )
+                    .count()
+                    .get_result::<i64>(conn)?;
+                if existing > 0 {
+                    return Ok(0);
+                }
+                Ok(diesel::insert_into(WatchedItems)
+                    .values(&item)
+                    .execute(conn)?)
+            })
+        })
+        .await
+    }
+
+    /// Fetches the watched items last checked before `before`
+    pub async fn fetch_due_watched_items(&self, before: i64) -> Result<Vec<WatchedItem>, Error> {
+        use crate::schema::WatchedItems::dsl::*;
+        self.run("fetch_due_watched_items", move |conn| {
+            Ok(WatchedItems
+                .filter(checked.lt(before))
+                .load::<WatchedItem>(conn)?)
+        })
+        .await
+    }
+
+    pub async fn update_watched_item(
+        &self,
+        watchid: i32,
+        new_title: &str,
+        new_price: i64,
+        checked_at: i64,
+    ) -> Result<usize, Error> {
+        use crate::schema::WatchedItems::dsl::*;
+        let new_title = new_title.to_owned();
+        self.run("update_watched_item", move |conn| {
+            Ok(diesel::update(WatchedItems.filter(id.eq(watchid)))
+                .set((
+                    title.eq(new_title),
+                    price.eq(new_price),
+                    checked.eq(checked_at),
+                ))
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Stops watching the item if it is watched by the user
+    pub async fn remove_watched_item(
+        &self,
+        watchid: i32,
+        userid: i64,
+        delivery: i32,
+    ) -> Result<usize, Error> {
+        info!("Removing watched item {} of the user {}", watchid, userid);
+        use crate::schema::WatchedItems::dsl::*;
+        self.run("remove_watched_item", move |conn| {
+            Ok(diesel::delete(
+                WatchedItems.filter(
+                    id.eq(watchid)
+                        .and(user_id.eq(userid))
+                        .and(delivery_method.eq(delivery)),
+                ),
+            )
+            .execute(conn)?)
+        })
+        .await
+    }
+
+    pub async fn add_pending_deliveries(
+        &self,
+        deliveries: &[NewPendingDelivery],
+    ) -> Result<usize, Error> {
+        info!("Persisting {} pending deliveries", deliveries.len());
+        use crate::schema::PendingDeliveries;
+        let deliveries = deliveries.to_vec();
+        self.run("add_pending_deliveries", move |conn| {
+            Ok(diesel::insert_into(PendingDeliveries::table)
+                .values(&deliveries)
+                .execute(conn)?)
+        })
+        .await
+    }
+
+    /// Fetches and removes all the pending deliveries
+    pub async fn take_pending_deliveries(&self) -> Result<Vec<PendingDelivery>, Error> {
+        use crate::schema::PendingDeliveries::dsl::*;
+        self.run("take_pending_deliveries", |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                let pending = PendingDeliveries.load::<PendingDelivery>(conn)?;
+                diesel::delete(PendingDeliveries).execute(conn)?;
+                Ok(pending)
+            })
+        })
+        .await
+    }
+
+    pub async fn fetch_item_history(&self) -> Result<Vec<ItemHistoryEntry>, Error> {
+        use crate::schema::ItemHistories::dsl::*;
+        self.run("fetch_item_history", |conn| {
+            Ok(ItemHistories.load::<ItemHistoryEntry>(conn)?)
+        })
+        .await
+    }
+
+    /// Replaces the persisted item history with the given entries
+    pub async fn replace_item_history(&self, entries: &[NewItemHistoryEntry]) -> Result<(), Error> {
+        info!("Persisting {} item history entries", entries.len());
+        use crate::schema::ItemHistories::dsl::*;
+        let entries = entries.to_vec();
+        self.run("replace_item_history", move |conn| {
+            conn.transaction::<_, Error, _>(|| {
+                diesel::delete(ItemHistories).execute(conn)?;
+                diesel::insert_into(ItemHistories)
+                    .values(&entries)
+                    .execute(conn)?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Checkpoints the write-ahead log so that the database file is
+    /// self-contained once the pool is dropped
+    pub async fn close(&self) -> Result<(), Error> {
+        info!("Closing the database");
+        self.run("close", |_conn| {
+            #[cfg(not(feature = "postgres"))]
+            _conn.batch_execute("PRAGMA wal_checkpoint(TRUNCATE);")?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+/// Runs a transaction that reads before it writes. On SQLite the write lock is taken
+/// up front, as concurrent transactions could not upgrade their read locks
+#[cfg(feature = "postgres")]
+fn write_transaction<T, F>(conn: &DbConnection, f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error>,
+{
+    conn.transaction(f)
+}
+
+/// Runs a transaction that reads before it writes. On SQLite the write lock is taken
+/// up front, as concurrent transactions could not upgrade their read locks
+#[cfg(not(feature = "postgres"))]
+fn write_transaction<T, F>(conn: &DbConnection, f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error>,
+{
+    conn.immediate_transaction(f)
+}
+
+/// Creates a new account, returning its id
+#[cfg(feature = "postgres")]
+fn insert_user(conn: &DbConnection) -> QueryResult<i32> {
+    use crate::schema::Users;
+    diesel::insert_into(Users::table)
+        .values(&NewUser {
+            created: chrono::Local::now().timestamp(),
+        })
+        .returning(Users::id)
+        .get_result(conn)
+}
+
+/// Creates a new account, returning its id
+#[cfg(not(feature = "postgres"))]
+fn insert_user(conn: &DbConnection) -> QueryResult<i32> {
+    use crate::schema::Users;
+    diesel::insert_into(Users::table)
+        .values(&NewUser {
+            created: chrono::Local::now().timestamp(),
+        })
+        .execute(conn)?;
+    // NOTE: The rowid is per connection, so a concurrent insert can not be read here
+    diesel::select(last_insert_rowid).get_result(conn)
 }
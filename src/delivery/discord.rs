@@ -10,7 +10,7 @@ use serenity::http::Http;
 use serenity::model::application::ButtonStyle;
 use serenity::model::colour::Color;
 
-use crate::delivery::Delivery;
+use crate::delivery::{Delivery, Notification};
 use crate::error::Error;
 use crate::vahti::VahtiItem;
 
@@ -25,8 +25,12 @@ pub struct Discord {
 /// There will be a separate client for handling commands
 impl Discord {
     pub async fn init() -> Result<Self, Error> {
-        let token =
-            std::env::var("DISCORD_TOKEN").expect("Expected DISCORD_TOKEN in the environment");
+        let config = crate::config::get();
+        let token = config
+            .discord
+            .token
+            .clone()
+            .ok_or(Error::Config(String::from("Discord token is not set")))?;
 
         // NOTE: We don't need a serenity::Client because we don't have to listen to events.
         let http = Arc::new(Http::new(&token));
@@ -38,6 +42,25 @@ impl Discord {
 
 impl VahtiItem {
     fn embed(self) -> CreateEmbed {
+        let seller_details = self
+            .seller_details()
+            .map(|d| format!("\n{}", d))
+            .unwrap_or_default();
+        let seller = match self.seller_url() {
+            Some(url) => format!("[{}]({}){}", self.seller_name, url, seller_details),
+            None => format!("{}{}", self.seller_name, seller_details),
+        };
+        let mut price = match self.previous_price {
+            Some(p) => format!("~~{} €~~ {} €", p, self.price),
+            None => format!("{} €", self.price),
+        };
+        if let Some(bargain) = self.bargain_text() {
+            price.push_str(&format!("\n**{}**", bargain));
+        }
+        let e = match self.price_drop_title().or_else(|| self.repost_title()) {
+            Some(title) => CreateEmbed::new().title(title),
+            None => CreateEmbed::new(),
+        };
         match self.site_id {
             #[cfg(feature = "tori")]
             crate::tori::ID => {
@@ -46,20 +69,12 @@ impl VahtiItem {
                     "Annetaan" => Color::BLITZ_BLUE,
                     _ => Color::FADED_PURPLE,
                 };
-
-                let e = CreateEmbed::new()
+                let e = e
                     .color(color)
                     .description(format!("[{}]({})", self.title, self.url))
-                    .field("Hinta", format!("{} €", self.price), true)
-                    .field(
-                        "Myyjä",
-                        format!(
-                            "[{}](https://www.tori.fi/li?&aid={})",
-                            self.seller_name, self.seller_id
-                        ),
-                        true,
-                    )
-                    .field("Sijainti", &self.location, true)
+                    .field("Hinta", &price, true)
+                    .field("Myyjä", seller, true)
+                    .field("Sijainti", self.location_text(), true)
                     .field(
                         "Ilmoitus Jätetty",
                         Local
@@ -81,19 +96,75 @@ impl VahtiItem {
             }
             #[cfg(feature = "huutonet")]
             crate::huutonet::ID => {
-                let e = CreateEmbed::new()
+                let e = e
                     .color(Color::BLUE)
                     .description(format!("[{}]({})", self.title, self.url))
-                    .field("Hinta", format!("{} €", self.price), true)
+                    .field("Hinta", &price, true)
+                    .field("Myyjä", seller, true)
+                    .field("Sijainti", self.location_text(), true)
                     .field(
-                        "Myyjä",
-                        format!(
-                            "[{}](https://www.huuto.net/kayttaja/{})",
-                            &self.seller_name, self.seller_id
-                        ),
+                        "Ilmoitus Jätetty",
+                        Local
+                            .timestamp_opt(self.published, 0)
+                            .unwrap()
+                            .format("%d/%m/%Y %R")
+                            .to_string(),
                         true,
                     )
-                    .field("Sijainti", &self.location, true)
+                    .field("Ilmoitustyyppi", self.ad_type.to_string(), true)
+                    .footer(CreateEmbedFooter::new(
+                        self.vahti_url.expect("bug: impossible"),
+                    ));
+                if !self.img_url.is_empty() {
+                    e.image(&self.img_url)
+                } else {
+                    e
+                }
+            }
+            #[cfg(feature = "nettiauto")]
+            crate::nettiauto::ID => {
+                let mut e = e
+                    .color(Color::ORANGE)
+                    .description(format!("[{}]({})", self.title, self.url))
+                    .field("Hinta", &price, true)
+                    .field("Myyjä", seller, true)
+                    .field("Sijainti", self.location_text(), true);
+                if let Some(vehicle) = &self.vehicle {
+                    if let Some(year) = vehicle.year {
+                        e = e.field("Vuosimalli", year.to_string(), true);
+                    }
+                    if let Some(mileage) = vehicle.mileage {
+                        e = e.field("Mittarilukema", format!("{} km", mileage), true);
+                    }
+                }
+                let e = e
+                    .field(
+                        "Ilmoitus Jätetty",
+                        Local
+                            .timestamp_opt(self.published, 0)
+                            .unwrap()
+                            .format("%d/%m/%Y %R")
+                            .to_string(),
+                        true,
+                    )
+                    .field("Myyjätyyppi", self.ad_type.to_string(), true)
+                    .footer(CreateEmbedFooter::new(
+                        self.vahti_url.expect("bug: impossible"),
+                    ));
+                if !self.img_url.is_empty() {
+                    e.image(&self.img_url)
+                } else {
+                    e
+                }
+            }
+            #[cfg(feature = "muusikoiden")]
+            crate::muusikoiden::ID => {
+                let e = e
+                    .color(Color::DARK_RED)
+                    .description(format!("[{}]({})", self.title, self.url))
+                    .field("Hinta", &price, true)
+                    .field("Myyjä", seller, true)
+                    .field("Sijainti", self.location_text(), true)
                     .field(
                         "Ilmoitus Jätetty",
                         Local
@@ -140,36 +211,109 @@ impl Delivery for Discord {
         let http = self.http.clone();
         let recipient = http
             .get_user(fst.deliver_to.expect("bug: impossible").into())
-            .await?;
+            .await
+            .map_err(|e| unreachable_or(e, fst.deliver_to.unwrap()))?;
 
-        stream::iter(chunks.iter().cloned())
+        let results = stream::iter(chunks.iter().cloned())
             .map(|is| (is, http.clone(), recipient.clone()))
             .map(async move |(items, http, rec)| {
                 let mut message = CreateMessage::new();
+                let mut watch_buttons = vec![];
+                // NOTE: Only the sellers with a public page can be blocked from the menu
+                let blockable = items.iter().any(|i| i.seller_url().is_some());
                 for item in items {
+                    if let Some(id) = item.watch_action_id() {
+                        if !watch_buttons.iter().any(|(i, _)| *i == id) {
+                            watch_buttons.push((id, item.title.clone()));
+                        }
+                    }
                     message = message.add_embed(item.clone().embed());
                 }
-                let buttons = vec![
-                    CreateButton::new("block_seller")
-                        .label("Estä myyjä")
-                        .style(ButtonStyle::Danger),
+                let mut buttons = vec![];
+                if blockable {
+                    buttons.push(
+                        CreateButton::new("block_seller")
+                            .label("Estä myyjä")
+                            .style(ButtonStyle::Danger),
+                    );
+                }
+                buttons.extend([
+                    CreateButton::new("pause_vahti")
+                        .label("Pysäytä vahti")
+                        .style(ButtonStyle::Secondary),
                     CreateButton::new("remove_vahti")
                         .label("Poista vahti")
                         .style(ButtonStyle::Danger),
-                ];
-                let row = CreateActionRow::Buttons(buttons);
+                ]);
+                let mut rows = vec![CreateActionRow::Buttons(buttons)];
+                if !watch_buttons.is_empty() {
+                    rows.push(CreateActionRow::Buttons(
+                        watch_buttons
+                            .into_iter()
+                            .map(|(id, title)| {
+                                // NOTE: Discord limits button labels to 80 characters
+                                let title = title.chars().take(60).collect::<String>();
+                                CreateButton::new(id)
+                                    .label(format!("Seuraa: {}", title))
+                                    .style(ButtonStyle::Primary)
+                            })
+                            .collect(),
+                    ));
+                }
                 if cfg!(feature = "discord-command") {
-                    message = message.components(vec![row]);
+                    message = message.components(rows);
                 }
-                rec.dm(&http, message)
-                    .await
-                    // FIXME: Perhaps don't ignore an error here
-                    .ok()
+                rec.dm(&http, message).await
             })
-            .buffer_unordered(*crate::FUTURES_MAX_BUFFER_SIZE)
+            .buffer_unordered(crate::config::get().futures_max_buffer_size)
             .collect::<Vec<_>>()
             .await;
 
+        // FIXME: Perhaps don't ignore the other errors here
+        if let Some(e) = results.into_iter().filter_map(Result::err).next() {
+            if let Error::RecipientUnreachable(id) = unreachable_or(e, fst.deliver_to.unwrap()) {
+                return Err(Error::RecipientUnreachable(id));
+            }
+        }
+
         Ok(())
     }
+
+    async fn notify(&self, n: Notification) -> Result<(), Error> {
+        info!("Sending a notification to {}", n.deliver_to);
+
+        let recipient = self
+            .http
+            .get_user(n.deliver_to.into())
+            .await
+            .map_err(|e| unreachable_or(e, n.deliver_to))?;
+
+        let mut message = CreateMessage::new().content(&n.message);
+        if let Some(action) = n.action.filter(|_| cfg!(feature = "discord-command")) {
+            message = message.components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
+                action.id(),
+            )
+            .label(action.label())
+            .style(ButtonStyle::Primary)])]);
+        }
+
+        recipient
+            .dm(&self.http, message)
+            .await
+            .map_err(|e| unreachable_or(e, n.deliver_to))?;
+        Ok(())
+    }
+}
+
+/// Discord responds with 403 to direct messages to users who have blocked
+/// the bot or don't share a server with it, and with 404 to deleted users
+fn unreachable_or(e: serenity::Error, recipient: u64) -> Error {
+    match &e {
+        serenity::Error::Http(h)
+            if matches!(h.status_code().map(|c| c.as_u16()), Some(403 | 404)) =>
+        {
+            Error::RecipientUnreachable(recipient)
+        }
+        _ => Error::Discord(e),
+    }
 }
@@ -18,12 +18,53 @@ use crate::vahti::VahtiItem;
 ///
 /// The deliver method should take in a Vec of VahtiItems, all of which
 /// have the same delivery_method and deliver_to fields
+///
+/// The notify method delivers a single free-form Notification, such
+/// as a reminder about an expiring Vahti
+///
+/// Both methods should return `Error::RecipientUnreachable` if the
+/// recipient has blocked the bot or no longer exists
 #[async_trait]
 pub trait Delivery
 where
     Self: Send + Sync,
 {
     async fn deliver(&self, vs: Vec<VahtiItem>) -> Result<(), Error>;
+    async fn notify(&self, n: Notification) -> Result<(), Error>;
+}
+
+/// An action the recipient can take directly from a Notification
+#[derive(Clone, Debug, PartialEq)]
+pub enum NotificationAction {
+    /// Extend the expiry of the Vahti with the given id
+    ExtendVahti(i32),
+    /// Stop watching the watched item with the given id
+    UnwatchItem(i32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    pub deliver_to: u64,
+    pub delivery_method: i32,
+    pub message: String,
+    pub action: Option<NotificationAction>,
+}
+
+impl NotificationAction {
+    /// Identifier used in Discord custom_ids and Telegram callback data
+    pub fn id(&self) -> String {
+        match self {
+            Self::ExtendVahti(id) => format!("extend_vahti_{}", id),
+            Self::UnwatchItem(id) => format!("unwatch_item_{}", id),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ExtendVahti(_) => "Jatka voimassaoloa",
+            Self::UnwatchItem(_) => "Lopeta seuraaminen",
+        }
+    }
 }
 
 pub async fn perform_delivery(
@@ -33,12 +74,22 @@ pub async fn perform_delivery(
     if let Some(v) = vs.first() {
         assert!(vs.iter().all(|vc| vc.delivery_method == v.delivery_method));
 
-        return delivery
-            .get(&v.delivery_method.expect("bug: impossible"))
-            .expect("Missing delivery-method feature")
-            .deliver(vs)
-            .await;
+        let delivery_method = v.delivery_method.expect("bug: impossible");
+        let Some(d) = delivery.get(&delivery_method) else {
+            return Err(Error::DeliveryDisabled(delivery_method));
+        };
+        return d.deliver(vs).await;
     }
 
     Ok(())
 }
+
+pub async fn perform_notification(
+    delivery: Arc<DashMap<i32, Box<dyn Delivery + Sync + Send>>>,
+    n: Notification,
+) -> Result<(), Error> {
+    let Some(d) = delivery.get(&n.delivery_method) else {
+        return Err(Error::DeliveryDisabled(n.delivery_method));
+    };
+    d.notify(n).await
+}
@@ -3,9 +3,10 @@ use chrono::{Local, TimeZone};
 use futures::stream::{self, StreamExt};
 use teloxide::adaptors::throttle::Limits;
 use teloxide::prelude::*;
-use teloxide::types::{ChatId, InputFile, ParseMode};
+use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, ParseMode};
+use teloxide::{ApiError, RequestError};
 
-use crate::delivery::Delivery;
+use crate::delivery::{Delivery, Notification};
 use crate::error::Error;
 use crate::vahti::VahtiItem;
 
@@ -20,8 +21,12 @@ pub const NAME: &str = "telegram";
 /// There will be a separate client for handling commands
 impl Telegram {
     pub async fn init() -> Result<Self, Error> {
-        let token =
-            std::env::var("TELOXIDE_TOKEN").expect("Expected TELOXIDE_TOKEN in the environment");
+        let config = crate::config::get();
+        let token = config
+            .telegram
+            .token
+            .clone()
+            .ok_or(Error::Config(String::from("Telegram token is not set")))?;
         let bot = Bot::new(token);
         Ok(Self { bot })
     }
@@ -31,28 +36,44 @@ impl Telegram {
 
 impl VahtiItem {
     fn format_telegram(self) -> String {
-        let sellerurl = match self.site_id {
-            #[cfg(feature = "tori")]
-            crate::tori::ID => {
-                format!("https://www.tori.fi/li?&aid={}", self.seller_id)
-            }
-            #[cfg(feature = "huutonet")]
-            crate::huutonet::ID => {
-                format!("https://www.huuto.net/kayttaja/{}", self.seller_id)
-            }
-            i => panic!("Unsupported site_id {}", i),
+        let seller = match self.seller_url() {
+            Some(url) => format!(r#"<a href="{}">{}</a>"#, url, self.seller_name),
+            None => self.seller_name.clone(),
         };
 
-        let mut msg = format!(r#"<a href="{}">{}</a>"#, self.url, self.title) + "\n";
-        msg.push_str((format!(r#"<b>Hinta</b>: {}€"#, self.price) + "\n").as_str());
+        let mut msg = self
+            .price_drop_title()
+            .or_else(|| self.repost_title())
+            .map(|t| format!("<b>{}</b>\n", t))
+            .unwrap_or_default();
+        msg.push_str(&(format!(r#"<a href="{}">{}</a>"#, self.url, self.title) + "\n"));
+        let mut price = match self.previous_price {
+            Some(p) => format!("<s>{}€</s> {}€", p, self.price),
+            None => format!("{}€", self.price),
+        };
+        if let Some(bargain) = self.bargain_text() {
+            price.push_str(&format!(" (<b>{}</b>)", bargain));
+        }
+        msg.push_str((format!(r#"<b>Hinta</b>: {}"#, price) + "\n").as_str());
         msg.push_str(
             (format!(
-                r#"<b>Myyjä</b>: <a href="{}">{}</a>"#,
-                sellerurl, self.seller_name
+                r#"<b>Myyjä</b>: {}{}"#,
+                seller,
+                self.seller_details()
+                    .map(|d| format!(" ({})", d))
+                    .unwrap_or_default()
             ) + "\n")
                 .as_str(),
         );
-        msg.push_str((format!(r#"<b>Sijainti</b>: {}"#, self.location) + "\n").as_str());
+        msg.push_str((format!(r#"<b>Sijainti</b>: {}"#, self.location_text()) + "\n").as_str());
+        if let Some(vehicle) = &self.vehicle {
+            if let Some(year) = vehicle.year {
+                msg.push_str((format!(r#"<b>Vuosimalli</b>: {}"#, year) + "\n").as_str());
+            }
+            if let Some(mileage) = vehicle.mileage {
+                msg.push_str((format!(r#"<b>Mittarilukema</b>: {} km"#, mileage) + "\n").as_str());
+            }
+        }
         msg.push_str(
             (format!(
                 r#"<b>Ilmoitus jätetty</b>: {}"#,
@@ -90,28 +111,69 @@ impl Delivery for Telegram {
 
         let recipient = ChatId(fst.deliver_to.unwrap() as i64);
 
-        stream::iter(items.iter().cloned())
-            .map(async move |i| {
+        let results = stream::iter(items.iter().cloned())
+            .map(|i| async move {
                 let file = if i.img_url.is_empty() {
                     InputFile::file("./media/no_image.jpg")
                 } else {
                     InputFile::url(url::Url::parse(&i.img_url).unwrap())
                 };
 
-                self.bot
+                let mut request = self
+                    .bot
                     .clone()
                     .throttle(Limits::default())
                     .send_photo(recipient, file)
                     .caption(i.clone().format_telegram())
-                    .parse_mode(ParseMode::Html)
-                    .await
-                    // FIXME: Perhaps don't ignore an error here
-                    .ok()
+                    .parse_mode(ParseMode::Html);
+                if let Some(id) = i
+                    .watch_action_id()
+                    .filter(|_| cfg!(feature = "telegram-command"))
+                {
+                    request = request.reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                        InlineKeyboardButton::callback("Seuraa ilmoitusta", id),
+                    ]]));
+                }
+                request.await
             })
-            .buffer_unordered(*crate::FUTURES_MAX_BUFFER_SIZE)
+            .buffer_unordered(crate::config::get().futures_max_buffer_size)
             .collect::<Vec<_>>()
             .await;
 
+        // FIXME: Perhaps don't ignore the other errors here
+        if let Some(e) = results.into_iter().filter_map(Result::err).next() {
+            if let Error::RecipientUnreachable(id) = unreachable_or(e, fst.deliver_to.unwrap()) {
+                return Err(Error::RecipientUnreachable(id));
+            }
+        }
+
         Ok(())
     }
+
+    async fn notify(&self, n: Notification) -> Result<(), Error> {
+        info!("Sending a notification to {}", n.deliver_to);
+
+        let bot = self.bot.clone().throttle(Limits::default());
+        let mut request = bot.send_message(ChatId(n.deliver_to as i64), &n.message);
+        if let Some(action) = n.action.filter(|_| cfg!(feature = "telegram-command")) {
+            request = request.reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback(action.label(), action.id()),
+            ]]));
+        }
+
+        request.await.map_err(|e| unreachable_or(e, n.deliver_to))?;
+        Ok(())
+    }
+}
+
+fn unreachable_or(e: RequestError, recipient: u64) -> Error {
+    match e {
+        RequestError::Api(
+            ApiError::BotBlocked
+            | ApiError::BotKicked
+            | ApiError::ChatNotFound
+            | ApiError::UserDeactivated,
+        ) => Error::RecipientUnreachable(recipient),
+        e => Error::Telegram(e),
+    }
 }
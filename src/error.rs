@@ -8,16 +8,48 @@ pub enum Error {
     Discord(#[from] serenity::Error),
     #[error("Database error {0}")]
     Database(#[from] diesel::result::Error),
+    #[error("Database connection error {0}")]
+    DbConnection(#[from] diesel::ConnectionError),
+    #[error("Migration error {0}")]
+    Migration(#[from] diesel_migrations::RunMigrationsError),
+    #[error("The database has migrations unknown to this version: {0}, refusing to start")]
+    SchemaTooNew(String),
+    #[error("Database task failed {0}")]
+    DbTask(#[from] tokio::task::JoinError),
     #[error("Database Pool error {0}")]
     DbPool(#[from] r2d2::Error),
     #[error("Unknown url passed: {0}")]
     UnknownUrl(String),
+    #[error("Unsupported site {0}")]
+    UnsupportedSite(i32),
     #[error("Json Error {0}")]
     Serde(#[from] serde_json::Error),
     #[error("Reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
     #[error("The specified Vahti already exists")]
     VahtiExists,
+    #[error("You have reached the maximum amount of {0} Vahtis")]
+    VahtiLimit(usize),
+    #[error("Please wait {0} seconds before adding a new Vahti")]
+    VahtiRateLimit(i64),
+    #[error("You have been banned from using the bot")]
+    Banned,
+    #[error("Only admins can use this command")]
+    NotAdmin,
     #[error("Invalid Item passed")]
     InvalidItem,
+    #[error("Invalid block rule, expected one of `yritykset`, `myyja [nimi]`, `sijainti [sijainti]` or `hakusana [sana]`")]
+    InvalidBlockRule,
+    #[error("Invalid history search: {0}")]
+    InvalidHistoryQuery(String),
+    #[error("Invalid or expired link code")]
+    InvalidLinkCode,
+    #[error("The recipient {0} cannot be reached")]
+    RecipientUnreachable(u64),
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+    #[error("Delivery method {0} is not enabled")]
+    DeliveryDisabled(i32),
+    #[error("Telegram error {0}")]
+    Telegram(#[from] teloxide::RequestError),
 }
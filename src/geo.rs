@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::database::Database;
+use crate::error::Error;
+use crate::vahti::VahtiItem;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// The centroids of the Finnish postal code areas, see `utils/postal_codes.sh`
+static POSTAL_CODES: LazyLock<HashMap<String, (f64, f64)>> =
+    LazyLock::new(|| parse_postal_codes(include_str!("../data/postal_codes.csv")));
+
+/// Parses the `postal_code,latitude,longitude` rows of the dataset. Every prefix of the codes
+/// gets the average centroid of the areas starting with it, which is used for the codes
+/// missing from the dataset, e.g. the ones of new areas
+pub fn parse_postal_codes(csv: &str) -> HashMap<String, (f64, f64)> {
+    let areas = csv
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let code = fields.next()?;
+            let lat = fields.next()?.parse().ok()?;
+            let lon = fields.next()?.parse().ok()?;
+            Some((code, (lat, lon)))
+        })
+        .collect::<Vec<(&str, (f64, f64))>>();
+
+    let mut prefixes: HashMap<&str, (f64, f64, usize)> = HashMap::new();
+    for (code, (lat, lon)) in &areas {
+        for len in 2..code.len() {
+            let sum = prefixes.entry(&code[..len]).or_default();
+            *sum = (sum.0 + lat, sum.1 + lon, sum.2 + 1);
+        }
+    }
+
+    // NOTE: The areas of the dataset override the averages of the prefixes
+    prefixes
+        .into_iter()
+        .map(|(prefix, (lat, lon, n))| (prefix, (lat / n as f64, lon / n as f64)))
+        .chain(areas)
+        .map(|(code, centroid)| (code.to_string(), centroid))
+        .collect()
+}
+
+pub fn is_postal_code(code: &str) -> bool {
+    code.len() == 5 && code.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The (latitude, longitude) of the postal code area, using the
+/// longest prefix of the code found in the dataset for unknown codes
+pub fn centroid(code: &str) -> Option<(f64, f64)> {
+    if !is_postal_code(code) {
+        return None;
+    }
+    (2..=5)
+        .rev()
+        .find_map(|len| POSTAL_CODES.get(&code[..len]).copied())
+}
+
+/// The great-circle distance between the two points in kilometers
+pub fn distance((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    let (dlat, dlon) = ((lat2 - lat1).to_radians(), (lon2 - lon1).to_radians());
+    let a = (dlat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// The distance between the two postal code areas in whole kilometers
+pub fn postal_code_distance(from: &str, to: &str) -> Option<i64> {
+    Some(distance(centroid(from)?, centroid(to)?).round() as i64)
+}
+
+impl VahtiItem {
+    /// The distance of the item from the postal code area in whole kilometers,
+    /// using the postal code of the item and falling back to its coordinates
+    pub fn distance_from(&self, code: &str) -> Option<i64> {
+        match (&self.postal_code, self.coordinates) {
+            (Some(c), _) if centroid(c).is_some() => postal_code_distance(code, c),
+            (_, Some(coordinates)) => Some(distance(centroid(code)?, coordinates).round() as i64),
+            _ => None,
+        }
+    }
+}
+
+impl VahtiItem {
+    /// The location of the item with its distance from the home of the recipient
+    pub fn location_text(&self) -> String {
+        match self.distance {
+            Some(d) => format!("{} ({} km)", self.location, d),
+            None => self.location.clone(),
+        }
+    }
+}
+
+/// Sets the distances of the items from the home of the user and removes the items
+/// farther away than `max_distance`. Items without a known location are kept
+pub async fn apply_distances(
+    db: &Database,
+    userid: u64,
+    delivery_method: i32,
+    max_distance: Option<i32>,
+    items: Vec<VahtiItem>,
+) -> Result<Vec<VahtiItem>, Error> {
+    if items.is_empty() {
+        return Ok(items);
+    }
+    let Some(home) = db
+        .fetch_home_location(userid as i64, delivery_method)
+        .await?
+    else {
+        return Ok(items);
+    };
+
+    Ok(items
+        .into_iter()
+        .filter_map(|mut i| {
+            i.distance = i.distance_from(&home);
+            match (max_distance, i.distance) {
+                (Some(max), Some(d)) if d > i64::from(max) => None,
+                _ => Some(i),
+            }
+        })
+        .collect())
+}
+
+/// Sets the home postal code of the user, `None` removes it
+pub async fn set_home(
+    db: Database,
+    userid: u64,
+    delivery_method: i32,
+    code: Option<&str>,
+) -> Result<String, Error> {
+    let Some(code) = code else {
+        db.set_home_location(userid as i64, delivery_method, None)
+            .await?;
+        return Ok(String::from("Home postal code removed!"));
+    };
+
+    if centroid(code).is_none() {
+        return Ok(format!("Unknown postal code `{}`", code));
+    }
+    db.set_home_location(userid as i64, delivery_method, Some(code.to_string()))
+        .await?;
+    Ok(format!("Home postal code set to {}!", code))
+}
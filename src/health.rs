@@ -0,0 +1,100 @@
+use std::fmt;
+use std::sync::RwLock;
+
+use dashmap::DashMap;
+use serde_json::{json, Value};
+
+use crate::Torimies;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CommanderStatus {
+    Starting,
+    Running,
+    Restarting,
+    Stopped,
+}
+
+impl fmt::Display for CommanderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            CommanderStatus::Starting => "starting",
+            CommanderStatus::Running => "running",
+            CommanderStatus::Restarting => "restarting",
+            CommanderStatus::Stopped => "stopped",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Keeps track of the state reported by the health endpoints
+pub struct Health {
+    pub started: i64,
+    pub last_update: RwLock<Option<i64>>,
+    pub commanders: DashMap<String, CommanderStatus>,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Self {
+            started: chrono::Local::now().timestamp(),
+            last_update: RwLock::new(None),
+            commanders: DashMap::new(),
+        }
+    }
+
+    pub fn update_succeeded(&self) {
+        *self.last_update.write().unwrap() = Some(chrono::Local::now().timestamp());
+    }
+
+    pub fn set_commander_status(&self, name: &str, status: CommanderStatus) {
+        self.commanders.insert(name.to_string(), status);
+    }
+
+    /// The update loop is considered stalled if there has not been a
+    /// successful update in three update intervals
+    pub fn update_stalled(&self) -> bool {
+        let last = self.last_update.read().unwrap().unwrap_or(self.started);
+        chrono::Local::now().timestamp() - last > 3 * crate::config::get().update_interval as i64
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Torimies {
+    /// Returns whether the bot is healthy and a report describing its state
+    pub async fn health_report(&self) -> (bool, Value) {
+        let database = self.database.ping().await.is_ok();
+        let stalled = self.health.update_stalled();
+
+        let report = json!({
+            "last_update": *self.health.last_update.read().unwrap(),
+            "update_stalled": stalled,
+            "database": database,
+            "commanders": self
+                .health
+                .commanders
+                .iter()
+                .map(|c| (c.key().clone(), Value::from(c.value().to_string())))
+                .collect::<serde_json::Map<_, _>>(),
+        });
+
+        (database && !stalled, report)
+    }
+
+    /// Returns whether the bot is ready to serve users and a report describing its state
+    pub async fn readiness_report(&self) -> (bool, Value) {
+        let (healthy, report) = self.health_report().await;
+        let updated = self.health.last_update.read().unwrap().is_some();
+        let commanders = self
+            .health
+            .commanders
+            .iter()
+            .all(|c| *c.value() == CommanderStatus::Running);
+
+        (healthy && updated && commanders, report)
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::{Local, NaiveDate, TimeZone};
+
+use crate::database::Database;
+use crate::error::Error;
+use crate::models::{DeliveredItem, NewDeliveredItem};
+use crate::vahti::VahtiItem;
+
+/// The amount of items on a single page of search results
+pub const PAGE_SIZE: i64 = 10;
+
+/// A search over the delivered items of a user, all the filters are optional
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryQuery {
+    pub keyword: Option<String>,
+    pub min_price: Option<i64>,
+    pub max_price: Option<i64>,
+    pub site_id: Option<i32>,
+    /// Only items delivered at or after this timestamp
+    pub since: Option<i64>,
+    /// Only items delivered before this timestamp
+    pub until: Option<i64>,
+    /// The page of the results, starting from 1
+    pub page: u32,
+}
+
+impl Default for HistoryQuery {
+    fn default() -> Self {
+        Self {
+            keyword: None,
+            min_price: None,
+            max_price: None,
+            site_id: None,
+            since: None,
+            until: None,
+            page: 1,
+        }
+    }
+}
+
+pub fn parse_site(site: &str) -> Result<i32, Error> {
+    match site.to_lowercase().as_str() {
+        #[cfg(feature = "tori")]
+        crate::tori::NAME => Ok(crate::tori::ID),
+        #[cfg(feature = "huutonet")]
+        crate::huutonet::NAME => Ok(crate::huutonet::ID),
+        #[cfg(feature = "nettiauto")]
+        crate::nettiauto::NAME => Ok(crate::nettiauto::ID),
+        #[cfg(feature = "muusikoiden")]
+        crate::muusikoiden::NAME => Ok(crate::muusikoiden::ID),
+        _ => Err(Error::InvalidHistoryQuery(format!(
+            "unknown site `{}`",
+            site
+        ))),
+    }
+}
+
+/// Parses a date given as `dd.mm.yyyy` or `yyyy-mm-dd` into the timestamp
+/// of the start of the day, or the end of the day if `end_of_day` is set
+pub fn parse_date(date: &str, end_of_day: bool) -> Result<i64, Error> {
+    let day = NaiveDate::parse_from_str(date, "%d.%m.%Y")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .map_err(|_| Error::InvalidHistoryQuery(format!("invalid date `{}`", date)))?;
+    let day = if end_of_day {
+        day.succ_opt().unwrap_or(day)
+    } else {
+        day
+    };
+
+    Local
+        .from_local_datetime(&day.and_hms_opt(0, 0, 0).expect("bug: impossible"))
+        .earliest()
+        .map(|d| d.timestamp())
+        .ok_or_else(|| Error::InvalidHistoryQuery(format!("invalid date `{}`", date)))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::InvalidHistoryQuery(format!("invalid number `{}`", value)))
+}
+
+impl HistoryQuery {
+    /// Parses a search given as command arguments, e.g.
+    /// `polkupyörä min=50 max=200 sivusto=tori alkaen=1.10.2026 asti=15.10.2026 sivu=2`.
+    /// The words without a filter form the keyword
+    pub fn parse(args: &str) -> Result<Self, Error> {
+        let mut query = Self::default();
+        let mut keyword = vec![];
+        for arg in args.split_whitespace() {
+            match arg.split_once('=') {
+                Some(("min", v)) => query.min_price = Some(parse_number(v)?),
+                Some(("max", v)) => query.max_price = Some(parse_number(v)?),
+                Some(("sivusto", v)) => query.site_id = Some(parse_site(v)?),
+                Some(("alkaen", v)) => query.since = Some(parse_date(v, false)?),
+                Some(("asti", v)) => query.until = Some(parse_date(v, true)?),
+                Some(("sivu", v)) => query.page = parse_number(v)?,
+                _ => keyword.push(arg),
+            }
+        }
+
+        if !keyword.is_empty() {
+            query.keyword = Some(keyword.join(" "));
+        }
+        if query.page == 0 {
+            return Err(Error::InvalidHistoryQuery(String::from(
+                "the pages start from 1",
+            )));
+        }
+        Ok(query)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HistoryPage {
+    pub items: Vec<DeliveredItem>,
+    pub page: u32,
+    pub pages: u32,
+    pub total: i64,
+}
+
+impl HistoryPage {
+    fn format(&self, item: impl Fn(&DeliveredItem) -> String) -> String {
+        if self.items.is_empty() {
+            return String::from("No delivered items found");
+        }
+
+        let mut msg = format!(
+            "Page {}/{} of {} delivered items:\n",
+            self.page, self.pages, self.total
+        );
+        for i in &self.items {
+            msg.push_str(&item(i));
+            msg.push('\n');
+        }
+        msg
+    }
+
+    /// Formats the page with Markdown links for Discord
+    pub fn format_markdown(&self) -> String {
+        self.format(|i| {
+            format!(
+                "{} [{}](<{}>) {} €",
+                format_date(i.delivered),
+                i.title,
+                i.url,
+                i.price
+            )
+        })
+    }
+
+    /// Formats the page as plain text with the links on their own lines
+    pub fn format_plain(&self) -> String {
+        self.format(|i| {
+            format!(
+                "{} {} {} €\n{}",
+                format_date(i.delivered),
+                i.title,
+                i.price,
+                i.url
+            )
+        })
+    }
+}
+
+fn format_date(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .unwrap()
+        .format("%d/%m/%Y %R")
+        .to_string()
+}
+
+/// Records successfully delivered items in the delivery history
+pub async fn record_deliveries(db: &Database, items: &[VahtiItem]) {
+    if crate::config::get().history_retention_days == 0 {
+        return;
+    }
+
+    let now = chrono::Local::now().timestamp();
+    let entries = items
+        .iter()
+        .map(|i| NewDeliveredItem {
+            user_id: i.deliver_to.expect("bug: impossible") as i64,
+            delivery_method: i.delivery_method.expect("bug: impossible"),
+            site_id: i.site_id,
+            item_id: i.ad_id,
+            title: i.title.clone(),
+            url: i.url.clone(),
+            vahti_url: i.vahti_url.clone().unwrap_or_default(),
+            price: i.price,
+            location: i.location.clone(),
+            seller_name: i.seller_name.clone(),
+            published: i.published,
+            delivered: now,
+        })
+        .collect();
+
+    if let Err(e) = db.add_delivered_items(entries).await {
+        error!("Failed to record delivered items: {}", e);
+    }
+}
+
+pub async fn search_history(
+    db: &Database,
+    userid: u64,
+    delivery_method: i32,
+    query: HistoryQuery,
+) -> Result<HistoryPage, Error> {
+    let page = query.page;
+    let (items, total) = db
+        .search_delivered_items(userid as i64, delivery_method, query, PAGE_SIZE)
+        .await?;
+
+    Ok(HistoryPage {
+        items,
+        page,
+        pages: (total.max(1) as u64).div_ceil(PAGE_SIZE as u64) as u32,
+        total,
+    })
+}
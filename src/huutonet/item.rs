@@ -0,0 +1,32 @@
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use super::models::FullHuutonetItem;
+use crate::error::Error;
+use crate::vahti::VahtiItem;
+use crate::watch::ItemStatus;
+
+/// Parses the status of an item from the item endpoint of the Huuto.net API.
+/// A closed item counts as sold only if somebody bid on it
+pub fn parse_item(json: &str) -> Result<ItemStatus, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    match response_json["status"].as_str() {
+        Some("published") => {
+            let fullitem: FullHuutonetItem = serde_json::from_value(response_json)?;
+            Ok(ItemStatus::Available(Box::new(VahtiItem::from(fullitem))))
+        }
+        Some("closed") if response_json["bidderCount"].as_i64().unwrap_or(0) > 0 => {
+            Ok(ItemStatus::Sold)
+        }
+        _ => Ok(ItemStatus::Removed),
+    }
+}
+
+pub async fn fetch_item(itemid: i64) -> Result<ItemStatus, Error> {
+    let url = format!("https://api.huuto.net/1.1/items/{}", itemid);
+    let response = reqwest::get(&url).await?;
+    if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+        return Ok(ItemStatus::Removed);
+    }
+    parse_item(&response.error_for_status()?.text().await?)
+}
@@ -1,4 +1,5 @@
 pub mod api;
+pub mod item;
 mod models;
 pub mod parse;
 pub mod seller;
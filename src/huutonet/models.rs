@@ -70,7 +70,17 @@ impl From<FullHuutonetItem> for VahtiItem {
             published,
             price: h.current_price.round() as i64,
             seller_name: h.seller,
-            seller_id: h.seller_id,
+            seller_id: Some(h.seller_id),
+            seller_created: None,
+            seller_ads: None,
+            company_ad: false,
+            previous_price: None,
+            repost: None,
+            median_price: None,
+            postal_code: h.postal_code,
+            coordinates: None,
+            distance: None,
+            vehicle: None,
             location: h.location,
             ad_type: h.sale_method,
             ad_id: h.id,
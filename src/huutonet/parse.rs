@@ -20,3 +20,11 @@ pub fn api_parse_after(search: &str, after: i64) -> Result<Vec<VahtiItem>, Error
     debug!("Parsed {} items", items.len());
     Ok(items)
 }
+
+/// Returns the total amount of items matching the search
+pub fn api_result_count(search: &str) -> Result<i64, Error> {
+    let response_json: Value = serde_json::from_str(search)?;
+    response_json["totalCount"]
+        .as_i64()
+        .ok_or(Error::InvalidItem)
+}
@@ -1,10 +1,23 @@
 use serde_json::Value;
 
 use crate::error::Error;
+use crate::models::NewSeller;
 
-pub async fn get_seller_name_from_id(sellerid: i32) -> Result<String, Error> {
+/// Parses the seller from the user endpoint of the Huuto.net API
+pub fn parse_seller(sellerid: i32, json: &str) -> Result<Option<NewSeller>, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    Ok(response_json["username"].as_str().map(|name| NewSeller {
+        seller_id: sellerid,
+        site_id: super::ID,
+        name: name.to_string(),
+        account_created: None,
+        ad_count: None,
+        updated: chrono::Local::now().timestamp(),
+    }))
+}
+
+pub async fn fetch_seller(sellerid: i32) -> Result<Option<NewSeller>, Error> {
     let url = format!("https://api.huuto.net/1.1/users/{}", sellerid);
     let response = reqwest::get(&url).await?.text().await?;
-    let response_json: Value = serde_json::from_str(&response)?;
-    Ok(response_json["username"].to_string())
+    parse_seller(sellerid, &response)
 }
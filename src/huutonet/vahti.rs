@@ -7,12 +7,10 @@ pub static HUUTONET_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^https://(www\.)?huuto\.net/haku?.*$").unwrap());
 
 use super::api::{is_valid_url, vahti_to_api};
-use super::parse::api_parse_after;
+use super::parse::{api_parse_after, api_result_count};
 use crate::error::Error;
-use crate::itemhistory::ItemHistoryStorage;
 use crate::models::DbVahti;
-use crate::vahti::{Vahti, VahtiItem};
-use crate::Database;
+use crate::vahti::{SearchResult, Vahti};
 
 #[derive(Debug, Clone)]
 pub struct HuutonetVahti {
@@ -26,60 +24,14 @@ pub struct HuutonetVahti {
 
 #[async_trait]
 impl Vahti for HuutonetVahti {
-    async fn update(
-        &mut self,
-        db: &Database,
-        ihs: ItemHistoryStorage,
-    ) -> Result<Vec<VahtiItem>, Error> {
+    async fn fetch(&self) -> Result<SearchResult, Error> {
         debug!("Updating {}", self.url);
-        let ihref = ihs
-            .get(&(self.user_id, self.delivery_method))
-            .expect("bug: impossible");
+        let res = reqwest::get(vahti_to_api(&self.url)).await?.text().await?;
 
-        let res = reqwest::get(vahti_to_api(&self.url))
-            .await?
-            .text()
-            .await?
-            .to_string();
-
-        let mut ih = ihref.lock().unwrap().clone();
-        let ret = api_parse_after(&res, self.last_updated)?
-            .into_iter()
-            .filter_map(|i| {
-                if !ih.contains(i.ad_id, i.site_id) {
-                    ih.add_item(i.ad_id, i.site_id, chrono::Local::now().timestamp());
-                    let mut newi = i.clone();
-                    newi.vahti_url = Some(self.url.clone());
-                    newi.deliver_to = Some(self.user_id);
-                    newi.delivery_method = Some(self.delivery_method);
-
-                    Some(newi)
-                } else {
-                    None
-                }
-            })
-            .map(|mut i| {
-                i.vahti_url = Some(self.url.clone());
-                i.deliver_to = Some(self.user_id);
-                i.delivery_method = Some(self.delivery_method);
-                i
-            })
-            .collect::<Vec<_>>();
-
-        {
-            let mut locked = ihref.lock().unwrap();
-            ih.extend(&locked);
-            *locked = ih;
-        }
-
-        if ret.is_empty() {
-            return Ok(vec![]);
-        }
-
-        db.vahti_updated(self.to_db(), ret.iter().map(|i| i.published).max())
-            .await?;
-
-        Ok(ret)
+        Ok(SearchResult {
+            count: api_result_count(&res)?,
+            items: api_parse_after(&res, 0)?,
+        })
     }
 
     fn is_valid_url(&self, url: &str) -> bool {
@@ -104,13 +56,13 @@ impl Vahti for HuutonetVahti {
     }
 
     fn to_db(&self) -> DbVahti {
-        DbVahti {
-            id: self.id,
-            url: self.url.clone(),
-            user_id: self.user_id as i64,
-            last_updated: self.last_updated,
-            site_id: self.site_id,
-            delivery_method: self.delivery_method,
-        }
+        DbVahti::new(
+            self.id,
+            self.url.clone(),
+            self.user_id as i64,
+            self.site_id,
+            self.delivery_method,
+            self.last_updated,
+        )
     }
 }
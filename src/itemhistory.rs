@@ -3,6 +3,9 @@ use std::sync::{Arc, Mutex};
 
 use dashmap::DashMap;
 
+use crate::models::NewItemHistoryEntry;
+use crate::Torimies;
+
 #[derive(Debug, Clone)]
 pub struct ItemHistory {
     // (item_id, site_id), timestamp
@@ -30,6 +33,21 @@ impl ItemHistory {
         self.items.contains_key(&(id, site_id))
     }
 
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterates over the (item_id, site_id, timestamp) entries of the history
+    pub fn items(&self) -> impl Iterator<Item = (i64, i32, i64)> + '_ {
+        self.items
+            .iter()
+            .map(|((id, site_id), ts)| (*id, *site_id, *ts))
+    }
+
     pub fn purge_old(&mut self) {
         self.items
             .retain(|(_, _), timestamp| timestamp > &mut (chrono::Local::now().timestamp() - 1000));
@@ -46,3 +64,60 @@ impl Default for ItemHistory {
         Self::new()
     }
 }
+
+impl Torimies {
+    /// Loads the item history persisted during the previous shutdown
+    pub async fn load_item_history(&self) {
+        let entries = match self.database.fetch_item_history().await {
+            Ok(e) => e,
+            Err(e) => {
+                error!("Failed to load the item history: {}", e);
+                return;
+            }
+        };
+
+        for e in entries {
+            self.itemhistorystorage
+                .entry((e.user_id as u64, e.delivery_method))
+                .or_insert_with(|| Arc::new(Mutex::new(ItemHistory::new())))
+                .lock()
+                .unwrap()
+                .add_item(e.item_id, e.site_id, e.timestamp);
+        }
+
+        self.itemhistorystorage
+            .iter()
+            .for_each(|ih| ih.value().lock().unwrap().purge_old());
+        info!(
+            "Loaded the item history of {} recipients",
+            self.itemhistorystorage.len()
+        );
+    }
+
+    /// Persists the item history so that it survives a restart
+    pub async fn flush_item_history(&self) {
+        let entries = self
+            .itemhistorystorage
+            .iter()
+            .flat_map(|ih| {
+                let (user_id, delivery_method) = *ih.key();
+                ih.value()
+                    .lock()
+                    .unwrap()
+                    .items()
+                    .map(|(item_id, site_id, timestamp)| NewItemHistoryEntry {
+                        user_id: user_id as i64,
+                        delivery_method,
+                        item_id,
+                        site_id,
+                        timestamp,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        if let Err(e) = self.database.replace_item_history(&entries).await {
+            error!("Failed to persist the item history: {}", e);
+        }
+    }
+}
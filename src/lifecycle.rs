@@ -0,0 +1,132 @@
+use chrono::{Local, TimeZone};
+use futures::stream::{self, StreamExt};
+
+use crate::delivery::{perform_notification, Notification, NotificationAction};
+use crate::error::Error;
+use crate::models::DbVahti;
+use crate::Torimies;
+
+fn format_date(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .unwrap()
+        .format("%d/%m/%Y %R")
+        .to_string()
+}
+
+impl Torimies {
+    /// Handles the automatic state changes of the Vahtis and forgets the prices, reposts,
+    /// price statistics and delivery history of items that have not been seen in a long time
+    pub async fn update_vahti_lifecycles(&self) -> Result<(), Error> {
+        self.update_vahti_states().await?;
+        self.purge_item_history().await
+    }
+
+    /// Resumes the Vahtis whose pause has ended, reminds about and pauses expiring Vahtis
+    /// and pauses the Vahtis whose search has not returned anything in a while
+    pub async fn update_vahti_states(&self) -> Result<(), Error> {
+        let now = chrono::Local::now().timestamp();
+        let mut notifications = vec![];
+
+        self.database.resume_due_vahtis().await?;
+
+        let reminder_threshold =
+            now + i64::from(crate::config::get().vahti.expiry_reminder_days) * 86400;
+        let (expired, expiring): (Vec<DbVahti>, Vec<DbVahti>) = self
+            .database
+            .fetch_expiring_vahtis(reminder_threshold)
+            .await?
+            .into_iter()
+            .partition(|v| v.expires_at.is_some_and(|e| e <= now));
+
+        if !expired.is_empty() {
+            info!("Pausing {} expired Vahtis", expired.len());
+            self.database
+                .pause_vahtis_by_id(&expired.iter().map(|v| v.id).collect::<Vec<_>>())
+                .await?;
+        }
+
+        notifications.extend(expired.iter().map(|v| Notification {
+            deliver_to: v.user_id as u64,
+            delivery_method: v.delivery_method,
+            message: format!(
+                "Vahtisi {} vanheni ja se on pysäytetty. Voit jatkaa sitä komennolla /jatka",
+                v.url
+            ),
+            action: None,
+        }));
+
+        for v in expiring.iter().filter(|v| !v.reminded) {
+            self.database.set_vahti_reminded(v.id).await?;
+            notifications.push(Notification {
+                deliver_to: v.user_id as u64,
+                delivery_method: v.delivery_method,
+                message: format!(
+                    "Vahtisi {} vanhenee {}. Voit jatkaa sen voimassaoloa {} päivällä",
+                    v.url,
+                    format_date(v.expires_at.expect("bug: impossible")),
+                    crate::config::get().vahti.extend_days
+                ),
+                action: Some(NotificationAction::ExtendVahti(v.id)),
+            });
+        }
+
+        let empty = self
+            .database
+            .fetch_empty_vahtis(
+                now - i64::from(crate::config::get().vahti.empty_pause_days) * 86400,
+            )
+            .await?;
+
+        if !empty.is_empty() {
+            info!("Pausing {} Vahtis without search results", empty.len());
+            self.database
+                .pause_vahtis_by_id(&empty.iter().map(|v| v.id).collect::<Vec<_>>())
+                .await?;
+        }
+
+        notifications.extend(empty.iter().map(|v| Notification {
+            deliver_to: v.user_id as u64,
+            delivery_method: v.delivery_method,
+            message: format!(
+                "Vahtisi {} ei ole löytänyt yhtään ilmoitusta {} päivään, joten se on pysäytetty. Voit jatkaa sitä komennolla /jatka",
+                v.url,
+                crate::config::get().vahti.empty_pause_days
+            ),
+            action: None,
+        }));
+
+        let dm = self.delivery.clone();
+        stream::iter(notifications)
+            .for_each_concurrent(crate::config::get().futures_max_buffer_size, |n| {
+                let dm = dm.clone();
+                async move {
+                    if let Err(e) = perform_notification(dm, n).await {
+                        error!("Failed to send a notification: {}", e);
+                    }
+                }
+            })
+            .await;
+
+        Ok(())
+    }
+
+    async fn purge_item_history(&self) -> Result<(), Error> {
+        let now = chrono::Local::now().timestamp();
+        self.database
+            .purge_item_prices(now - crate::pricedrop::ITEM_PRICE_RETENTION)
+            .await?;
+        self.database
+            .purge_repost_history(now - crate::repost::REPOST_HISTORY_RETENTION)
+            .await?;
+        self.database
+            .purge_matched_items(now - crate::stats::MATCHED_ITEM_RETENTION)
+            .await?;
+        self.database
+            .purge_delivered_items(
+                now - i64::from(crate::config::get().history_retention_days) * 86400,
+            )
+            .await?;
+        Ok(())
+    }
+}
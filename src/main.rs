@@ -4,14 +4,38 @@
 #[cfg(test)]
 mod tests;
 
+mod account;
+mod admin;
+mod blockrule;
+mod config;
 mod itemhistory;
+mod lifecycle;
+mod metrics;
+mod migrations;
+mod pricedrop;
+mod reload;
+mod repost;
+mod search;
+mod seller;
+mod server;
+mod stats;
+mod supervisor;
 #[cfg(feature = "tori")]
 mod tori;
 
 #[cfg(feature = "huutonet")]
 mod huutonet;
 
+#[cfg(feature = "nettiauto")]
+mod nettiauto;
+
+#[cfg(feature = "muusikoiden")]
+mod muusikoiden;
+
 mod error;
+mod geo;
+mod health;
+mod history;
 pub mod models;
 pub mod schema;
 
@@ -19,13 +43,14 @@ pub mod command;
 pub mod database;
 pub mod delivery;
 mod vahti;
+mod watch;
 
 #[macro_use]
 extern crate tracing;
 #[macro_use]
 extern crate diesel;
 
-use std::sync::{Arc, LazyLock, RwLock};
+use std::sync::Arc;
 
 use command::{Command, Manager};
 use dashmap::DashMap;
@@ -33,26 +58,8 @@ use database::Database;
 use delivery::Delivery;
 use futures::future::join_all;
 use futures::stream::{self, StreamExt};
-
-static UPDATE_INTERVAL: LazyLock<u64> = LazyLock::new(|| {
-    std::env::var("UPDATE_INTERVAL")
-        .unwrap_or(String::from("120"))
-        .parse()
-        .expect("Invalid UPDATED_INTERVAL")
-});
-
-static FUTURES_MAX_BUFFER_SIZE: LazyLock<usize> = LazyLock::new(|| {
-    std::env::var("FUTURES_MAX_BUFFER_SIZE")
-        .unwrap_or(String::from("50"))
-        .parse()
-        .expect("Invalid FUTURES_MAX_BUFFER_SIZE")
-});
-
-#[derive(PartialEq, Clone)]
-enum State {
-    Running,
-    Shutdown,
-}
+use health::{CommanderStatus, Health};
+use tokio_util::sync::CancellationToken;
 
 #[derive(Clone)]
 struct Torimies {
@@ -61,39 +68,39 @@ struct Torimies {
     pub command_manager: Arc<DashMap<String, Box<dyn Manager + Send + Sync>>>,
     pub database: Database,
     pub itemhistorystorage: crate::itemhistory::ItemHistoryStorage,
-    pub state: Arc<RwLock<State>>,
+    pub shutdown: CancellationToken,
+    pub health: Arc<Health>,
 }
 
 // False positive
 #[allow(clippy::needless_pass_by_ref_mut)]
-async fn update_loop(man: &mut Torimies) {
-    let mut interval = tokio::time::interval(std::time::Duration::from_secs(*UPDATE_INTERVAL));
+pub(crate) async fn update_loop(man: &mut Torimies) {
+    let mut period = std::time::Duration::from_secs(config::get().update_interval);
+    let mut interval = tokio::time::interval(period);
     loop {
-        // Exiting after recieved signal depends on
-        // 1) the ongoing update
-        // 2) the following UPDATE_INTERVAL-tick
-        interval.tick().await;
-        let mut failcount = 0;
-
-        let state = if let Ok(state) = man.state.read() {
-            state.clone()
-        } else {
-            error!("Failed to read Torimies.state");
-            failcount += 1;
-            if failcount > 2 {
-                error!("Assuming State::Shutdown");
-                State::Shutdown
-            } else {
-                State::Running
-            }
+        // An ongoing update is finished before exiting, but it stops fetching
+        // new vahtis and persists the deliveries that miss the shutdown deadline
+        let reloaded = tokio::select! {
+            _ = interval.tick() => false,
+            _ = config::RELOADED.notified() => true,
+            _ = man.shutdown.cancelled() => break,
         };
 
-        if state == State::Shutdown {
-            break;
+        // Adjust the interval if it was changed by a configuration reload
+        let new_period = std::time::Duration::from_secs(config::get().update_interval);
+        if new_period != period {
+            info!("Update interval changed to {}s", new_period.as_secs());
+            period = new_period;
+            interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        }
+
+        if reloaded {
+            continue;
         }
 
-        if let Err(e) = man.update_all_vahtis().await {
-            error!("Error while updating: {}", e);
+        match man.update_all_vahtis().await {
+            Ok(()) => man.health.update_succeeded(),
+            Err(e) => error!("Error while updating: {}", e),
         }
     }
 
@@ -101,33 +108,35 @@ async fn update_loop(man: &mut Torimies) {
 }
 
 async fn command_loop(man: &Torimies) {
-    let mut balls = man.command.iter_mut().collect::<Vec<_>>();
-    let fs = stream::iter(balls.iter_mut())
-        .map(async move |c| {
-            let mut failcount = 0;
-            while let Err(e) = c.start().await {
-                error!("Failed to start {} commander {}", c.key(), e);
-                failcount += 1;
-                if failcount > 2 {
-                    error!("Giving up with starting {} commander", c.key());
-                    break;
-                }
-            }
-        })
-        .collect::<Vec<_>>()
-        .await;
+    // The commanders are moved out of the map so that each of them
+    // can be supervised and restarted independently
+    let names = man
+        .command
+        .iter()
+        .map(|c| c.key().clone())
+        .collect::<Vec<_>>();
+    let fs = names
+        .into_iter()
+        .filter_map(|name| man.command.remove(&name))
+        .map(|(name, c)| supervisor::supervise_commander(man.clone(), name, c));
 
     join_all(fs).await;
     info!("Command loop exited")
 }
 
-async fn ctrl_c_handler(man: &Torimies) {
-    tokio::signal::ctrl_c()
-        .await
-        .expect("Failed to register ctrl+c handler");
-    info!("Recieved ctrl+c");
-    info!("Setting State to State::Shutdown");
-    *man.state.write().unwrap() = State::Shutdown;
+async fn shutdown_handler(man: &Torimies) {
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .expect("Failed to register SIGTERM handler");
+    tokio::select! {
+        r = tokio::signal::ctrl_c() => {
+            r.expect("Failed to register ctrl+c handler");
+            info!("Recieved ctrl+c");
+        }
+        _ = terminate.recv() => info!("Recieved SIGTERM"),
+    }
+
+    info!("Shutting down");
+    man.shutdown.cancel();
 
     let balls = man.command_manager.iter().collect::<Vec<_>>();
     let fs = stream::iter(balls.iter())
@@ -135,7 +144,7 @@ async fn ctrl_c_handler(man: &Torimies) {
         .collect::<Vec<_>>()
         .await;
     join_all(fs).await;
-    info!("Ctrl+c handler exited");
+    info!("Shutdown handler exited");
 }
 
 impl Torimies {
@@ -146,7 +155,8 @@ impl Torimies {
             command_manager: Arc::new(DashMap::new()),
             database: db,
             itemhistorystorage: Arc::new(DashMap::new()),
-            state: Arc::new(RwLock::new(State::Running)),
+            shutdown: CancellationToken::new(),
+            health: Arc::new(Health::new()),
         }
     }
 
@@ -161,22 +171,45 @@ impl Torimies {
     ) {
         self.command_manager
             .insert(name.to_string(), commander.manager());
+        self.health
+            .set_commander_status(&name.to_string(), CommanderStatus::Starting);
         self.command.insert(name.to_string(), Box::new(commander));
     }
 }
 
 #[tokio::main]
 async fn main() {
-    dotenv::dotenv().expect("Failed to load .env file");
+    dotenv::dotenv().ok();
 
     tracing_subscriber::fmt::init();
 
-    let database = Database::new().await;
+    match config::Config::load() {
+        Ok(c) => config::set(c),
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    let config = config::get();
+
+    let database = match Database::new().await {
+        Ok(db) => db,
+        Err(e) => {
+            error!("Failed to open the database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    #[cfg(feature = "tori")]
+    if let Err(e) = tori::recommerce::migrate_legacy_urls(&database).await {
+        error!("Failed to migrate the legacy Tori urls: {}", e);
+    }
 
     let mut the_man = Torimies::new(database);
+    the_man.load_item_history().await;
 
     #[cfg(feature = "discord-delivery")]
-    {
+    if config.discord_enabled() {
         let dc = crate::delivery::discord::Discord::init()
             .await
             .expect("Discord delivery initialization failed");
@@ -185,7 +218,7 @@ async fn main() {
     }
 
     #[cfg(feature = "discord-command")]
-    {
+    if config.discord_enabled() {
         let dc = crate::command::discord::Discord::init(&the_man.database.clone())
             .await
             .expect("Discord commmand initialization failed");
@@ -194,7 +227,7 @@ async fn main() {
     }
 
     #[cfg(feature = "telegram-delivery")]
-    {
+    if config.telegram_enabled() {
         let tg = crate::delivery::telegram::Telegram::init()
             .await
             .expect("Telegram delivery initialization failed");
@@ -203,7 +236,7 @@ async fn main() {
     }
 
     #[cfg(feature = "telegram-command")]
-    {
+    if config.telegram_enabled() {
         let tg = crate::command::telegram::Telegram::init(&the_man.database.clone())
             .await
             .expect("Telegram commmand initialization failed");
@@ -214,9 +247,24 @@ async fn main() {
     let the_man2 = the_man.clone();
     let the_man3 = the_man.clone();
 
-    let update = tokio::task::spawn(async move { update_loop(&mut the_man).await });
+    if let Some(address) = config.http_address.clone() {
+        let the_man4 = the_man.clone();
+        tokio::task::spawn(async move { server::serve(the_man4, address).await });
+    }
+
+    let the_man5 = the_man.clone();
+    tokio::task::spawn(async move { reload::reload_handler(the_man5).await });
+
+    let update = tokio::task::spawn(async move { supervisor::supervise_update(the_man).await });
     let command = tokio::task::spawn(async move { command_loop(&the_man2).await });
-    let ctrl_c = tokio::task::spawn(async move { ctrl_c_handler(&the_man3).await });
+    let the_man6 = the_man3.clone();
+    let shutdown = tokio::task::spawn(async move { shutdown_handler(&the_man3).await });
 
-    let _ = futures::join!(update, command, ctrl_c);
+    let _ = futures::join!(update, command, shutdown);
+
+    the_man6.flush_item_history().await;
+    if let Err(e) = the_man6.database.close().await {
+        error!("Failed to close the database: {}", e);
+    }
+    info!("Shutdown complete");
 }
@@ -0,0 +1,148 @@
+use std::sync::LazyLock;
+
+use prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge, register_int_gauge_vec, Encoder, Histogram, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, TextEncoder,
+};
+
+pub static UPDATE_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
+    register_histogram!(
+        "torimies_update_duration_seconds",
+        "Duration of a full update round",
+        vec![0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0]
+    )
+    .unwrap()
+});
+
+pub static FETCH_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "torimies_fetch_duration_seconds",
+        "Duration of updating a single Vahti",
+        &["site"]
+    )
+    .unwrap()
+});
+
+pub static FETCH_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "torimies_fetch_errors_total",
+        "Amount of failed Vahti updates",
+        &["site"]
+    )
+    .unwrap()
+});
+
+pub static ITEMS_PARSED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "torimies_items_parsed_total",
+        "Amount of new items found by the Vahtis",
+        &["site"]
+    )
+    .unwrap()
+});
+
+pub static ITEMS_BLACKLISTED: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!(
+        "torimies_items_blacklisted_total",
+        "Amount of items filtered out by the blacklists and block rules"
+    )
+    .unwrap()
+});
+
+pub static ITEMS_DELIVERED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "torimies_items_delivered_total",
+        "Amount of items delivered",
+        &["delivery_method"]
+    )
+    .unwrap()
+});
+
+pub static DELIVERIES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "torimies_deliveries_total",
+        "Amount of deliveries by result",
+        &["delivery_method", "result"]
+    )
+    .unwrap()
+});
+
+pub static ACTIVE_VAHTIS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "torimies_active_vahtis",
+        "Amount of Vahtis updated during the latest update round",
+        &["site"]
+    )
+    .unwrap()
+});
+
+pub static ITEM_HISTORY_SIZE: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "torimies_item_history_size",
+        "Amount of items in the ItemHistoryStorage"
+    )
+    .unwrap()
+});
+
+pub static DB_QUERY_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "torimies_db_query_duration_seconds",
+        "Duration of database queries including the wait for a connection",
+        &["query"],
+        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 30.0]
+    )
+    .unwrap()
+});
+
+pub static DB_CONNECTION_WAIT: LazyLock<Histogram> = LazyLock::new(|| {
+    register_histogram!(
+        "torimies_db_connection_wait_seconds",
+        "Time spent waiting for a connection from the database pool",
+        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 30.0]
+    )
+    .unwrap()
+});
+
+pub static DB_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "torimies_db_errors_total",
+        "Amount of failed database queries",
+        &["query"]
+    )
+    .unwrap()
+});
+
+pub fn site_name(site_id: i32) -> &'static str {
+    match site_id {
+        crate::search::ID => crate::search::NAME,
+        #[cfg(feature = "tori")]
+        crate::tori::ID => crate::tori::NAME,
+        #[cfg(feature = "huutonet")]
+        crate::huutonet::ID => crate::huutonet::NAME,
+        #[cfg(feature = "nettiauto")]
+        crate::nettiauto::ID => crate::nettiauto::NAME,
+        #[cfg(feature = "muusikoiden")]
+        crate::muusikoiden::ID => crate::muusikoiden::NAME,
+        _ => "unknown",
+    }
+}
+
+pub fn delivery_name(delivery_method: i32) -> &'static str {
+    match delivery_method {
+        #[cfg(feature = "discord-delivery")]
+        crate::delivery::discord::ID => crate::delivery::discord::NAME,
+        #[cfg(feature = "telegram-delivery")]
+        crate::delivery::telegram::ID => crate::delivery::telegram::NAME,
+        _ => "unknown",
+    }
+}
+
+/// Encodes all registered metrics in the Prometheus text format
+pub fn gather() -> String {
+    let mut buffer = vec![];
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("Failed to encode metrics");
+    String::from_utf8(buffer).expect("Metrics are not valid utf-8")
+}
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use diesel::connection::SimpleConnection;
+#[cfg(not(feature = "postgres"))]
+use diesel::prelude::*;
+use diesel_migrations::{Migration, MigrationConnection, RunMigrationsError};
+
+use crate::database::DbConnection;
+use crate::error::Error;
+
+/// The directory containing the migrations of the selected database backend
+#[cfg(not(feature = "postgres"))]
+pub const MIGRATIONS_DIR: &str = "migrations";
+#[cfg(feature = "postgres")]
+pub const MIGRATIONS_DIR: &str = "migrations_postgres";
+
+pub struct EmbeddedMigration {
+    pub name: &'static str,
+    version: String,
+    up_sql: &'static str,
+}
+
+impl EmbeddedMigration {
+    fn new(name: &'static str, up_sql: &'static str) -> Self {
+        // The version is the name up to the first underscore without the dashes,
+        // just like the diesel CLI does it
+        let version = name.split('_').next().unwrap().replace('-', "");
+        Self {
+            name,
+            version,
+            up_sql,
+        }
+    }
+}
+
+impl Migration for EmbeddedMigration {
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn run(&self, conn: &dyn SimpleConnection) -> Result<(), RunMigrationsError> {
+        conn.batch_execute(self.up_sql).map_err(Into::into)
+    }
+
+    fn revert(&self, _conn: &dyn SimpleConnection) -> Result<(), RunMigrationsError> {
+        unreachable!()
+    }
+}
+
+#[cfg(not(feature = "postgres"))]
+macro_rules! embed_migration {
+    ($name:literal) => {
+        EmbeddedMigration::new(
+            $name,
+            include_str!(concat!("../migrations/", $name, "/up.sql")),
+        )
+    };
+}
+
+#[cfg(feature = "postgres")]
+macro_rules! embed_migration {
+    ($name:literal) => {
+        EmbeddedMigration::new(
+            $name,
+            include_str!(concat!("../migrations_postgres/", $name, "/up.sql")),
+        )
+    };
+}
+
+/// All the migrations in `migrations/`, new migrations must be added here as well
+#[cfg(not(feature = "postgres"))]
+pub static MIGRATIONS: LazyLock<Vec<EmbeddedMigration>> = LazyLock::new(|| {
+    vec![
+        embed_migration!("20211112185056_initial_migration"),
+        embed_migration!("20211215123733_vahti_blacklist"),
+        embed_migration!("2021-12-17-203524_vahti_and_blacklist_ids"),
+        embed_migration!("2021-12-28-153855_add_site_id"),
+        embed_migration!("2023-03-02-121436_add_delivery_method"),
+        embed_migration!("2026-10-19-100000_users"),
+        embed_migration!("2026-10-19-110000_vahti_destinations"),
+        embed_migration!("2026-10-19-120000_pause_vahti"),
+        embed_migration!("2026-10-19-130000_vahti_expiry"),
+        embed_migration!("2026-10-19-140000_quotas_and_bans"),
+        embed_migration!("2026-10-19-150000_shutdown_persistence"),
+        embed_migration!("2026-10-19-170000_sellers"),
+        embed_migration!("2026-10-19-180000_block_rules"),
+        embed_migration!("2026-10-19-190000_price_drops"),
+        embed_migration!("2026-10-19-200000_reposts"),
+        embed_migration!("2026-10-19-210000_matched_items"),
+        embed_migration!("2026-10-19-220000_delivered_items"),
+        embed_migration!("2026-10-19-230000_watched_items"),
+        embed_migration!("2026-10-20-000000_postal_codes"),
+    ]
+});
+
+/// All the migrations in `migrations_postgres/`, new migrations must be added here as well
+///
+/// The PostgreSQL schema starts from the state the SQLite migrations had reached
+/// when the backend was added, later migrations must be written for both backends
+#[cfg(feature = "postgres")]
+pub static MIGRATIONS: LazyLock<Vec<EmbeddedMigration>> = LazyLock::new(|| {
+    vec![
+        embed_migration!("2026-10-19-160000_initial_schema"),
+        embed_migration!("2026-10-19-170000_sellers"),
+        embed_migration!("2026-10-19-180000_block_rules"),
+        embed_migration!("2026-10-19-190000_price_drops"),
+        embed_migration!("2026-10-19-200000_reposts"),
+        embed_migration!("2026-10-19-210000_matched_items"),
+        embed_migration!("2026-10-19-220000_delivered_items"),
+        embed_migration!("2026-10-19-230000_watched_items"),
+        embed_migration!("2026-10-20-000000_postal_codes"),
+    ]
+});
+
+/// Databases created before diesel was adopted have the tables of the initial
+/// migration, but no record of running it
+#[cfg(not(feature = "postgres"))]
+fn mark_legacy_database(conn: &DbConnection) -> Result<(), Error> {
+    #[derive(QueryableByName)]
+    struct Count {
+        #[sql_type = "diesel::sql_types::BigInt"]
+        count: i64,
+    }
+
+    let tables = |name: &str| -> Result<i64, Error> {
+        Ok(diesel::sql_query(format!(
+            "SELECT COUNT(*) AS count FROM sqlite_master WHERE type = 'table' AND name = '{}'",
+            name
+        ))
+        .get_result::<Count>(conn)?
+        .count)
+    };
+
+    if tables("__diesel_schema_migrations")? == 0 && tables("Vahdit")? > 0 {
+        info!("Found a database created before diesel migrations, marking the initial migration as run");
+        diesel_migrations::setup_database(conn)?;
+        conn.batch_execute(&format!(
+            "INSERT INTO __diesel_schema_migrations (version) VALUES ('{}')",
+            MIGRATIONS[0].version()
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the path of a new backup of the database, placed in `backup_dir`
+/// or next to the database if it is not set
+pub fn backup_path(database_url: &str, backup_dir: Option<&str>) -> PathBuf {
+    let database = Path::new(database_url);
+    let name = format!(
+        "{}.backup-{}",
+        database
+            .file_name()
+            .map_or(database_url.into(), |n| n.to_string_lossy()),
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    );
+    match backup_dir {
+        Some(dir) => Path::new(dir).join(name),
+        None => database.with_file_name(name),
+    }
+}
+
+/// Runs the pending migrations, backing up the database first
+///
+/// Refuses to touch a database that has migrations this binary does not know about,
+/// as it has most likely been migrated by a newer version of torimies-rs
+pub fn run_migrations(conn: &DbConnection, database_url: &str) -> Result<(), Error> {
+    #[cfg(not(feature = "postgres"))]
+    mark_legacy_database(conn)?;
+    diesel_migrations::setup_database(conn)?;
+
+    let known = MIGRATIONS
+        .iter()
+        .map(|m| m.version().to_string())
+        .collect::<HashSet<_>>();
+    let applied = conn.previously_run_migration_versions()?;
+
+    let mut unknown = applied.difference(&known).cloned().collect::<Vec<_>>();
+    if !unknown.is_empty() {
+        unknown.sort();
+        return Err(Error::SchemaTooNew(unknown.join(", ")));
+    }
+
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|m| !applied.contains(m.version()))
+        .collect::<Vec<_>>();
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    // NOTE: PostgreSQL databases are expected to be backed up with pg_dump,
+    // the migrations are run in transactions there anyway
+    if cfg!(not(feature = "postgres")) && !applied.is_empty() && database_url != ":memory:" {
+        let backup_dir = crate::config::get().backup_dir.clone();
+        let backup = backup_path(database_url, backup_dir.as_deref());
+        if let Some(dir) = backup.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| Error::Config(format!("Failed to create {}: {}", dir.display(), e)))?;
+        }
+        let backup = backup.to_string_lossy();
+        info!("Backing up the database to {}", backup);
+        conn.batch_execute(&format!("VACUUM INTO '{}'", backup.replace('\'', "''")))?;
+    }
+
+    for m in &pending {
+        info!("Running migration {}", m.name);
+    }
+    diesel_migrations::run_migrations(
+        conn,
+        pending.into_iter().map(|m| m as &dyn Migration),
+        &mut std::io::sink(),
+    )?;
+
+    Ok(())
+}
@@ -6,6 +6,15 @@ pub struct DbVahti {
     pub last_updated: i64,
     pub site_id: i32,
     pub delivery_method: i32,
+    pub paused: bool,
+    pub resume_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    pub empty_since: Option<i64>,
+    pub reminded: bool,
+    pub created: i64,
+    pub price_drops: bool,
+    pub reposts: i32,
+    pub max_distance: Option<i32>,
 }
 
 use crate::schema::Vahdit;
@@ -18,6 +27,8 @@ pub struct NewVahti {
     pub last_updated: i64,
     pub site_id: i32,
     pub delivery_method: i32,
+    pub expires_at: Option<i64>,
+    pub created: i64,
 }
 
 #[derive(Queryable, Clone, Debug)]
@@ -37,3 +48,292 @@ pub struct NewBlacklist {
     pub seller_id: i32,
     pub site_id: i32,
 }
+
+#[derive(Queryable, Clone, Debug)]
+pub struct UserIdentity {
+    pub id: i32,
+    pub account_id: i32,
+    pub user_id: i64,
+    pub delivery_method: i32,
+}
+
+use crate::schema::{LinkCodes, UserIdentities, Users};
+
+#[derive(Insertable)]
+#[table_name = "Users"]
+pub struct NewUser {
+    pub created: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "UserIdentities"]
+pub struct NewUserIdentity {
+    pub account_id: i32,
+    pub user_id: i64,
+    pub delivery_method: i32,
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct LinkCode {
+    pub code: String,
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub created: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "LinkCodes"]
+pub struct NewLinkCode {
+    pub code: String,
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub created: i64,
+}
+
+use crate::schema::VahtiDestinations;
+
+#[derive(Insertable)]
+#[table_name = "VahtiDestinations"]
+pub struct NewVahtiDestination {
+    pub vahti_id: i32,
+    pub user_id: i64,
+    pub delivery_method: i32,
+}
+
+use crate::schema::Bans;
+
+#[derive(Insertable)]
+#[table_name = "Bans"]
+pub struct NewBan {
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub created: i64,
+}
+
+use crate::schema::PendingDeliveries;
+
+#[derive(Queryable, Clone, Debug)]
+pub struct PendingDelivery {
+    pub id: i32,
+    pub deliver_to: i64,
+    pub delivery_method: i32,
+    pub item: String,
+    pub created: i64,
+}
+
+#[derive(Insertable, Clone)]
+#[table_name = "PendingDeliveries"]
+pub struct NewPendingDelivery {
+    pub deliver_to: i64,
+    pub delivery_method: i32,
+    pub item: String,
+    pub created: i64,
+}
+
+use crate::schema::ItemHistories;
+
+#[derive(Queryable, Clone, Debug)]
+pub struct ItemHistoryEntry {
+    pub id: i32,
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub item_id: i64,
+    pub site_id: i32,
+    pub timestamp: i64,
+}
+
+#[derive(Insertable, Clone)]
+#[table_name = "ItemHistories"]
+pub struct NewItemHistoryEntry {
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub item_id: i64,
+    pub site_id: i32,
+    pub timestamp: i64,
+}
+
+use crate::schema::Sellers;
+
+#[derive(Queryable, Clone, Debug)]
+pub struct Seller {
+    pub id: i32,
+    pub seller_id: i32,
+    pub site_id: i32,
+    pub name: String,
+    pub account_created: Option<String>,
+    pub ad_count: Option<i32>,
+    pub updated: i64,
+}
+
+#[derive(Insertable, AsChangeset, Clone, Debug, PartialEq)]
+#[table_name = "Sellers"]
+pub struct NewSeller {
+    pub seller_id: i32,
+    pub site_id: i32,
+    pub name: String,
+    pub account_created: Option<String>,
+    pub ad_count: Option<i32>,
+    pub updated: i64,
+}
+
+use crate::schema::BlockRules;
+
+#[derive(Queryable, Clone, Debug)]
+pub struct DbBlockRule {
+    pub id: i32,
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub kind: String,
+    pub value: String,
+    pub created: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "BlockRules"]
+pub struct NewBlockRule {
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub kind: String,
+    pub value: String,
+    pub created: i64,
+}
+
+use crate::schema::ItemPrices;
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "ItemPrices"]
+pub struct NewItemPrice {
+    pub vahti_id: i32,
+    pub item_id: i64,
+    pub site_id: i32,
+    pub price: i64,
+    pub updated: i64,
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct RepostHistoryEntry {
+    pub id: i32,
+    pub vahti_id: i32,
+    pub item_id: i64,
+    pub site_id: i32,
+    pub seller_id: i32,
+    pub title: String,
+    pub price: i64,
+    pub img_url: String,
+    pub seen: i64,
+}
+
+use crate::schema::RepostHistory;
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "RepostHistory"]
+pub struct NewRepostHistoryEntry {
+    pub vahti_id: i32,
+    pub item_id: i64,
+    pub site_id: i32,
+    pub seller_id: i32,
+    pub title: String,
+    pub price: i64,
+    pub img_url: String,
+    pub seen: i64,
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct MatchedItem {
+    pub id: i32,
+    pub vahti_id: i32,
+    pub item_id: i64,
+    pub site_id: i32,
+    pub title: String,
+    pub price: i64,
+    pub published: i64,
+}
+
+use crate::schema::MatchedItems;
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "MatchedItems"]
+pub struct NewMatchedItem {
+    pub vahti_id: i32,
+    pub item_id: i64,
+    pub site_id: i32,
+    pub title: String,
+    pub price: i64,
+    pub published: i64,
+}
+
+#[derive(Queryable, Clone, Debug, serde::Serialize)]
+pub struct DeliveredItem {
+    pub id: i32,
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub site_id: i32,
+    pub item_id: i64,
+    pub title: String,
+    pub url: String,
+    pub vahti_url: String,
+    pub price: i64,
+    pub location: String,
+    pub seller_name: String,
+    pub published: i64,
+    pub delivered: i64,
+}
+
+use crate::schema::DeliveredItems;
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "DeliveredItems"]
+pub struct NewDeliveredItem {
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub site_id: i32,
+    pub item_id: i64,
+    pub title: String,
+    pub url: String,
+    pub vahti_url: String,
+    pub price: i64,
+    pub location: String,
+    pub seller_name: String,
+    pub published: i64,
+    pub delivered: i64,
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct WatchedItem {
+    pub id: i32,
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub site_id: i32,
+    pub listing_id: i64,
+    pub title: String,
+    pub url: String,
+    pub price: i64,
+    pub created: i64,
+    pub checked: i64,
+}
+
+use crate::schema::WatchedItems;
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "WatchedItems"]
+pub struct NewWatchedItem {
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub site_id: i32,
+    pub listing_id: i64,
+    pub title: String,
+    pub url: String,
+    pub price: i64,
+    pub created: i64,
+    pub checked: i64,
+}
+
+use crate::schema::HomeLocations;
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "HomeLocations"]
+pub struct NewHomeLocation {
+    pub user_id: i64,
+    pub delivery_method: i32,
+    pub postal_code: String,
+}
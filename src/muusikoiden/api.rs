@@ -0,0 +1,42 @@
+use encoding::all::WINDOWS_1252;
+use encoding::{DecoderTrap, Encoding};
+use url::Url;
+
+use crate::error::Error;
+
+pub const BASE_URL: &str = "https://muusikoiden.net";
+
+/// Muusikoiden.net has no API, the vahti url itself is scraped after making sure
+/// the results are sorted by the newest first and start from the first page
+pub fn vahti_to_search(vahti: &str) -> String {
+    let Ok(mut url) = Url::parse(vahti) else {
+        return vahti.to_string();
+    };
+    let params = url
+        .query_pairs()
+        .filter(|(k, _)| k != "sort" && k != "page")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect::<Vec<_>>();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(params)
+        .append_pair("sort", "new");
+    url.to_string()
+}
+
+/// Fetches the search results page, the pages are encoded in Windows-1252
+pub async fn fetch_search(vahti: &str) -> Result<String, Error> {
+    let bytes = reqwest::get(vahti_to_search(vahti))
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    WINDOWS_1252
+        .decode(&bytes, DecoderTrap::Replace)
+        .map_err(|_| Error::InvalidItem)
+}
+
+pub async fn is_valid_url(url: &str) -> Result<bool, Error> {
+    let page = fetch_search(url).await?;
+    Ok(super::parse::result_count(&page) > 0)
+}
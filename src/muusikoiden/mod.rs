@@ -0,0 +1,8 @@
+pub mod api;
+pub mod parse;
+pub mod vahti;
+
+pub const ID: i32 = 4;
+pub const NAME: &str = "muusikoiden";
+/// The ads only tell the minute they were published
+pub const PUBLISHED_PRECISION: i64 = 60;
@@ -0,0 +1,129 @@
+use std::sync::LazyLock;
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use scraper::{ElementRef, Html, Selector};
+
+use super::api::BASE_URL;
+use crate::error::Error;
+use crate::vahti::VahtiItem;
+
+const AD_TYPES: [&str; 4] = ["Myydään", "Ostetaan", "Vaihdetaan", "Annetaan"];
+
+fn selector(s: &str) -> Selector {
+    Selector::parse(s).expect("bug: invalid selector")
+}
+
+static ITEM: LazyLock<Selector> = LazyLock::new(|| selector("table.tori_item"));
+static TITLE: LazyLock<Selector> = LazyLock::new(|| selector("td.tori_title a"));
+static IMAGE: LazyLock<Selector> = LazyLock::new(|| selector("td.tori_image img"));
+static PRICE: LazyLock<Selector> = LazyLock::new(|| selector("span.price"));
+static LOCATION: LazyLock<Selector> = LazyLock::new(|| selector("span.location"));
+static SELLER: LazyLock<Selector> = LazyLock::new(|| selector("span.seller a"));
+static DATE: LazyLock<Selector> = LazyLock::new(|| selector("span.date"));
+
+fn text(ad: &ElementRef, selector: &Selector) -> Option<String> {
+    let element = ad.select(selector).next()?;
+    Some(element.text().collect::<String>().trim().to_string())
+}
+
+/// The value of a detail like `Sijainti: Helsinki`
+fn detail(ad: &ElementRef, selector: &Selector) -> Option<String> {
+    let text = text(ad, selector)?;
+    let value = text.split_once(':').map_or(&*text, |(_, v)| v).trim();
+    Some(value.to_string())
+}
+
+fn absolute_url(href: &str) -> String {
+    if href.starts_with("http") {
+        href.to_string()
+    } else {
+        format!("{}{}", BASE_URL, href)
+    }
+}
+
+/// The number at the end of the url, e.g. the id of the ad or the seller
+fn trailing_id<T: std::str::FromStr>(url: &str) -> Option<T> {
+    let start = url
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    url[start..].parse().ok()
+}
+
+fn parse_ad(ad: ElementRef) -> Option<VahtiItem> {
+    let link = ad.select(&TITLE).next()?;
+    let url = absolute_url(link.value().attr("href")?);
+    let heading = link.text().collect::<String>();
+    // NOTE: The titles start with the type of the ad, e.g. `Myydään: Fender Telecaster`
+    let (ad_type, title) = heading
+        .split_once(':')
+        .filter(|(t, _)| AD_TYPES.contains(&t.trim()))
+        .map_or(("", heading.as_str()), |(t, h)| (t.trim(), h));
+
+    let seller = ad.select(&SELLER).next();
+    let published = NaiveDateTime::parse_from_str(&detail(&ad, &DATE)?, "%d.%m.%Y %H:%M")
+        .ok()
+        .and_then(|d| Local.from_local_datetime(&d).earliest())?
+        .timestamp();
+
+    Some(VahtiItem {
+        deliver_to: None,
+        delivery_method: None,
+        site_id: super::ID,
+        title: title.trim().to_string(),
+        vahti_url: None,
+        img_url: ad
+            .select(&IMAGE)
+            .next()
+            .and_then(|i| i.value().attr("src"))
+            .map(absolute_url)
+            .unwrap_or_default(),
+        published,
+        // NOTE: Ads without a price, e.g. `Hinta: Tarjoa`, are priced at zero
+        price: detail(&ad, &PRICE)
+            .map(|p| p.chars().filter(char::is_ascii_digit).collect::<String>())
+            .and_then(|p| p.parse().ok())
+            .unwrap_or_default(),
+        seller_name: seller
+            .map(|s| s.text().collect::<String>().trim().to_string())
+            .unwrap_or_default(),
+        seller_id: seller
+            .and_then(|s| s.value().attr("href"))
+            .and_then(trailing_id),
+        seller_created: None,
+        seller_ads: None,
+        company_ad: false,
+        previous_price: None,
+        repost: None,
+        median_price: None,
+        postal_code: None,
+        coordinates: None,
+        distance: None,
+        vehicle: None,
+        location: detail(&ad, &LOCATION).unwrap_or_default(),
+        ad_type: ad_type.to_string(),
+        ad_id: trailing_id(&url)?,
+        url,
+    })
+}
+
+pub fn parse_after(page: &str, after: i64) -> Result<Vec<VahtiItem>, Error> {
+    let document = Html::parse_document(page);
+    let mut items = vec![];
+    for ad in document.select(&ITEM) {
+        let Some(item) = parse_ad(ad) else {
+            debug!("Skipping an ad that couldn't be parsed");
+            continue;
+        };
+        if item.published <= after {
+            break;
+        }
+        items.push(item);
+    }
+    debug!("Parsed {} items", items.len());
+    Ok(items)
+}
+
+/// Returns the amount of ads on the first page of the search results
+pub fn result_count(page: &str) -> i64 {
+    Html::parse_document(page).select(&ITEM).count() as i64
+}
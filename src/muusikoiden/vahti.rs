@@ -0,0 +1,68 @@
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use regex::Regex;
+
+pub static MUUSIKOIDEN_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^https://(www\.)?muusikoiden\.net/tori/haku\.php\?.*$").unwrap());
+
+use super::api::{fetch_search, is_valid_url};
+use super::parse::{parse_after, result_count};
+use crate::error::Error;
+use crate::models::DbVahti;
+use crate::vahti::{SearchResult, Vahti};
+
+#[derive(Debug, Clone)]
+pub struct MuusikoidenVahti {
+    pub id: i32,
+    pub url: String,
+    pub user_id: u64,
+    pub last_updated: i64,
+    pub site_id: i32,
+    pub delivery_method: i32,
+}
+
+#[async_trait]
+impl Vahti for MuusikoidenVahti {
+    async fn fetch(&self) -> Result<SearchResult, Error> {
+        debug!("Updating {}", self.url);
+        let res = fetch_search(&self.url).await?;
+
+        Ok(SearchResult {
+            count: result_count(&res),
+            items: parse_after(&res, 0)?,
+        })
+    }
+
+    fn is_valid_url(&self, url: &str) -> bool {
+        MUUSIKOIDEN_REGEX.is_match(url)
+    }
+
+    async fn validate_url(&self) -> Result<bool, Error> {
+        is_valid_url(&self.url).await
+    }
+
+    fn from_db(v: DbVahti) -> Result<Self, Error> {
+        assert_eq!(v.site_id, super::ID);
+
+        Ok(Self {
+            id: v.id,
+            url: v.url,
+            user_id: v.user_id as u64,
+            last_updated: v.last_updated,
+            site_id: super::ID,
+            delivery_method: v.delivery_method,
+        })
+    }
+
+    fn to_db(&self) -> DbVahti {
+        DbVahti::new(
+            self.id,
+            self.url.clone(),
+            self.user_id as i64,
+            self.site_id,
+            self.delivery_method,
+            self.last_updated,
+        )
+    }
+}
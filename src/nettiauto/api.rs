@@ -0,0 +1,69 @@
+use url::{form_urlencoded, Url};
+
+use super::auth::access_token;
+use crate::error::Error;
+
+const API_BASE: &str = "https://api.nettix.fi/rest/car/search?";
+
+/// The search parameters of nettiauto.com and their names in the Nettix API,
+/// the rest of the parameters are passed as they are
+const PARAMS: &[(&str, &str)] = &[
+    ("yfrom", "yearFrom"),
+    ("yto", "yearTo"),
+    ("pfrom", "priceFrom"),
+    ("pto", "priceTo"),
+    ("kmfrom", "kilometersFrom"),
+    ("kmto", "kilometersTo"),
+    ("fuel", "fuelType"),
+    ("gear", "gearType"),
+];
+
+/// Converts a nettiauto.com search url to the corresponding Nettix API search,
+/// e.g. `https://www.nettiauto.com/volkswagen/golf?yfrom=2012` searches
+/// Volkswagen Golfs from 2012 onwards
+pub fn vahti_to_api(vahti: &str) -> String {
+    let mut query = form_urlencoded::Serializer::for_suffix(String::from(API_BASE), API_BASE.len());
+    let Ok(url) = Url::parse(vahti) else {
+        return query.finish();
+    };
+
+    let path = url
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|s| !s.is_empty() && *s != "hakutulokset");
+    for (key, value) in ["make", "model"].into_iter().zip(path) {
+        query.append_pair(key, value);
+    }
+
+    for (key, value) in url.query_pairs().filter(|(_, v)| !v.is_empty()) {
+        let key = PARAMS
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map_or(&*key, |(_, k)| k);
+        query.append_pair(key, &value);
+    }
+
+    query
+        .append_pair("sortBy", "dateCreated")
+        .append_pair("sortOrder", "desc")
+        .finish()
+}
+
+/// Performs an authenticated request to the Nettix API
+pub async fn fetch(url: &str) -> Result<reqwest::Response, Error> {
+    Ok(reqwest::Client::new()
+        .get(url)
+        .header("X-Access-Token", access_token().await?)
+        .send()
+        .await?)
+}
+
+pub async fn is_valid_url(url: &str) -> Result<bool, Error> {
+    let response = fetch(&vahti_to_api(url))
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    Ok(super::parse::api_result_count(&response)? > 0)
+}
@@ -0,0 +1,64 @@
+use std::sync::LazyLock;
+
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::error::Error;
+
+const TOKEN_URL: &str = "https://auth.nettix.fi/oauth2/token";
+
+/// Tokens are renewed this many seconds before they expire
+const EXPIRY_MARGIN: i64 = 60;
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+}
+
+struct AccessToken {
+    token: String,
+    expires: i64,
+}
+
+static TOKEN: LazyLock<Mutex<Option<AccessToken>>> = LazyLock::new(Default::default);
+
+/// Returns an access token to the Nettix API, fetching a new one with the
+/// client credentials of the configuration when the previous one has expired
+pub async fn access_token() -> Result<String, Error> {
+    let mut cached = TOKEN.lock().await;
+    let now = chrono::Local::now().timestamp();
+    if let Some(t) = cached.as_ref().filter(|t| t.expires > now + EXPIRY_MARGIN) {
+        return Ok(t.token.clone());
+    }
+
+    let config = crate::config::get();
+    let (Some(id), Some(secret)) = (
+        config.nettiauto.client_id.as_deref(),
+        config.nettiauto.client_secret.as_deref(),
+    ) else {
+        return Err(Error::Config(String::from(
+            "Nettix API credentials are not set",
+        )));
+    };
+
+    let response = reqwest::Client::new()
+        .post(TOKEN_URL)
+        .form(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", id),
+            ("client_secret", secret),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let response: TokenResponse = serde_json::from_str(&response)?;
+
+    *cached = Some(AccessToken {
+        token: response.access_token.clone(),
+        expires: now + response.expires_in,
+    });
+    Ok(response.access_token)
+}
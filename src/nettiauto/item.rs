@@ -0,0 +1,30 @@
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use super::api::fetch;
+use super::models::FullNettiautoItem;
+use crate::error::Error;
+use crate::vahti::VahtiItem;
+use crate::watch::ItemStatus;
+
+/// Parses the status of an ad from the ad endpoint of the Nettix API
+pub fn parse_item(json: &str) -> Result<ItemStatus, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    match response_json["status"].as_str() {
+        None | Some("forsale") => {
+            let fullitem: FullNettiautoItem = serde_json::from_value(response_json)?;
+            Ok(ItemStatus::Available(Box::new(VahtiItem::from(fullitem))))
+        }
+        Some("sold") => Ok(ItemStatus::Sold),
+        _ => Ok(ItemStatus::Removed),
+    }
+}
+
+pub async fn fetch_item(adid: i64) -> Result<ItemStatus, Error> {
+    let url = format!("https://api.nettix.fi/rest/car/ad/{}", adid);
+    let response = fetch(&url).await?;
+    if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+        return Ok(ItemStatus::Removed);
+    }
+    parse_item(&response.error_for_status()?.text().await?)
+}
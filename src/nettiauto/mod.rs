@@ -0,0 +1,9 @@
+pub mod api;
+mod auth;
+pub mod item;
+mod models;
+pub mod parse;
+pub mod vahti;
+
+pub const ID: i32 = 3;
+pub const NAME: &str = "nettiauto";
@@ -0,0 +1,116 @@
+#![allow(dead_code)]
+use serde::Deserialize;
+
+use crate::vahti::{VahtiItem, Vehicle};
+
+#[derive(Deserialize, Debug, Default)]
+struct NettixName {
+    id: i64,
+    name: String,
+}
+
+/// The values with translations, e.g. the fuel type or the town
+#[derive(Deserialize, Debug, Default)]
+struct NettixLocalized {
+    id: i64,
+    fi: String,
+    en: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct NettixImageUrl {
+    url: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct NettixImage {
+    large: Option<NettixImageUrl>,
+    medium: Option<NettixImageUrl>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct NettixUser {
+    id: i32,
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FullNettiautoItem {
+    id: i64,
+    ad_url: String,
+    status: Option<String>,
+    make: NettixName,
+    model: NettixName,
+    model_type_name: Option<String>,
+    year: Option<i32>,
+    kilometers: Option<i64>,
+    price: Option<f64>,
+    date_created: String,
+    town: Option<NettixLocalized>,
+    region: Option<NettixLocalized>,
+    post_code: Option<String>,
+    fuel_type: Option<NettixLocalized>,
+    gear_type: Option<NettixLocalized>,
+    seller_type: String,
+    user: NettixUser,
+    #[serde(default)]
+    images: Vec<NettixImage>,
+}
+
+impl From<FullNettiautoItem> for VahtiItem {
+    fn from(n: FullNettiautoItem) -> VahtiItem {
+        let published = chrono::DateTime::parse_from_str(&n.date_created, "%FT%T%:z")
+            .unwrap()
+            .timestamp();
+        let img_url = n
+            .images
+            .into_iter()
+            .next()
+            .and_then(|i| i.large.or(i.medium))
+            .map(|i| i.url)
+            .unwrap_or_default();
+        let title = [Some(n.make.name), Some(n.model.name), n.model_type_name]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let location = [n.town, n.region]
+            .into_iter()
+            .flatten()
+            .map(|l| l.fi)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let company_ad = n.seller_type == "dealer";
+        VahtiItem {
+            delivery_method: None,
+            vahti_url: None,
+            deliver_to: None,
+            site_id: 3,
+            title,
+            url: n.ad_url,
+            img_url,
+            published,
+            price: n.price.unwrap_or_default().round() as i64,
+            seller_name: n.user.name,
+            seller_id: Some(n.user.id),
+            seller_created: None,
+            seller_ads: None,
+            company_ad,
+            previous_price: None,
+            repost: None,
+            median_price: None,
+            postal_code: n.post_code,
+            coordinates: None,
+            distance: None,
+            vehicle: Some(Vehicle {
+                year: n.year,
+                mileage: n.kilometers,
+            }),
+            location,
+            ad_type: String::from(if company_ad { "Liike" } else { "Yksityinen" }),
+            ad_id: n.id,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use serde_json::Value;
+
+use super::models::FullNettiautoItem;
+use crate::error::Error;
+use crate::vahti::VahtiItem;
+
+pub fn api_parse_after(search: &str, after: i64) -> Result<Vec<VahtiItem>, Error> {
+    let response_json: Value = serde_json::from_str(search)?;
+    let mut items = vec![];
+    if let Some(ads) = response_json.as_array() {
+        for ad in ads {
+            let fullitem: FullNettiautoItem = serde_json::from_value(ad.to_owned())?;
+            let item = VahtiItem::from(fullitem);
+            if item.published <= after {
+                break;
+            }
+            items.push(item);
+        }
+    }
+    debug!("Parsed {} items", items.len());
+    Ok(items)
+}
+
+/// Returns the amount of items on the first page of the search,
+/// the API doesn't report the total amount
+pub fn api_result_count(search: &str) -> Result<i64, Error> {
+    let response_json: Value = serde_json::from_str(search)?;
+    response_json
+        .as_array()
+        .map(|a| a.len() as i64)
+        .ok_or(Error::InvalidItem)
+}
@@ -0,0 +1,72 @@
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use regex::Regex;
+
+pub static NETTIAUTO_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^https://(www\.)?nettiauto\.com/.+$").unwrap());
+
+use super::api::{fetch, is_valid_url, vahti_to_api};
+use super::parse::{api_parse_after, api_result_count};
+use crate::error::Error;
+use crate::models::DbVahti;
+use crate::vahti::{SearchResult, Vahti};
+
+#[derive(Debug, Clone)]
+pub struct NettiautoVahti {
+    pub id: i32,
+    pub url: String,
+    pub user_id: u64,
+    pub last_updated: i64,
+    pub site_id: i32,
+    pub delivery_method: i32,
+}
+
+#[async_trait]
+impl Vahti for NettiautoVahti {
+    async fn fetch(&self) -> Result<SearchResult, Error> {
+        debug!("Updating {}", self.url);
+        let res = fetch(&vahti_to_api(&self.url))
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Ok(SearchResult {
+            count: api_result_count(&res)?,
+            items: api_parse_after(&res, 0)?,
+        })
+    }
+
+    fn is_valid_url(&self, url: &str) -> bool {
+        NETTIAUTO_REGEX.is_match(url)
+    }
+
+    async fn validate_url(&self) -> Result<bool, Error> {
+        is_valid_url(&self.url).await
+    }
+
+    fn from_db(v: DbVahti) -> Result<Self, Error> {
+        assert_eq!(v.site_id, super::ID);
+
+        Ok(Self {
+            id: v.id,
+            url: v.url,
+            user_id: v.user_id as u64,
+            last_updated: v.last_updated,
+            site_id: super::ID,
+            delivery_method: v.delivery_method,
+        })
+    }
+
+    fn to_db(&self) -> DbVahti {
+        DbVahti::new(
+            self.id,
+            self.url.clone(),
+            self.user_id as i64,
+            self.site_id,
+            self.delivery_method,
+            self.last_updated,
+        )
+    }
+}
@@ -0,0 +1,62 @@
+use crate::database::Database;
+use crate::error::Error;
+use crate::models::NewItemPrice;
+use crate::vahti::VahtiItem;
+
+/// Prices that have not changed in this long are forgotten, the ads are most likely gone
+pub const ITEM_PRICE_RETENTION: i64 = 60 * 24 * 60 * 60;
+
+impl VahtiItem {
+    fn item_price(&self, vahti_id: i32, updated: i64) -> NewItemPrice {
+        NewItemPrice {
+            vahti_id,
+            item_id: self.ad_id,
+            site_id: self.site_id,
+            price: self.price,
+            updated,
+        }
+    }
+
+    /// The headline of a price drop notification
+    pub fn price_drop_title(&self) -> Option<String> {
+        self.previous_price
+            .map(|p| format!("Hinta laski {} € → {} €", p, self.price))
+    }
+}
+
+/// Records the prices of the items delivered by the Vahti and returns the
+/// previously delivered items in `current` whose price has dropped since.
+/// The returned items have their `previous_price` set
+pub async fn check_price_drops(
+    db: &Database,
+    vahti_id: i32,
+    delivered: &[VahtiItem],
+    current: Vec<VahtiItem>,
+) -> Result<Vec<VahtiItem>, Error> {
+    let known = db.fetch_item_prices(vahti_id).await?;
+    let now = chrono::Local::now().timestamp();
+
+    let changed = current
+        .into_iter()
+        .filter_map(|i| {
+            let old = *known.get(&(i.ad_id, i.site_id))?;
+            (i.price != old).then_some((i, old))
+        })
+        .collect::<Vec<_>>();
+
+    let prices = delivered
+        .iter()
+        .chain(changed.iter().map(|(i, _)| i))
+        .map(|i| i.item_price(vahti_id, now))
+        .collect::<Vec<_>>();
+    db.update_item_prices(prices).await?;
+
+    Ok(changed
+        .into_iter()
+        .filter(|(i, old)| i.price < *old)
+        .map(|(mut i, old)| {
+            i.previous_price = Some(old);
+            i
+        })
+        .collect())
+}
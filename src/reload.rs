@@ -0,0 +1,223 @@
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::command::Command;
+use crate::config::{self, Config};
+use crate::delivery::Delivery;
+use crate::error::Error;
+use crate::{supervisor, Torimies};
+
+/// The platforms whose settings differ between two configurations
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigChanges {
+    pub discord: bool,
+    pub telegram: bool,
+}
+
+/// Compares the reloaded configuration to the active one. The settings that
+/// require a restart are kept as they are
+pub fn diff_config(old: &Config, mut new: Config) -> (Config, ConfigChanges) {
+    if new.database_url != old.database_url {
+        warn!("Changing database_url requires a restart, ignoring");
+        new.database_url = old.database_url.clone();
+    }
+
+    if new.http_address != old.http_address {
+        warn!("Changing http_address requires a restart, ignoring");
+        new.http_address = old.http_address.clone();
+    }
+
+    let changes = ConfigChanges {
+        discord: new.discord != old.discord,
+        telegram: new.telegram != old.telegram,
+    };
+    (new, changes)
+}
+
+/// The new clients of a platform, `None` when the platform is disabled
+#[derive(Default)]
+struct PlatformClients {
+    delivery: Option<Box<dyn Delivery + Send + Sync>>,
+    command: Option<Box<dyn Command + Send + Sync>>,
+}
+
+impl Torimies {
+    /// Reloads the configuration and restarts the platforms whose settings have changed
+    ///
+    /// The update interval and the concurrency limits are read from the configuration
+    /// on use and thus take effect without restarting anything
+    pub async fn reload_config(&self) -> Result<(), Error> {
+        let old = config::get();
+        let (new, changes) = diff_config(&old, Config::load()?);
+
+        // NOTE: The clients read their settings from the active configuration. All of them
+        // are initialized before any of the running ones is replaced and the old configuration
+        // is restored if one fails, so the active configuration matches the running clients
+        config::set(new.clone());
+        let clients = match self.init_platforms(&new, &changes).await {
+            Ok(clients) => clients,
+            Err(e) => {
+                config::set((*old).clone());
+                return Err(e);
+            }
+        };
+
+        if let Some(clients) = clients.0 {
+            info!("Discord configuration changed");
+            self.replace_discord(clients).await;
+        }
+
+        if let Some(clients) = clients.1 {
+            info!("Telegram configuration changed");
+            self.replace_telegram(clients).await;
+        }
+
+        config::RELOADED.notify_waiters();
+        info!("Configuration reloaded");
+        Ok(())
+    }
+
+    async fn init_platforms(
+        &self,
+        config: &Config,
+        changes: &ConfigChanges,
+    ) -> Result<(Option<PlatformClients>, Option<PlatformClients>), Error> {
+        let discord = match changes.discord {
+            true => Some(self.init_discord(config).await?),
+            false => None,
+        };
+        let telegram = match changes.telegram {
+            true => Some(self.init_telegram(config).await?),
+            false => None,
+        };
+        Ok((discord, telegram))
+    }
+
+    #[allow(unused_mut)]
+    async fn init_discord(&self, config: &Config) -> Result<PlatformClients, Error> {
+        let mut clients = PlatformClients::default();
+        if !config.discord_enabled() {
+            return Ok(clients);
+        }
+
+        #[cfg(feature = "discord-delivery")]
+        {
+            let dc = crate::delivery::discord::Discord::init().await?;
+            clients.delivery = Some(Box::new(dc));
+        }
+
+        #[cfg(feature = "discord-command")]
+        {
+            let dc = crate::command::discord::Discord::init(&self.database).await?;
+            clients.command = Some(Box::new(dc));
+        }
+
+        Ok(clients)
+    }
+
+    #[allow(unused_variables)]
+    async fn replace_discord(&self, clients: PlatformClients) {
+        #[cfg(feature = "discord-delivery")]
+        match clients.delivery {
+            Some(dc) => {
+                self.delivery.insert(crate::delivery::discord::ID, dc);
+            }
+            None => {
+                self.delivery.remove(&crate::delivery::discord::ID);
+            }
+        }
+
+        #[cfg(feature = "discord-command")]
+        match clients.command {
+            Some(dc) => {
+                self.replace_commander(crate::command::discord::NAME, dc)
+                    .await
+            }
+            None => self.remove_commander(crate::command::discord::NAME).await,
+        }
+    }
+
+    #[allow(unused_mut)]
+    async fn init_telegram(&self, config: &Config) -> Result<PlatformClients, Error> {
+        let mut clients = PlatformClients::default();
+        if !config.telegram_enabled() {
+            return Ok(clients);
+        }
+
+        #[cfg(feature = "telegram-delivery")]
+        {
+            let tg = crate::delivery::telegram::Telegram::init().await?;
+            clients.delivery = Some(Box::new(tg));
+        }
+
+        #[cfg(feature = "telegram-command")]
+        {
+            let tg = crate::command::telegram::Telegram::init(&self.database).await?;
+            clients.command = Some(Box::new(tg));
+        }
+
+        Ok(clients)
+    }
+
+    #[allow(unused_variables)]
+    async fn replace_telegram(&self, clients: PlatformClients) {
+        #[cfg(feature = "telegram-delivery")]
+        match clients.delivery {
+            Some(tg) => {
+                self.delivery.insert(crate::delivery::telegram::ID, tg);
+            }
+            None => {
+                self.delivery.remove(&crate::delivery::telegram::ID);
+            }
+        }
+
+        #[cfg(feature = "telegram-command")]
+        match clients.command {
+            Some(tg) => {
+                self.replace_commander(crate::command::telegram::NAME, tg)
+                    .await
+            }
+            None => self.remove_commander(crate::command::telegram::NAME).await,
+        }
+    }
+
+    /// Replaces a running commander by shutting it down and letting its supervisor
+    /// start the new one, or starts the commander if it is not running
+    async fn replace_commander(&self, name: &str, commander: Box<dyn Command + Send + Sync>) {
+        let old = self
+            .command_manager
+            .insert(name.to_string(), commander.manager());
+
+        if let Some(old) = old {
+            self.command.insert(name.to_string(), commander);
+            old.shutdown().await;
+        } else {
+            info!("Starting {} commander", name);
+            let man = self.clone();
+            let name = name.to_string();
+            tokio::task::spawn(supervisor::supervise_commander(man, name, commander));
+        }
+    }
+
+    async fn remove_commander(&self, name: &str) {
+        if let Some((_, old)) = self.command_manager.remove(name) {
+            info!("Stopping {} commander", name);
+            old.shutdown().await;
+        }
+    }
+}
+
+/// Reloads the configuration on SIGHUP or when requested with an admin command
+pub async fn reload_handler(man: Torimies) {
+    let mut hangup = signal(SignalKind::hangup()).expect("Failed to register SIGHUP handler");
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => info!("Recieved SIGHUP"),
+            _ = config::RELOAD_REQUESTED.notified() => info!("Configuration reload requested"),
+            _ = man.shutdown.cancelled() => break,
+        }
+
+        if let Err(e) = man.reload_config().await {
+            error!("Failed to reload the configuration: {}", e);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use crate::database::Database;
+use crate::error::Error;
+use crate::models::{NewRepostHistoryEntry, RepostHistoryEntry};
+use crate::vahti::VahtiItem;
+
+/// Items older than this are forgotten and their reposts are delivered as new items
+pub const REPOST_HISTORY_RETENTION: i64 = 30 * 24 * 60 * 60;
+
+/// How a Vahti handles the ads its sellers have deleted and posted again
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RepostMode {
+    /// Reposts are delivered like any other new item
+    #[default]
+    Off,
+    /// Reposts are delivered with a note about the previous post
+    Label,
+    /// Reposts are not delivered at all
+    Suppress,
+}
+
+impl RepostMode {
+    /// Parses a mode given as a command argument
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "pois" => Some(Self::Off),
+            "merkitse" => Some(Self::Label),
+            "piilota" => Some(Self::Suppress),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for RepostMode {
+    fn from(mode: i32) -> Self {
+        match mode {
+            1 => Self::Label,
+            2 => Self::Suppress,
+            _ => Self::Off,
+        }
+    }
+}
+
+impl From<RepostMode> for i32 {
+    fn from(mode: RepostMode) -> Self {
+        match mode {
+            RepostMode::Off => 0,
+            RepostMode::Label => 1,
+            RepostMode::Suppress => 2,
+        }
+    }
+}
+
+/// The previous post of a reposted item
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Repost {
+    pub seen: i64,
+    pub price: i64,
+}
+
+/// Lowercases the title and strips punctuation and extra whitespace,
+/// so that minor edits to the title do not hide a repost
+pub fn normalize_title(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl VahtiItem {
+    /// Items of unknown sellers can not be told apart from the items
+    /// of other sellers, so they are not tracked
+    fn repost_history_entry(&self, vahti_id: i32, seen: i64) -> Option<NewRepostHistoryEntry> {
+        Some(NewRepostHistoryEntry {
+            vahti_id,
+            item_id: self.ad_id,
+            site_id: self.site_id,
+            seller_id: self.seller_id?,
+            title: normalize_title(&self.title),
+            price: self.price,
+            img_url: self.img_url.clone(),
+            seen,
+        })
+    }
+
+    /// Whether the item looks like a repost of the earlier one: the same seller
+    /// and title, and either the same price or the same image
+    fn is_repost_of(&self, entry: &RepostHistoryEntry) -> bool {
+        entry.item_id != self.ad_id
+            && entry.site_id == self.site_id
+            && self.seller_id == Some(entry.seller_id)
+            && entry.title == normalize_title(&self.title)
+            && (entry.price == self.price
+                || (!self.img_url.is_empty() && entry.img_url == self.img_url))
+    }
+
+    /// The headline of a labeled repost
+    pub fn repost_title(&self) -> Option<String> {
+        self.repost.as_ref().map(|r| {
+            let days = (chrono::Local::now().timestamp() - r.seen) / 86400;
+            let when = match days {
+                0 => String::from("tänään"),
+                1 => String::from("eilen"),
+                d => format!("{} päivää sitten", d),
+            };
+            format!(
+                "Uudelleen julkaistu, nähty aiemmin {} hintaan {} €",
+                when, r.price
+            )
+        })
+    }
+}
+
+/// Records the new items of the Vahti and labels or removes the ones
+/// that are reposts of items seen earlier, depending on the mode
+pub async fn check_reposts(
+    db: &Database,
+    vahti_id: i32,
+    mode: RepostMode,
+    items: Vec<VahtiItem>,
+) -> Result<Vec<VahtiItem>, Error> {
+    if mode == RepostMode::Off || items.is_empty() {
+        return Ok(items);
+    }
+
+    let history = db.fetch_repost_history(vahti_id).await?;
+    let now = chrono::Local::now().timestamp();
+
+    db.add_repost_history(
+        items
+            .iter()
+            .filter_map(|i| i.repost_history_entry(vahti_id, now))
+            .collect(),
+    )
+    .await?;
+
+    Ok(items
+        .into_iter()
+        .filter_map(|mut i| {
+            let Some(previous) = history
+                .iter()
+                .filter(|e| i.is_repost_of(e))
+                .max_by_key(|e| e.seen)
+            else {
+                return Some(i);
+            };
+
+            debug!("Item {} is a repost of {}", i.ad_id, previous.item_id);
+            match mode {
+                RepostMode::Suppress => None,
+                _ => {
+                    i.repost = Some(Repost {
+                        seen: previous.seen,
+                        price: previous.price,
+                    });
+                    Some(i)
+                }
+            }
+        })
+        .collect())
+}
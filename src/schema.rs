@@ -1,6 +1,15 @@
 #![allow(non_snake_case)]
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    Bans (id) {
+        id -> Integer,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+        created -> BigInt,
+    }
+}
+
 diesel::table! {
     Blacklists (id) {
         id -> Integer,
@@ -10,6 +19,148 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    BlockRules (id) {
+        id -> Integer,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+        kind -> Text,
+        value -> Text,
+        created -> BigInt,
+    }
+}
+
+diesel::table! {
+    DeliveredItems (id) {
+        id -> Integer,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+        site_id -> Integer,
+        item_id -> BigInt,
+        title -> Text,
+        url -> Text,
+        vahti_url -> Text,
+        price -> BigInt,
+        location -> Text,
+        seller_name -> Text,
+        published -> BigInt,
+        delivered -> BigInt,
+    }
+}
+
+diesel::table! {
+    HomeLocations (id) {
+        id -> Integer,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+        postal_code -> Text,
+    }
+}
+
+diesel::table! {
+    ItemHistories (id) {
+        id -> Integer,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+        item_id -> BigInt,
+        site_id -> Integer,
+        timestamp -> BigInt,
+    }
+}
+
+diesel::table! {
+    ItemPrices (id) {
+        id -> Integer,
+        vahti_id -> Integer,
+        item_id -> BigInt,
+        site_id -> Integer,
+        price -> BigInt,
+        updated -> BigInt,
+    }
+}
+
+diesel::table! {
+    LinkCodes (code) {
+        code -> Text,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+        created -> BigInt,
+    }
+}
+
+diesel::table! {
+    MatchedItems (id) {
+        id -> Integer,
+        vahti_id -> Integer,
+        item_id -> BigInt,
+        site_id -> Integer,
+        title -> Text,
+        price -> BigInt,
+        published -> BigInt,
+    }
+}
+
+diesel::table! {
+    PendingDeliveries (id) {
+        id -> Integer,
+        deliver_to -> BigInt,
+        delivery_method -> Integer,
+        item -> Text,
+        created -> BigInt,
+    }
+}
+
+diesel::table! {
+    RepostHistory (id) {
+        id -> Integer,
+        vahti_id -> Integer,
+        item_id -> BigInt,
+        site_id -> Integer,
+        seller_id -> Integer,
+        title -> Text,
+        price -> BigInt,
+        img_url -> Text,
+        seen -> BigInt,
+    }
+}
+
+diesel::table! {
+    Sellers (id) {
+        id -> Integer,
+        seller_id -> Integer,
+        site_id -> Integer,
+        name -> Text,
+        account_created -> Nullable<Text>,
+        ad_count -> Nullable<Integer>,
+        updated -> BigInt,
+    }
+}
+
+diesel::table! {
+    UserIdentities (id) {
+        id -> Integer,
+        account_id -> Integer,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+    }
+}
+
+diesel::table! {
+    Users (id) {
+        id -> Integer,
+        created -> BigInt,
+    }
+}
+
+diesel::table! {
+    VahtiDestinations (id) {
+        id -> Integer,
+        vahti_id -> Integer,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+    }
+}
+
 diesel::table! {
     Vahdit (id) {
         id -> Integer,
@@ -18,7 +169,49 @@ diesel::table! {
         last_updated -> BigInt,
         site_id -> Integer,
         delivery_method -> Integer,
+        paused -> Bool,
+        resume_at -> Nullable<BigInt>,
+        expires_at -> Nullable<BigInt>,
+        empty_since -> Nullable<BigInt>,
+        reminded -> Bool,
+        created -> BigInt,
+        price_drops -> Bool,
+        reposts -> Integer,
+        max_distance -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    WatchedItems (id) {
+        id -> Integer,
+        user_id -> BigInt,
+        delivery_method -> Integer,
+        site_id -> Integer,
+        listing_id -> BigInt,
+        title -> Text,
+        url -> Text,
+        price -> BigInt,
+        created -> BigInt,
+        checked -> BigInt,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(Blacklists, Vahdit,);
+diesel::allow_tables_to_appear_in_same_query!(
+    Bans,
+    Blacklists,
+    BlockRules,
+    DeliveredItems,
+    HomeLocations,
+    ItemHistories,
+    ItemPrices,
+    LinkCodes,
+    MatchedItems,
+    PendingDeliveries,
+    RepostHistory,
+    Sellers,
+    UserIdentities,
+    Users,
+    VahtiDestinations,
+    Vahdit,
+    WatchedItems,
+);
@@ -0,0 +1,11 @@
+//! Keyword vahtis, which are defined by search terms instead of a url and search
+//! every enabled site that supports keyword searches
+
+pub mod query;
+pub mod vahti;
+
+/// The `site_id` of keyword vahtis. They are not bound to a single site and the items
+/// they deliver carry the id of their own site, so the id is negative to never collide
+/// with the ids of the sites
+pub const ID: i32 = -1;
+pub const NAME: &str = "search";
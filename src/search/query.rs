@@ -0,0 +1,178 @@
+use std::fmt;
+use std::sync::LazyLock;
+
+use itertools::Itertools;
+use regex::Regex;
+use url::form_urlencoded::Serializer;
+
+use crate::vahti::VahtiItem;
+
+/// The sites that support searching by keywords. Nettiauto is left out, its
+/// searches are made with the make and the model of the vehicle
+pub const SITES: &[i32] = &[
+    #[cfg(feature = "tori")]
+    crate::tori::ID,
+    #[cfg(feature = "huutonet")]
+    crate::huutonet::ID,
+    #[cfg(feature = "muusikoiden")]
+    crate::muusikoiden::ID,
+];
+
+/// Urls without the scheme, e.g. `tori.fi/koko_suomi?q=kitara` or `www.huuto.net`
+static DOMAIN_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(www\.|[a-z0-9-]+(\.[a-z0-9-]+)*\.((fi|com|net|org)([/?#:].*)?$|[a-z]{2,}/))")
+        .unwrap()
+});
+
+const PRICE_PREFIX: &str = "hinta:";
+const LOCATION_PREFIX: &str = "sijainti:";
+
+/// A site-independent search, stored in the place of the url of the Vahti
+/// in its canonical form, e.g. `thinkpad t14 hinta:100-500 sijainti:helsinki`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeywordQuery {
+    pub keywords: String,
+    pub min_price: Option<i64>,
+    pub max_price: Option<i64>,
+    pub location: Option<String>,
+}
+
+impl KeywordQuery {
+    /// Parses the search terms. Anything that looks like a url is not a keyword
+    /// search, so that a mistyped url is not stored as a search for its text
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .any(|t| t.contains("://") || DOMAIN_REGEX.is_match(&t))
+        {
+            return None;
+        }
+
+        let mut query = Self::default();
+        let mut keywords = vec![];
+        for term in text.split_whitespace() {
+            let lower = term.to_lowercase();
+            if let Some(range) = lower.strip_prefix(PRICE_PREFIX) {
+                let (min, max) = range.split_once('-')?;
+                query.min_price = parse_price(min)?;
+                query.max_price = parse_price(max)?;
+            } else if let Some(location) = lower.strip_prefix(LOCATION_PREFIX) {
+                query.location = Some(location.to_string()).filter(|l| !l.is_empty());
+            } else {
+                keywords.push(lower);
+            }
+        }
+        query.keywords = keywords.join(" ");
+
+        if query.keywords.is_empty() {
+            return None;
+        }
+        if let (Some(min), Some(max)) = (query.min_price, query.max_price) {
+            if min > max {
+                return None;
+            }
+        }
+        Some(query)
+    }
+
+    /// The searches of the query on each of the enabled sites
+    pub fn site_urls(&self) -> Vec<(i32, String)> {
+        SITES
+            .iter()
+            .filter(|s| crate::config::get().site_enabled(**s))
+            .filter_map(|s| Some((*s, self.site_url(*s)?)))
+            .collect()
+    }
+
+    fn site_url(&self, site_id: i32) -> Option<String> {
+        let params: Option<(&str, &str, &str, &str)> = match site_id {
+            #[cfg(feature = "tori")]
+            crate::tori::ID => Some((
+                "https://www.tori.fi/recommerce/forsale/search?",
+                "q",
+                "price_from",
+                "price_to",
+            )),
+            #[cfg(feature = "huutonet")]
+            crate::huutonet::ID => Some((
+                "https://www.huuto.net/haku?",
+                "words",
+                "price_min",
+                "price_max",
+            )),
+            // NOTE: The price range is only applied after fetching the results
+            #[cfg(feature = "muusikoiden")]
+            crate::muusikoiden::ID => {
+                Some(("https://muusikoiden.net/tori/haku.php?", "keyword", "", ""))
+            }
+            _ => None,
+        };
+        let (base, keywords, min, max) = params?;
+
+        let mut url = Serializer::for_suffix(String::from(base), base.len());
+        url.append_pair(keywords, &self.keywords);
+        for (key, price) in [(min, self.min_price), (max, self.max_price)] {
+            if let Some(p) = price.filter(|_| !key.is_empty()) {
+                url.append_pair(key, &p.to_string());
+            }
+        }
+        Some(url.finish())
+    }
+
+    /// Whether the item is within the price range and the location of the query.
+    /// The sites may not support all of the filters, so they are checked for every item
+    pub fn matches(&self, item: &VahtiItem) -> bool {
+        self.min_price.is_none_or(|p| item.price >= p)
+            && self.max_price.is_none_or(|p| item.price <= p)
+            && self
+                .location
+                .as_ref()
+                .is_none_or(|l| item.location.to_lowercase().contains(l.as_str()))
+    }
+}
+
+fn parse_price(price: &str) -> Option<Option<i64>> {
+    match price.trim_end_matches('€') {
+        "" => Some(None),
+        p => p.parse().ok().filter(|p| *p >= 0).map(Some),
+    }
+}
+
+impl fmt::Display for KeywordQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.keywords)?;
+        if self.min_price.is_some() || self.max_price.is_some() {
+            write!(
+                f,
+                " {}{}-{}",
+                PRICE_PREFIX,
+                self.min_price.map(|p| p.to_string()).unwrap_or_default(),
+                self.max_price.map(|p| p.to_string()).unwrap_or_default()
+            )?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, " {}{}", LOCATION_PREFIX, location)?;
+        }
+        Ok(())
+    }
+}
+
+/// The stored form of the Vahti, the canonical form of keyword searches and urls as they are
+pub fn canonical_url(vahti: &str) -> String {
+    KeywordQuery::parse(vahti)
+        .map(|q| q.to_string())
+        .unwrap_or_else(|| vahti.to_string())
+}
+
+/// Merges the results of the sites into the items matching the query, the same
+/// item posted on several sites is only kept from the first site
+pub fn merge_results(query: &KeywordQuery, results: Vec<Vec<VahtiItem>>) -> Vec<VahtiItem> {
+    results
+        .into_iter()
+        .flatten()
+        .filter(|i| query.matches(i))
+        .unique_by(|i| (i.title.trim().to_lowercase(), i.price))
+        .collect()
+}
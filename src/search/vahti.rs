@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+
+use super::query::{merge_results, KeywordQuery};
+use crate::error::Error;
+use crate::models::DbVahti;
+use crate::vahti::{SearchResult, Vahti, VahtiItem};
+
+#[derive(Debug, Clone)]
+pub struct SearchVahti {
+    pub id: i32,
+    pub url: String,
+    pub user_id: u64,
+    pub last_updated: i64,
+    pub site_id: i32,
+    pub delivery_method: i32,
+}
+
+#[async_trait]
+impl Vahti for SearchVahti {
+    async fn fetch(&self) -> Result<SearchResult, Error> {
+        debug!("Updating {}", self.url);
+        let query = KeywordQuery::parse(&self.url).ok_or(Error::UnknownUrl(self.url.clone()))?;
+        let searches = query.site_urls();
+        let responses =
+            futures::future::join_all(searches.iter().map(|(s, url)| search_site(*s, url))).await;
+
+        // NOTE: The results of the other sites are used even if some of the sites fail
+        let mut count = 0;
+        let mut results = vec![];
+        let mut error = None;
+        for ((site_id, _), response) in searches.iter().zip(responses) {
+            match response {
+                Ok((c, items)) => {
+                    count += c;
+                    results.push(items);
+                }
+                Err(e) => {
+                    debug!(
+                        "Failed to search {} for `{}`: {}",
+                        crate::metrics::site_name(*site_id),
+                        self.url,
+                        e
+                    );
+                    error = Some(e);
+                }
+            }
+        }
+        if let (true, Some(e)) = (results.is_empty(), error) {
+            return Err(e);
+        }
+
+        Ok(SearchResult {
+            count,
+            items: merge_results(&query, results),
+        })
+    }
+
+    fn is_valid_url(&self, url: &str) -> bool {
+        KeywordQuery::parse(url).is_some()
+    }
+
+    async fn validate_url(&self) -> Result<bool, Error> {
+        Ok(KeywordQuery::parse(&self.url).is_some())
+    }
+
+    fn from_db(v: DbVahti) -> Result<Self, Error> {
+        assert_eq!(v.site_id, super::ID);
+
+        Ok(Self {
+            id: v.id,
+            url: v.url,
+            user_id: v.user_id as u64,
+            last_updated: v.last_updated,
+            site_id: super::ID,
+            delivery_method: v.delivery_method,
+        })
+    }
+
+    fn to_db(&self) -> DbVahti {
+        DbVahti::new(
+            self.id,
+            self.url.clone(),
+            self.user_id as i64,
+            self.site_id,
+            self.delivery_method,
+            self.last_updated,
+        )
+    }
+}
+
+/// Searches the site returning the total amount of matches and the items of the first page
+async fn search_site(site_id: i32, url: &str) -> Result<(i64, Vec<VahtiItem>), Error> {
+    match site_id {
+        #[cfg(feature = "tori")]
+        crate::tori::ID => {
+            use crate::tori::recommerce::{api, parse};
+            let res = reqwest::get(api::vahti_to_api(url)).await?.text().await?;
+            Ok((
+                parse::api_result_count(&res)?,
+                parse::api_parse_after(&res, 0)?,
+            ))
+        }
+        #[cfg(feature = "huutonet")]
+        crate::huutonet::ID => {
+            use crate::huutonet::{api, parse};
+            let res = reqwest::get(api::vahti_to_api(url)).await?.text().await?;
+            Ok((
+                parse::api_result_count(&res)?,
+                parse::api_parse_after(&res, 0)?,
+            ))
+        }
+        #[cfg(feature = "muusikoiden")]
+        crate::muusikoiden::ID => {
+            use crate::muusikoiden::{api, parse};
+            let page = api::fetch_search(url).await?;
+            Ok((parse::result_count(&page), parse::parse_after(&page, 0)?))
+        }
+        _ => Err(Error::UnknownUrl(url.to_string())),
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use futures::stream::{self, StreamExt};
+use itertools::Itertools;
+
+use crate::database::Database;
+use crate::error::Error;
+use crate::models::{NewSeller, Seller};
+use crate::vahti::VahtiItem;
+
+/// Cached sellers older than this are refreshed from the site when looked up
+const SELLER_CACHE_TTL: i64 = 7 * 24 * 60 * 60;
+
+pub const UNKNOWN_SELLER: &str = "Unknown Seller";
+
+impl VahtiItem {
+    pub fn seller(&self) -> Option<NewSeller> {
+        Some(NewSeller {
+            seller_id: self.seller_id?,
+            site_id: self.site_id,
+            name: self.seller_name.clone(),
+            account_created: self.seller_created.clone(),
+            ad_count: self.seller_ads,
+            updated: chrono::Local::now().timestamp(),
+        })
+    }
+
+    /// The public page of the seller, if the site has one
+    pub fn seller_url(&self) -> Option<String> {
+        match self.site_id {
+            #[cfg(feature = "tori")]
+            crate::tori::ID
+                if crate::tori::recommerce::item::RECOMMERCE_ITEM_REGEX.is_match(&self.url) =>
+            {
+                Some(format!(
+                    "https://www.tori.fi/profile/ads?userId={}",
+                    self.seller_id?
+                ))
+            }
+            #[cfg(feature = "tori")]
+            crate::tori::ID => Some(format!("https://www.tori.fi/li?&aid={}", self.seller_id?)),
+            #[cfg(feature = "huutonet")]
+            crate::huutonet::ID => Some(format!(
+                "https://www.huuto.net/kayttaja/{}",
+                self.seller_id?
+            )),
+            #[cfg(feature = "muusikoiden")]
+            crate::muusikoiden::ID => Some(format!(
+                "https://muusikoiden.net/jasenet/profiili.php?id={}",
+                self.seller_id?
+            )),
+            // NOTE: Nettiauto has no public seller pages
+            _ => None,
+        }
+    }
+
+    /// The account creation date and the amount of ads of the seller, if known
+    pub fn seller_details(&self) -> Option<String> {
+        let details = [
+            self.seller_created.as_ref().map(|c| format!("Jäsen {}", c)),
+            self.seller_ads.map(|a| format!("{} ilmoitusta", a)),
+        ]
+        .into_iter()
+        .flatten()
+        .join(", ");
+
+        Some(details).filter(|d| !d.is_empty())
+    }
+}
+
+async fn fetch_seller(sellerid: i32, siteid: i32) -> Result<Option<NewSeller>, Error> {
+    match siteid {
+        #[cfg(feature = "tori")]
+        crate::tori::ID => crate::tori::seller::fetch_seller(sellerid).await,
+        #[cfg(feature = "huutonet")]
+        crate::huutonet::ID => crate::huutonet::seller::fetch_seller(sellerid).await,
+        _ => Ok(None),
+    }
+}
+
+/// Returns the seller from the cache, refreshing it from the site
+/// if it is missing or stale. A stale entry is returned if the refresh fails
+pub async fn get_seller(db: &Database, sellerid: i32, siteid: i32) -> Option<Seller> {
+    let cached = db.fetch_seller(sellerid, siteid).await.ok().flatten();
+    if cached
+        .as_ref()
+        .is_some_and(|s| s.updated > chrono::Local::now().timestamp() - SELLER_CACHE_TTL)
+    {
+        return cached;
+    }
+
+    match fetch_seller(sellerid, siteid).await {
+        Ok(Some(seller)) => {
+            if let Err(e) = db.update_sellers(vec![seller]).await {
+                error!("Failed to cache seller {}: {}", sellerid, e);
+            }
+            db.fetch_seller(sellerid, siteid).await.ok().flatten()
+        }
+        Ok(None) => cached,
+        Err(e) => {
+            debug!("Failed to fetch seller {}: {}", sellerid, e);
+            cached
+        }
+    }
+}
+
+/// Returns the names of the given (seller_id, site_id) pairs in the same order
+pub async fn seller_names(db: &Database, sellers: &[(i32, i32)]) -> Vec<String> {
+    stream::iter(sellers.iter().copied())
+        .map(|(sellerid, siteid)| async move {
+            get_seller(db, sellerid, siteid)
+                .await
+                .map(|s| s.name)
+                .unwrap_or_else(|| String::from(UNKNOWN_SELLER))
+        })
+        .buffered(crate::config::get().futures_max_buffer_size)
+        .collect()
+        .await
+}
+
+/// Caches the sellers of the items, the information in the items is always the freshest
+pub async fn cache_sellers(db: &Database, items: &[VahtiItem]) {
+    let sellers = items
+        .iter()
+        .filter(|i| !i.seller_name.is_empty())
+        .unique_by(|i| (i.seller_id, i.site_id))
+        .filter_map(|i| i.seller())
+        .collect::<Vec<_>>();
+
+    if let Err(e) = db.update_sellers(sellers).await {
+        error!("Failed to cache sellers: {}", e);
+    }
+}
+
+/// Fills in the seller information the items lack from the cache
+pub async fn fill_seller_info(db: &Database, items: &mut [VahtiItem]) {
+    let missing = items
+        .iter()
+        .filter(|i| {
+            i.seller_name.is_empty() || i.seller_created.is_none() || i.seller_ads.is_none()
+        })
+        .filter_map(|i| Some((i.seller_id?, i.site_id)))
+        .unique()
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return;
+    }
+
+    let sellers = match db.fetch_sellers(missing).await {
+        Ok(s) => s
+            .into_iter()
+            .map(|s| ((s.seller_id, s.site_id), s))
+            .collect::<HashMap<_, _>>(),
+        Err(e) => {
+            error!("Failed to fetch cached sellers: {}", e);
+            return;
+        }
+    };
+
+    for item in items.iter_mut() {
+        let Some(seller) = item
+            .seller_id
+            .and_then(|id| sellers.get(&(id, item.site_id)))
+        else {
+            continue;
+        };
+        if item.seller_name.is_empty() {
+            item.seller_name = seller.name.clone();
+        }
+        if item.seller_created.is_none() {
+            item.seller_created = seller.account_created.clone();
+        }
+        if item.seller_ads.is_none() {
+            item.seller_ads = seller.ad_count;
+        }
+    }
+}
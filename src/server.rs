@@ -0,0 +1,144 @@
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::Error;
+use crate::history::{parse_date, parse_site, search_history, HistoryQuery};
+use crate::Torimies;
+
+async fn metrics() -> String {
+    crate::metrics::gather()
+}
+
+fn status((ok, report): (bool, Value)) -> (StatusCode, Json<Value>) {
+    let code = if ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(report))
+}
+
+async fn healthz(State(man): State<Torimies>) -> (StatusCode, Json<Value>) {
+    status(man.health_report().await)
+}
+
+async fn readyz(State(man): State<Torimies>) -> (StatusCode, Json<Value>) {
+    status(man.readiness_report().await)
+}
+
+#[derive(Deserialize)]
+struct HistoryParams {
+    /// The user as `platform:user_id`
+    user: String,
+    keyword: Option<String>,
+    min_price: Option<i64>,
+    max_price: Option<i64>,
+    site: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    page: Option<u32>,
+}
+
+impl HistoryParams {
+    fn query(self) -> Result<HistoryQuery, Error> {
+        Ok(HistoryQuery {
+            keyword: self.keyword.filter(|k| !k.is_empty()),
+            min_price: self.min_price,
+            max_price: self.max_price,
+            site_id: self.site.as_deref().map(parse_site).transpose()?,
+            since: self
+                .since
+                .as_deref()
+                .map(|d| parse_date(d, false))
+                .transpose()?,
+            until: self
+                .until
+                .as_deref()
+                .map(|d| parse_date(d, true))
+                .transpose()?,
+            page: self.page.unwrap_or(1).max(1),
+        })
+    }
+}
+
+/// Whether the request carries the configured `HTTP_TOKEN`
+fn authorized(headers: &HeaderMap) -> bool {
+    let Some(token) = crate::config::get().http_token.clone() else {
+        return false;
+    };
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .is_some_and(|t| t == token)
+}
+
+fn error(code: StatusCode, message: impl ToString) -> (StatusCode, Json<Value>) {
+    (code, Json(json!({ "error": message.to_string() })))
+}
+
+/// Searches the delivery history of a user, requires the `HTTP_TOKEN` as a bearer token
+async fn history(
+    State(man): State<Torimies>,
+    headers: HeaderMap,
+    Query(params): Query<HistoryParams>,
+) -> (StatusCode, Json<Value>) {
+    if !authorized(&headers) {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    }
+
+    let Some((userid, delivery_method)) = crate::admin::parse_admin(&params.user) else {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "expected user as `platform:user_id`",
+        );
+    };
+    let query = match params.query() {
+        Ok(q) => q,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+
+    match search_history(&man.database, userid, delivery_method, query).await {
+        Ok(page) => (
+            StatusCode::OK,
+            Json(json!({
+                "page": page.page,
+                "pages": page.pages,
+                "total": page.total,
+                "items": page.items,
+            })),
+        ),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+/// Serves the HTTP endpoints on `HTTP_ADDRESS` until shutdown
+pub async fn serve(man: Torimies, address: String) {
+    let shutdown = man.shutdown.clone();
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/history", get(history))
+        .with_state(man);
+
+    let listener = match tokio::net::TcpListener::bind(&address).await {
+        Ok(l) => l,
+        Err(e) => {
+            error!("Failed to bind the HTTP server to {}: {}", address, e);
+            return;
+        }
+    };
+
+    info!("Serving HTTP on {}", address);
+    if let Err(e) = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await
+    {
+        error!("HTTP server failed: {}", e);
+    }
+}
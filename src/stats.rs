@@ -0,0 +1,192 @@
+use std::fmt;
+
+use crate::database::Database;
+use crate::error::Error;
+use crate::models::NewMatchedItem;
+use crate::vahti::VahtiItem;
+
+/// Matched items are kept for this long for the price statistics
+pub const MATCHED_ITEM_RETENTION: i64 = 365 * 24 * 60 * 60;
+
+/// The window of the median new deliveries are compared against
+const BARGAIN_WINDOW: i64 = 30 * 24 * 60 * 60;
+
+/// The amount of priced items needed before an item can be marked as a bargain
+const MIN_BARGAIN_SAMPLES: usize = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceStats {
+    pub days: u32,
+    pub count: usize,
+    pub median: i64,
+    pub p25: i64,
+    pub p75: i64,
+    pub min: i64,
+    pub max: i64,
+    /// The change of the median from the first half of the window to the second, in percent
+    pub trend: Option<i64>,
+}
+
+/// The nearest-rank percentile of sorted prices
+fn percentile(sorted: &[i64], p: usize) -> i64 {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn median(sorted: &[i64]) -> i64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2
+    } else {
+        sorted[mid]
+    }
+}
+
+impl PriceStats {
+    /// Calculates the statistics of the (price, published) pairs published during
+    /// the `days` before `until`. Items without a price are ignored
+    pub fn new(items: &[(i64, i64)], days: u32, until: i64) -> Option<Self> {
+        let since = until - i64::from(days) * 86400;
+        let halfway = since + (until - since) / 2;
+        let items = items
+            .iter()
+            .filter(|(price, published)| *price > 0 && *published >= since)
+            .collect::<Vec<_>>();
+
+        let sorted = |pred: &dyn Fn(i64) -> bool| {
+            let mut prices = items
+                .iter()
+                .filter(|(_, published)| pred(*published))
+                .map(|(price, _)| *price)
+                .collect::<Vec<_>>();
+            prices.sort_unstable();
+            prices
+        };
+
+        let prices = sorted(&|_| true);
+        if prices.is_empty() {
+            return None;
+        }
+
+        let earlier = sorted(&|p| p < halfway);
+        let later = sorted(&|p| p >= halfway);
+        let trend = (!earlier.is_empty() && !later.is_empty()).then(|| {
+            let (earlier, later) = (median(&earlier), median(&later));
+            (later - earlier) * 100 / earlier
+        });
+
+        Some(Self {
+            days,
+            count: prices.len(),
+            median: median(&prices),
+            p25: percentile(&prices, 25),
+            p75: percentile(&prices, 75),
+            min: prices[0],
+            max: prices[prices.len() - 1],
+            trend,
+        })
+    }
+}
+
+impl fmt::Display for PriceStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Prices of the last {} days ({} items):",
+            self.days, self.count
+        )?;
+        writeln!(f, "Median: {} €", self.median)?;
+        writeln!(f, "25th to 75th percentile: {} – {} €", self.p25, self.p75)?;
+        write!(f, "Range: {} – {} €", self.min, self.max)?;
+        if let Some(trend) = self.trend {
+            write!(f, "\nTrend: {:+} %", trend)?;
+        }
+        Ok(())
+    }
+}
+
+impl VahtiItem {
+    /// How far below the median price of the Vahti the item is, if it is a bargain
+    pub fn bargain_text(&self) -> Option<String> {
+        self.median_price
+            .map(|m| format!("{} % alle mediaanin ({} €)", 100 - self.price * 100 / m, m))
+    }
+}
+
+/// Records the items matched by the Vahti for the price statistics
+pub async fn record_matches(
+    db: &Database,
+    vahti_id: i32,
+    items: &[VahtiItem],
+) -> Result<(), Error> {
+    db.add_matched_items(
+        items
+            .iter()
+            .map(|i| NewMatchedItem {
+                vahti_id,
+                item_id: i.ad_id,
+                site_id: i.site_id,
+                title: i.title.clone(),
+                price: i.price,
+                published: i.published,
+            })
+            .collect(),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Sets the median price on the items priced well below the recent median of the Vahti
+pub async fn mark_bargains(
+    db: &Database,
+    vahti_id: i32,
+    items: &mut [VahtiItem],
+) -> Result<(), Error> {
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    let now = chrono::Local::now().timestamp();
+    let matched = db
+        .fetch_matched_items(vahti_id, now - BARGAIN_WINDOW)
+        .await?
+        .into_iter()
+        .map(|i| (i.price, i.published))
+        .collect::<Vec<_>>();
+
+    let Some(stats) = PriceStats::new(&matched, (BARGAIN_WINDOW / 86400) as u32, now)
+        .filter(|s| s.count >= MIN_BARGAIN_SAMPLES)
+    else {
+        return Ok(());
+    };
+
+    let threshold = stats.median * i64::from(crate::config::get().vahti.bargain_percent) / 100;
+    for item in items
+        .iter_mut()
+        .filter(|i| i.price > 0 && i.price <= threshold)
+    {
+        item.median_price = Some(stats.median);
+    }
+    Ok(())
+}
+
+pub async fn price_stats(db: Database, url: &str, userid: u64, days: u32) -> Result<String, Error> {
+    let Ok(vahti) = db.fetch_vahti(url, userid as i64).await else {
+        return Ok(String::from(
+            "A Vahti is not defined with that url. Make sure the url is correct",
+        ));
+    };
+
+    let now = chrono::Local::now().timestamp();
+    let matched = db
+        .fetch_matched_items(vahti.id, now - i64::from(days) * 86400)
+        .await?
+        .into_iter()
+        .map(|i| (i.price, i.published))
+        .collect::<Vec<_>>();
+
+    Ok(match PriceStats::new(&matched, days, now) {
+        Some(stats) => stats.to_string(),
+        None => format!("No priced items found in the last {} days", days),
+    })
+}
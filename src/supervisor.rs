@@ -0,0 +1,137 @@
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
+
+use futures::FutureExt;
+
+use crate::command::Command;
+use crate::health::CommanderStatus;
+use crate::Torimies;
+
+pub const MIN_BACKOFF: Duration = Duration::from_secs(1);
+pub const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Exponential backoff between restarts of a supervised task
+///
+/// The backoff is reset once the task has stayed up for longer than MAX_BACKOFF
+pub struct Backoff {
+    current: Duration,
+    pub started: Instant,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self {
+            current: MIN_BACKOFF,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn started(&mut self) {
+        self.started = Instant::now();
+    }
+
+    /// Returns the time to wait before the next restart
+    pub fn next_delay(&mut self) -> Duration {
+        if self.started.elapsed() > MAX_BACKOFF {
+            self.current = MIN_BACKOFF;
+        }
+
+        let delay = self.current;
+        self.current = (self.current * 2).min(MAX_BACKOFF);
+        delay
+    }
+
+    async fn wait(&mut self) {
+        tokio::time::sleep(self.next_delay()).await;
+    }
+}
+
+fn shutting_down(man: &Torimies) -> bool {
+    man.shutdown.is_cancelled()
+}
+
+/// Runs the update loop, restarting it with backoff if it panics
+pub async fn supervise_update(mut man: Torimies) {
+    let mut backoff = Backoff::new();
+    loop {
+        backoff.started();
+        if AssertUnwindSafe(crate::update_loop(&mut man))
+            .catch_unwind()
+            .await
+            .is_ok()
+        {
+            break;
+        }
+
+        error!("Update loop panicked");
+        if shutting_down(&man) {
+            break;
+        }
+
+        backoff.wait().await;
+        info!("Restarting the update loop");
+    }
+
+    info!("Update supervisor exited")
+}
+
+/// Whether the commander should stop, either because Torimies is shutting down
+/// or because the commander has been removed
+fn commander_stopped(man: &Torimies, name: &str) -> bool {
+    shutting_down(man) || !man.command_manager.contains_key(name)
+}
+
+/// Runs a commander, restarting it with backoff if it fails, panics or exits
+/// while Torimies is still running
+///
+/// A replacement commander inserted into `Torimies.command` is started
+/// in place of the current one once it has been shut down
+pub async fn supervise_commander(
+    man: Torimies,
+    name: String,
+    mut commander: Box<dyn Command + Send + Sync>,
+) {
+    let mut backoff = Backoff::new();
+    loop {
+        backoff.started();
+        man.health
+            .set_commander_status(&name, CommanderStatus::Running);
+
+        match AssertUnwindSafe(commander.start()).catch_unwind().await {
+            Ok(Ok(())) => info!("{} commander exited", name),
+            Ok(Err(e)) => error!("{} commander failed: {}", name, e),
+            Err(_) => error!("{} commander panicked", name),
+        }
+
+        if commander_stopped(&man, &name) {
+            break;
+        }
+
+        if let Some((_, c)) = man.command.remove(&name) {
+            info!("Replacing {} commander", name);
+            commander = c;
+            continue;
+        }
+
+        man.health
+            .set_commander_status(&name, CommanderStatus::Restarting);
+        backoff.wait().await;
+
+        if commander_stopped(&man, &name) {
+            break;
+        }
+
+        if let Some((_, c)) = man.command.remove(&name) {
+            commander = c;
+        }
+        info!("Restarting {} commander", name);
+    }
+
+    if shutting_down(&man) {
+        man.health
+            .set_commander_status(&name, CommanderStatus::Stopped);
+    } else {
+        man.health.commanders.remove(&name);
+    }
+    info!("{} commander supervisor exited", name)
+}
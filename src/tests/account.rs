@@ -0,0 +1,98 @@
+use super::database::{test_database, test_user};
+use crate::account::{link_account, new_link_code};
+use crate::error::Error;
+
+#[tokio::test]
+async fn link_codes() {
+    let t = test_database().await;
+    let first = (test_user() as u64, 1);
+    let second = (test_user() as u64, 2);
+
+    let code = new_link_code(t.db.clone(), first.0, first.1).await.unwrap();
+    assert_eq!(code.len(), 8);
+    assert!(code
+        .chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+
+    // A new code replaces the previous one
+    let old = code;
+    let code = new_link_code(t.db.clone(), first.0, first.1).await.unwrap();
+    if old != code {
+        assert!(matches!(
+            link_account(t.db.clone(), &old, second.0, second.1).await,
+            Err(Error::InvalidLinkCode)
+        ));
+    }
+
+    // The own code of the user can not be used
+    assert!(matches!(
+        link_account(t.db.clone(), &code, first.0, first.1).await,
+        Err(Error::InvalidLinkCode)
+    ));
+
+    let code = new_link_code(t.db.clone(), first.0, first.1).await.unwrap();
+    let typed = format!(" {} ", code.to_lowercase());
+    link_account(t.db.clone(), &typed, second.0, second.1)
+        .await
+        .unwrap();
+    assert!(t
+        .db
+        .fetch_linked_identities(second.0 as i64, second.1)
+        .await
+        .unwrap()
+        .contains(&(first.0 as i64, first.1)));
+
+    // The codes can only be used once
+    assert!(matches!(
+        link_account(t.db.clone(), &code, second.0, second.1).await,
+        Err(Error::InvalidLinkCode)
+    ));
+}
+
+#[tokio::test]
+async fn expired_link_codes() {
+    let t = test_database().await;
+    let user = test_user();
+    let code = format!("expired-{}", user);
+
+    t.db.add_link_code(&code, user, 1).await.unwrap();
+    let valid_after = chrono::Local::now().timestamp() + 1;
+    assert_eq!(t.db.take_link_code(&code, valid_after).await.unwrap(), None);
+
+    // Expired codes are removed
+    assert_eq!(t.db.take_link_code(&code, 0).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn merge_accounts() {
+    let t = test_database().await;
+    let a = (test_user(), 1);
+    let b = (test_user(), 2);
+    let c = (test_user(), 1);
+    let d = (test_user(), 2);
+
+    // Concurrent links create separate accounts
+    let (first, second) = tokio::join!(t.db.link_identities(a, b), t.db.link_identities(c, d));
+    first.unwrap();
+    second.unwrap();
+    let mut linked = t.db.fetch_linked_identities(a.0, a.1).await.unwrap();
+    linked.sort();
+    let mut expected = vec![a, b];
+    expected.sort();
+    assert_eq!(linked, expected);
+
+    // Linking identities of two accounts merges them
+    t.db.link_identities(b, c).await.unwrap();
+    let mut linked = t.db.fetch_linked_identities(d.0, d.1).await.unwrap();
+    linked.sort();
+    let mut expected = vec![a, b, c, d];
+    expected.sort();
+    assert_eq!(linked, expected);
+
+    // Linking already linked identities changes nothing
+    t.db.link_identities(a, d).await.unwrap();
+    assert_eq!(
+        t.db.fetch_linked_identities(a.0, a.1).await.unwrap().len(),
+        4
+    );
+}
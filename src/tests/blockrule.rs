@@ -0,0 +1,50 @@
+use crate::blockrule::{wildcard_match, BlockRule};
+use crate::vahti::VahtiItem;
+
+#[test]
+fn wildcards() {
+    assert!(wildcard_match("gigantti*", "gigantti outlet vaasa"));
+    assert!(wildcard_match("*outlet*", "gigantti outlet vaasa"));
+    assert!(wildcard_match("g*t*a", "gigantti outlet vaasa"));
+    assert!(wildcard_match("*", ""));
+    assert!(wildcard_match("exact", "exact"));
+    assert!(!wildcard_match("exact", "exactly"));
+    assert!(!wildcard_match("outlet*", "gigantti outlet vaasa"));
+    assert!(!wildcard_match("*vaasa?", "gigantti outlet vaasa"));
+}
+
+#[test]
+fn parse_rules() {
+    assert_eq!(
+        BlockRule::parse("yritykset", "").unwrap(),
+        BlockRule::Company
+    );
+    assert_eq!(
+        BlockRule::parse("myyja", " Gigantti* ").unwrap(),
+        BlockRule::SellerName("Gigantti*".to_string())
+    );
+    assert!(BlockRule::parse("sijainti", "  ").is_err());
+    assert!(BlockRule::parse("tuntematon", "arvo").is_err());
+}
+
+#[test]
+fn matching() {
+    let item = VahtiItem {
+        title: "Audio Pro G10 älykäs monihuonekaiutin".to_string(),
+        seller_name: "Gigantti outlet Vaasa".to_string(),
+        company_ad: true,
+        location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
+        ..super::item(90554189, 167)
+    };
+    assert!(BlockRule::Company.matches(&item));
+    assert!(!BlockRule::Company.matches(&VahtiItem {
+        company_ad: false,
+        ..item.clone()
+    }));
+    assert!(BlockRule::SellerName("gigantti*".to_string()).matches(&item));
+    assert!(!BlockRule::SellerName("gigantti".to_string()).matches(&item));
+    assert!(BlockRule::Location("vaasa".to_string()).matches(&item));
+    assert!(!BlockRule::Location("Helsinki".to_string()).matches(&item));
+    assert!(BlockRule::Keyword("KAIUTIN".to_string()).matches(&item));
+    assert!(!BlockRule::Keyword("kuulokkeet".to_string()).matches(&item));
+}
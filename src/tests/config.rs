@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use crate::config::{Config, NettiautoConfig};
+use crate::error::Error;
+
+/// Loads the configuration file with the given contents and environment variables
+fn load(contents: &str, vars: &[(&str, &str)]) -> Result<Config, Error> {
+    let name = format!("torimies-config-{}.toml", rand::random::<u64>());
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, contents).unwrap();
+
+    let mut vars = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>();
+    vars.insert(
+        String::from("CONFIG_FILE"),
+        path.to_string_lossy().into_owned(),
+    );
+    let config = Config::load_with(&|name| vars.get(name).filter(|v| !v.is_empty()).cloned());
+
+    std::fs::remove_file(&path).unwrap();
+    config
+}
+
+/// The least configuration that passes the validation
+const MINIMAL: &str = r#"
+database_url = "database.sqlite"
+
+[discord]
+enabled = false
+
+[telegram]
+enabled = false
+"#;
+
+fn config_error(contents: &str, vars: &[(&str, &str)]) -> String {
+    match load(contents, vars) {
+        Err(Error::Config(e)) => e,
+        Err(e) => panic!("unexpected error {}", e),
+        Ok(_) => panic!("the configuration should be invalid"),
+    }
+}
+
+#[test]
+fn parse_file() {
+    let config = load(
+        r#"
+        database_url = "torimies.sqlite"
+        update_interval = 60
+        admins = ["discord:1234"]
+
+        [vahti]
+        max_per_user = 5
+        min_interval = 30
+
+        [discord]
+        token = "token"
+        application_id = 1234
+
+        [telegram]
+        enabled = false
+        "#,
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(config.database_url, "torimies.sqlite");
+    assert_eq!(config.update_interval, 60);
+    assert_eq!(config.admins, vec![String::from("discord:1234")]);
+    assert_eq!(config.vahti.max_per_user, Some(5));
+    assert_eq!(config.vahti.min_interval, 30);
+    assert_eq!(config.discord.token.as_deref(), Some("token"));
+    assert!(!config.telegram.enabled);
+
+    // The settings not in the file keep their defaults
+    assert_eq!(config.futures_max_buffer_size, 50);
+    assert_eq!(config.vahti.extend_days, 30);
+}
+
+#[test]
+fn unknown_settings() {
+    let e = config_error(&format!("update_intervall = 60\n{}", MINIMAL), &[]);
+    assert!(e.starts_with("Failed to parse"));
+    assert!(e.contains("update_intervall"));
+}
+
+#[test]
+fn env_over_file() {
+    let config = load(
+        &format!("update_interval = 60\n{}", MINIMAL),
+        &[
+            ("UPDATE_INTERVAL", "30"),
+            ("DATABASE_URL", "env.sqlite"),
+            ("MAX_VAHTIS_PER_USER", "3"),
+            ("ADMINS", "discord:1, telegram:2"),
+            // Empty variables are ignored
+            ("HTTP_ADDRESS", ""),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(config.update_interval, 30);
+    assert_eq!(config.database_url, "env.sqlite");
+    assert_eq!(config.vahti.max_per_user, Some(3));
+    assert_eq!(
+        config.admins,
+        vec![String::from("discord:1"), String::from("telegram:2")]
+    );
+    assert_eq!(config.http_address, None);
+
+    let e = config_error(MINIMAL, &[("UPDATE_INTERVAL", "often")]);
+    assert_eq!(e, "Invalid UPDATE_INTERVAL: often");
+}
+
+#[test]
+fn site_defaults() {
+    let config = load(&format!("{}\n[tori]\nenabled = false", MINIMAL), &[]).unwrap();
+    assert!(!config.tori.enabled);
+    assert!(config.huutonet.enabled);
+    assert!(config.muusikoiden.enabled);
+    assert!(!config.nettiauto.enabled);
+
+    let config = load(MINIMAL, &[("HUUTONET_ENABLED", "false")]).unwrap();
+    assert!(config.tori.enabled);
+    assert!(!config.huutonet.enabled);
+}
+
+#[test]
+fn nettiauto_disabled_by_default() {
+    assert!(!NettiautoConfig::default().enabled);
+    assert!(!Config::default().nettiauto.enabled);
+}
+
+#[test]
+fn validate() {
+    assert!(load(MINIMAL, &[]).is_ok());
+
+    assert_eq!(
+        config_error(
+            "[discord]\nenabled = false\n[telegram]\nenabled = false",
+            &[]
+        ),
+        "database_url (DATABASE_URL) must be set"
+    );
+
+    for (var, value, expected) in [
+        (
+            "UPDATE_INTERVAL",
+            "0",
+            "update_interval (UPDATE_INTERVAL) must be positive",
+        ),
+        (
+            "FUTURES_MAX_BUFFER_SIZE",
+            "0",
+            "futures_max_buffer_size (FUTURES_MAX_BUFFER_SIZE) must be positive",
+        ),
+        (
+            "VAHTI_BARGAIN_PERCENT",
+            "101",
+            "vahti.bargain_percent (VAHTI_BARGAIN_PERCENT) must be at most 100",
+        ),
+        (
+            "ADMINS",
+            "1234",
+            "Invalid admin `1234`, expected `platform:user_id`",
+        ),
+    ] {
+        assert_eq!(config_error(MINIMAL, &[(var, value)]), expected);
+    }
+
+    #[cfg(any(feature = "discord-delivery", feature = "discord-command"))]
+    assert_eq!(
+        config_error(MINIMAL, &[("DISCORD_ENABLED", "true")]),
+        "discord.token (DISCORD_TOKEN) must be set when Discord is enabled"
+    );
+
+    #[cfg(feature = "discord-command")]
+    assert_eq!(
+        config_error(
+            MINIMAL,
+            &[("DISCORD_ENABLED", "true"), ("DISCORD_TOKEN", "token")]
+        ),
+        "discord.application_id (APPLICATION_ID) must be set when Discord is enabled"
+    );
+
+    #[cfg(any(feature = "telegram-delivery", feature = "telegram-command"))]
+    assert_eq!(
+        config_error(MINIMAL, &[("TELEGRAM_ENABLED", "true")]),
+        "telegram.token (TELOXIDE_TOKEN) must be set when Telegram is enabled"
+    );
+
+    #[cfg(feature = "nettiauto")]
+    assert_eq!(
+        config_error(
+            MINIMAL,
+            &[("NETTIAUTO_ENABLED", "true"), ("NETTIX_CLIENT_ID", "id")]
+        ),
+        "nettiauto.client_id (NETTIX_CLIENT_ID) and nettiauto.client_secret \
+         (NETTIX_CLIENT_SECRET) must be set when Nettiauto is enabled"
+    );
+}
+
+#[test]
+fn missing_file() {
+    let path = std::env::temp_dir().join("torimies-missing-config.toml");
+    let path = path.to_string_lossy().into_owned();
+    let e = match Config::load_with(&|name| (name == "CONFIG_FILE").then(|| path.clone())) {
+        Err(Error::Config(e)) => e,
+        _ => panic!("a missing configuration file should be an error"),
+    };
+    assert!(e.starts_with("Failed to read"));
+}
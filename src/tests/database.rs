@@ -0,0 +1,833 @@
+use std::sync::LazyLock;
+
+use tokio::sync::Mutex;
+
+use super::item;
+use crate::database::Database;
+use crate::geo::apply_distances;
+use crate::history::HistoryQuery;
+use crate::models::{
+    NewDeliveredItem, NewItemHistoryEntry, NewPendingDelivery, NewSeller, NewWatchedItem,
+};
+use crate::pricedrop::check_price_drops;
+use crate::repost::{check_reposts, RepostMode};
+use crate::stats::{mark_bargains, price_stats, record_matches};
+use crate::vahti::VahtiItem;
+
+// NOTE: The tests share a single database on PostgreSQL, so the migrations
+// must not be run concurrently
+static CONNECT: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/// Held by the tests taking the pending deliveries, which would take each other's
+/// deliveries on a shared database
+pub(super) static PENDING: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+const DELIVERY: i32 = 1;
+const SITE: i32 = 1;
+
+/// A database for a single test, the SQLite database file is removed on drop
+pub(super) struct TestDatabase {
+    pub(super) db: Database,
+    path: Option<String>,
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "postgres"))]
+pub(super) async fn test_database() -> TestDatabase {
+    let path = std::env::temp_dir()
+        .join(format!("torimies-test-{}.sqlite", rand::random::<u64>()))
+        .to_string_lossy()
+        .into_owned();
+    let _guard = CONNECT.lock().await;
+    TestDatabase {
+        db: Database::connect(&path).await.unwrap(),
+        path: Some(path),
+    }
+}
+
+#[cfg(feature = "postgres")]
+pub(super) async fn test_database() -> TestDatabase {
+    let url = std::env::var("TEST_DATABASE_URL")
+        .expect("TEST_DATABASE_URL must be set to run the tests against PostgreSQL");
+    let _guard = CONNECT.lock().await;
+    TestDatabase {
+        db: Database::connect(&url).await.unwrap(),
+        path: None,
+    }
+}
+
+/// A random user id so that the tests do not interfere on a shared database
+pub(super) fn test_user() -> i64 {
+    rand::random::<u32>() as i64
+}
+
+#[tokio::test]
+async fn vahti_entries() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, SITE, DELIVERY, None)
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert_eq!(vahti.user_id, user);
+    assert!(!vahti.paused);
+
+    let vahtis = t.db.fetch_vahti_entries_by_user_id(user).await.unwrap();
+    assert_eq!(vahtis.len(), 1);
+    assert!(t
+        .db
+        .fetch_all_vahtis()
+        .await
+        .unwrap()
+        .iter()
+        .any(|v| v.id == vahti.id));
+
+    assert_eq!(
+        t.db.remove_vahti_entry(&url, user, DELIVERY).await.unwrap(),
+        1
+    );
+    assert!(t.db.fetch_vahti(&url, user).await.is_err());
+}
+
+#[tokio::test]
+async fn pause_and_resume_vahti() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, SITE, DELIVERY, None)
+        .await
+        .unwrap();
+    let id = t.db.fetch_vahti(&url, user).await.unwrap().id;
+
+    t.db.pause_vahti(&url, user, DELIVERY, None).await.unwrap();
+    assert!(t.db.fetch_vahti(&url, user).await.unwrap().paused);
+    assert!(!t
+        .db
+        .fetch_all_vahtis()
+        .await
+        .unwrap()
+        .iter()
+        .any(|v| v.id == id));
+
+    t.db.resume_vahti(&url, user, DELIVERY, false)
+        .await
+        .unwrap();
+    assert!(!t.db.fetch_vahti(&url, user).await.unwrap().paused);
+
+    t.db.remove_vahti_entry(&url, user, DELIVERY).await.unwrap();
+}
+
+#[tokio::test]
+async fn vahtis_updated() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, SITE, DELIVERY, None)
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+
+    // Timestamps older than the current one are ignored
+    t.db.vahtis_updated(vec![(vahti.id, vahti.last_updated - 10)])
+        .await
+        .unwrap();
+    assert_eq!(
+        t.db.fetch_vahti(&url, user).await.unwrap().last_updated,
+        vahti.last_updated
+    );
+
+    t.db.vahtis_updated(vec![(vahti.id, vahti.last_updated + 10)])
+        .await
+        .unwrap();
+    assert_eq!(
+        t.db.fetch_vahti(&url, user).await.unwrap().last_updated,
+        vahti.last_updated + 10
+    );
+
+    t.db.remove_vahti_entry(&url, user, DELIVERY).await.unwrap();
+}
+
+#[cfg(feature = "tori")]
+#[tokio::test]
+async fn legacy_tori_urls() {
+    let t = test_database().await;
+    let user = test_user();
+    let legacy = "https://www.tori.fi/koko_suomi?q=thinkpad&st=s";
+    let migrated = "https://www.tori.fi/recommerce/forsale/search?q=thinkpad&trade_type=1";
+    let unmapped = "https://www.tori.fi/uusimaa?q=thinkpad";
+    let duplicate = "https://www.tori.fi/koko_suomi?q=thinkpad&st=s&cg=0";
+    let paused = "https://www.tori.fi/koko_suomi?q=polkupy%F6r%E4";
+    let migrated_paused = "https://www.tori.fi/recommerce/forsale/search?q=polkupy%C3%B6r%C3%A4";
+
+    for url in [legacy, unmapped, paused] {
+        t.db.add_vahti_entry(url, user, crate::tori::ID, DELIVERY, None)
+            .await
+            .unwrap();
+    }
+    t.db.pause_vahti(paused, user, DELIVERY, None)
+        .await
+        .unwrap();
+    crate::tori::recommerce::migrate_legacy_urls(&t.db)
+        .await
+        .unwrap();
+    assert!(t.db.fetch_vahti(legacy, user).await.is_err());
+    assert!(t.db.fetch_vahti(migrated, user).await.is_ok());
+    assert!(t.db.fetch_vahti(unmapped, user).await.is_ok());
+
+    // Paused vahtis are migrated as well and stay paused
+    assert!(t.db.fetch_vahti(paused, user).await.is_err());
+    assert!(
+        t.db.fetch_vahti(migrated_paused, user)
+            .await
+            .unwrap()
+            .paused
+    );
+
+    // The url is kept when the user already has the migrated Vahti
+    t.db.add_vahti_entry(duplicate, user, crate::tori::ID, DELIVERY, None)
+        .await
+        .unwrap();
+    crate::tori::recommerce::migrate_legacy_urls(&t.db)
+        .await
+        .unwrap();
+    assert!(t.db.fetch_vahti(duplicate, user).await.is_ok());
+
+    for url in [migrated, unmapped, duplicate, migrated_paused] {
+        t.db.remove_vahti_entry(url, user, DELIVERY).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn price_drops() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, SITE, DELIVERY, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        t.db.set_vahti_price_drops(&url, user, DELIVERY, true)
+            .await
+            .unwrap(),
+        1
+    );
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert!(vahti.price_drops);
+
+    // Only the prices of delivered items are tracked
+    let delivered = vec![item(1, 100), item(2, 50)];
+    assert!(
+        check_price_drops(&t.db, vahti.id, &delivered, delivered.clone())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let current = vec![item(1, 80), item(2, 60), item(3, 10)];
+    let drops = check_price_drops(&t.db, vahti.id, &[], current.clone())
+        .await
+        .unwrap();
+    assert_eq!(drops.len(), 1);
+    assert_eq!(drops[0].ad_id, 1);
+    assert_eq!(drops[0].previous_price, Some(100));
+
+    // The raised price is remembered, so dropping back to it is still a drop
+    let drops = check_price_drops(&t.db, vahti.id, &[], vec![item(1, 80), item(2, 55)])
+        .await
+        .unwrap();
+    assert_eq!(drops.len(), 1);
+    assert_eq!(drops[0].previous_price, Some(60));
+
+    t.db.remove_vahti_entry(&url, user, DELIVERY).await.unwrap();
+    assert!(t.db.fetch_item_prices(vahti.id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn reposts() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, SITE, DELIVERY, None)
+        .await
+        .unwrap();
+    t.db.set_vahti_reposts(&url, user, DELIVERY, RepostMode::Label.into())
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert_eq!(RepostMode::from(vahti.reposts), RepostMode::Label);
+
+    let original = item(1, 100);
+    let items = check_reposts(&t.db, vahti.id, RepostMode::Label, vec![original.clone()])
+        .await
+        .unwrap();
+    assert_eq!(items[0].repost, None);
+
+    // The same title with a different price and image is another item
+    let mut other = item(3, 90);
+    other.img_url = String::from("https://example.com/other.jpg");
+    let repost = VahtiItem {
+        title: String::from("POLKUPYÖRÄ!"),
+        ..item(2, 100)
+    };
+    let items = check_reposts(
+        &t.db,
+        vahti.id,
+        RepostMode::Label,
+        vec![repost.clone(), other.clone()],
+    )
+    .await
+    .unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].repost.as_ref().unwrap().price, 100);
+    assert_eq!(items[1].repost, None);
+
+    let repost = item(4, 100);
+    let items = check_reposts(&t.db, vahti.id, RepostMode::Suppress, vec![repost])
+        .await
+        .unwrap();
+    assert!(items.is_empty());
+
+    t.db.remove_vahti_entry(&url, user, DELIVERY).await.unwrap();
+    assert!(t
+        .db
+        .fetch_repost_history(vahti.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn reposts_of_unknown_sellers() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, SITE, DELIVERY, None)
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+
+    // Two private sellers of the new Tori platform selling the same thing
+    let private = |ad_id| VahtiItem {
+        seller_name: String::from("Yksityinen"),
+        seller_id: None,
+        ..item(ad_id, 100)
+    };
+    check_reposts(&t.db, vahti.id, RepostMode::Label, vec![private(1)])
+        .await
+        .unwrap();
+    let items = check_reposts(&t.db, vahti.id, RepostMode::Suppress, vec![private(2)])
+        .await
+        .unwrap();
+    assert_eq!(items, vec![private(2)]);
+    assert!(t
+        .db
+        .fetch_repost_history(vahti.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    t.db.remove_vahti_entry(&url, user, DELIVERY).await.unwrap();
+}
+
+#[tokio::test]
+async fn matched_items() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, SITE, DELIVERY, None)
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+
+    let now = chrono::Local::now().timestamp();
+    let matched = (1..=10)
+        .map(|i| VahtiItem {
+            published: now - i * 3600,
+            ..item(i, 100 + i)
+        })
+        .collect::<Vec<_>>();
+    record_matches(&t.db, vahti.id, &matched).await.unwrap();
+    // Recording the same items again does not duplicate them
+    record_matches(&t.db, vahti.id, &matched[..2])
+        .await
+        .unwrap();
+    assert_eq!(
+        t.db.fetch_matched_items(vahti.id, 0).await.unwrap().len(),
+        10
+    );
+
+    let mut delivered = vec![item(11, 60), item(12, 100)];
+    mark_bargains(&t.db, vahti.id, &mut delivered)
+        .await
+        .unwrap();
+    assert_eq!(delivered[0].median_price, Some(105));
+    assert_eq!(delivered[1].median_price, None);
+
+    assert!(price_stats(t.db.clone(), &url, user as u64, 30)
+        .await
+        .unwrap()
+        .contains("Median: 105 €"));
+
+    t.db.remove_vahti_entry(&url, user, DELIVERY).await.unwrap();
+    assert!(t
+        .db
+        .fetch_matched_items(vahti.id, 0)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+fn delivered_item(user: (i64, i32), title: &str, price: i64, delivered: i64) -> NewDeliveredItem {
+    NewDeliveredItem {
+        user_id: user.0,
+        delivery_method: user.1,
+        site_id: SITE,
+        item_id: delivered,
+        title: title.to_string(),
+        url: format!("https://example.com/{}", delivered),
+        vahti_url: String::from("https://example.com/"),
+        price,
+        location: String::from("Helsinki"),
+        seller_name: String::from("myyjä"),
+        published: delivered,
+        delivered,
+    }
+}
+
+#[tokio::test]
+async fn delivered_items() {
+    let t = test_database().await;
+    let first = (test_user(), 1);
+    let second = (test_user(), 2);
+    t.db.link_identities(first, second).await.unwrap();
+
+    t.db.add_delivered_items(vec![
+        delivered_item(first, "Polkupyörä", 100, 1),
+        delivered_item(second, "Sähköpolkupyörä", 900, 2),
+        delivered_item(first, "Pyöränrengas 50%", 20, 3),
+        delivered_item((test_user(), 1), "Polkupyörä", 100, 4),
+    ])
+    .await
+    .unwrap();
+
+    let search = |query: HistoryQuery, page_size| {
+        t.db.search_delivered_items(first.0, first.1, query, page_size)
+    };
+
+    // The history is shared with the linked identities, newest first
+    let (items, total) = search(HistoryQuery::default(), 2).await.unwrap();
+    assert_eq!(total, 3);
+    assert_eq!(
+        items.iter().map(|i| i.delivered).collect::<Vec<_>>(),
+        vec![3, 2]
+    );
+    let (items, _) = search(
+        HistoryQuery {
+            page: 2,
+            ..Default::default()
+        },
+        2,
+    )
+    .await
+    .unwrap();
+    assert_eq!(items.len(), 1);
+
+    let (items, total) = search(
+        HistoryQuery {
+            keyword: Some(String::from("POLKUPYÖRÄ")),
+            max_price: Some(500),
+            ..Default::default()
+        },
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(items[0].delivered, 1);
+
+    // The wildcards of LIKE are matched literally
+    let (_, total) = search(
+        HistoryQuery {
+            keyword: Some(String::from("%")),
+            ..Default::default()
+        },
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(total, 1);
+
+    let (_, total) = search(
+        HistoryQuery {
+            since: Some(2),
+            until: Some(3),
+            ..Default::default()
+        },
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(total, 1);
+}
+
+#[tokio::test]
+async fn watched_items() {
+    let t = test_database().await;
+    let user = test_user();
+    let watched = |listing_id, checked| NewWatchedItem {
+        user_id: user,
+        delivery_method: DELIVERY,
+        site_id: SITE,
+        listing_id,
+        title: String::from("Polkupyörä"),
+        url: format!("https://example.com/{}", listing_id),
+        price: 100,
+        created: 0,
+        checked,
+    };
+
+    assert_eq!(t.db.add_watched_item(watched(1, 10)).await.unwrap(), 1);
+    assert_eq!(t.db.add_watched_item(watched(1, 10)).await.unwrap(), 0);
+    assert_eq!(t.db.add_watched_item(watched(2, 30)).await.unwrap(), 1);
+
+    let db = &t.db;
+    let due = |before| async move {
+        db.fetch_due_watched_items(before)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|w| w.user_id == user)
+            .collect::<Vec<_>>()
+    };
+    let first = due(20).await;
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].listing_id, 1);
+
+    t.db.update_watched_item(first[0].id, "Sähköpolkupyörä", 80, 40)
+        .await
+        .unwrap();
+    let updated = due(50).await;
+    assert_eq!(updated.len(), 2);
+    let updated = updated.iter().find(|w| w.id == first[0].id).unwrap();
+    assert_eq!(updated.title, "Sähköpolkupyörä");
+    assert_eq!(updated.price, 80);
+
+    // Only the watcher can stop watching the item
+    assert_eq!(
+        t.db.remove_watched_item(first[0].id, test_user(), DELIVERY)
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        t.db.remove_watched_item(first[0].id, user, DELIVERY)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(due(50).await.len(), 1);
+}
+
+#[tokio::test]
+async fn distances() {
+    let t = test_database().await;
+    let (first, second) = ((test_user(), 1), (test_user(), 2));
+    let located = |ad_id, postal_code: Option<&str>| VahtiItem {
+        postal_code: postal_code.map(String::from),
+        ..item(ad_id, 100)
+    };
+    let items = vec![
+        located(1, Some("00630")),
+        located(2, Some("33100")),
+        located(3, None),
+        // Items without a postal code are located by their coordinates
+        VahtiItem {
+            coordinates: Some((60.18, 24.95)),
+            ..located(4, None)
+        },
+        VahtiItem {
+            coordinates: Some((65.01, 25.47)),
+            ..located(5, None)
+        },
+    ];
+
+    // Without a home postal code the items are left untouched
+    assert_eq!(
+        apply_distances(&t.db, first.0 as u64, first.1, Some(50), items.clone())
+            .await
+            .unwrap(),
+        items
+    );
+
+    t.db.set_home_location(first.0, first.1, Some(String::from("00100")))
+        .await
+        .unwrap();
+    t.db.set_home_location(first.0, first.1, Some(String::from("02100")))
+        .await
+        .unwrap();
+    let filtered = apply_distances(&t.db, first.0 as u64, first.1, Some(50), items.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        filtered.iter().map(|i| i.ad_id).collect::<Vec<_>>(),
+        vec![1, 3, 4]
+    );
+    assert!(filtered[0].distance.is_some_and(|d| d < 50));
+    assert_eq!(filtered[1].distance, None);
+    assert!(filtered[2].distance.is_some_and(|d| d < 50));
+
+    // Linked identities share the home postal code
+    assert_eq!(
+        t.db.fetch_home_location(second.0, second.1).await.unwrap(),
+        None
+    );
+    t.db.link_identities(first, second).await.unwrap();
+    assert_eq!(
+        t.db.fetch_home_location(second.0, second.1).await.unwrap(),
+        Some(String::from("02100"))
+    );
+
+    t.db.set_home_location(first.0, first.1, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        t.db.fetch_home_location(first.0, first.1).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn seller_blacklist() {
+    let t = test_database().await;
+    let user = test_user();
+
+    t.db.add_seller_to_blacklist(user, 123, SITE).await.unwrap();
+    assert_eq!(
+        t.db.fetch_user_blacklist(user, DELIVERY).await.unwrap(),
+        vec![(123, SITE)]
+    );
+
+    t.db.remove_seller_from_blacklist(user, DELIVERY, 123, SITE)
+        .await
+        .unwrap();
+    assert!(t
+        .db
+        .fetch_user_blacklist(user, DELIVERY)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn sellers() {
+    let t = test_database().await;
+    let sellerid = test_user() as i32;
+    let seller = NewSeller {
+        seller_id: sellerid,
+        site_id: SITE,
+        name: String::from("myyjä"),
+        account_created: Some(String::from("lokakuusta 2021")),
+        ad_count: Some(3),
+        updated: 0,
+    };
+
+    t.db.update_sellers(vec![seller.clone()]).await.unwrap();
+    // Unknown details do not overwrite the cached ones
+    t.db.update_sellers(vec![NewSeller {
+        name: String::from("uusi nimi"),
+        account_created: None,
+        ad_count: None,
+        updated: 1,
+        ..seller
+    }])
+    .await
+    .unwrap();
+
+    let cached = t.db.fetch_seller(sellerid, SITE).await.unwrap().unwrap();
+    assert_eq!(cached.name, "uusi nimi");
+    assert_eq!(cached.account_created.as_deref(), Some("lokakuusta 2021"));
+    assert_eq!(cached.ad_count, Some(3));
+    assert_eq!(cached.updated, 1);
+
+    assert_eq!(
+        t.db.fetch_sellers(vec![(sellerid, SITE), (sellerid, SITE + 1)])
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(t
+        .db
+        .fetch_seller(sellerid, SITE + 1)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn unknown_sellers_are_not_cached() {
+    let t = test_database().await;
+    let unknown = VahtiItem {
+        seller_name: String::from("Yksityinen"),
+        seller_id: None,
+        ..item(1, 100)
+    };
+
+    crate::seller::cache_sellers(&t.db, std::slice::from_ref(&unknown)).await;
+    assert!(t.db.fetch_seller(0, SITE).await.unwrap().is_none());
+
+    let mut items = vec![unknown.clone()];
+    crate::seller::fill_seller_info(&t.db, &mut items).await;
+    assert_eq!(items, vec![unknown]);
+}
+
+#[tokio::test]
+async fn block_rules() {
+    let t = test_database().await;
+    let first = (test_user(), 1);
+    let second = (test_user(), 2);
+
+    t.db.add_block_rule(first.0, first.1, "keyword", "rikki")
+        .await
+        .unwrap();
+    t.db.link_identities(first, second).await.unwrap();
+
+    // The rules are shared between linked identities
+    let rules =
+        t.db.fetch_user_block_rules(second.0, second.1)
+            .await
+            .unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0].kind, "keyword");
+    assert_eq!(rules[0].value, "rikki");
+
+    // Other users cannot remove the rule
+    assert_eq!(
+        t.db.remove_block_rule(rules[0].id, test_user(), 1)
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        t.db.remove_block_rule(rules[0].id, second.0, second.1)
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(t
+        .db
+        .fetch_user_block_rules(first.0, first.1)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn bans() {
+    let t = test_database().await;
+    let user = test_user();
+
+    assert!(!t.db.is_banned(user, DELIVERY).await.unwrap());
+    t.db.add_ban(user, DELIVERY).await.unwrap();
+    assert!(t.db.is_banned(user, DELIVERY).await.unwrap());
+    t.db.remove_ban(user, DELIVERY).await.unwrap();
+    assert!(!t.db.is_banned(user, DELIVERY).await.unwrap());
+}
+
+#[tokio::test]
+async fn bans_of_linked_identities() {
+    let t = test_database().await;
+    let first = (test_user(), 1);
+    let second = (test_user(), 2);
+    t.db.link_identities(first, second).await.unwrap();
+
+    // A ban on one identity covers the whole account
+    t.db.add_ban(first.0, first.1).await.unwrap();
+    assert!(t.db.is_banned(second.0, second.1).await.unwrap());
+    assert!(!t.db.is_banned(second.0, first.1).await.unwrap());
+
+    assert_eq!(t.db.remove_ban(second.0, second.1).await.unwrap(), 1);
+    assert!(!t.db.is_banned(first.0, first.1).await.unwrap());
+}
+
+#[tokio::test]
+async fn link_identities() {
+    let t = test_database().await;
+    let first = (test_user(), 1);
+    let second = (test_user(), 2);
+    let code = format!("code-{}", first.0);
+
+    t.db.add_link_code(&code, first.0, first.1).await.unwrap();
+    assert_eq!(t.db.take_link_code(&code, 0).await.unwrap(), Some(first));
+    assert_eq!(t.db.take_link_code(&code, 0).await.unwrap(), None);
+
+    t.db.link_identities(first, second).await.unwrap();
+    let mut linked =
+        t.db.fetch_linked_identities(first.0, first.1)
+            .await
+            .unwrap();
+    linked.sort();
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(linked, expected);
+}
+
+#[tokio::test]
+async fn pending_deliveries_and_item_history() {
+    let t = test_database().await;
+    let user = test_user();
+    let _guard = PENDING.lock().await;
+
+    t.db.add_pending_deliveries(&[NewPendingDelivery {
+        deliver_to: user,
+        delivery_method: DELIVERY,
+        item: String::from("{}"),
+        created: 0,
+    }])
+    .await
+    .unwrap();
+    assert!(t
+        .db
+        .take_pending_deliveries()
+        .await
+        .unwrap()
+        .iter()
+        .any(|p| p.deliver_to == user));
+    assert!(!t
+        .db
+        .take_pending_deliveries()
+        .await
+        .unwrap()
+        .iter()
+        .any(|p| p.deliver_to == user));
+
+    t.db.replace_item_history(&[NewItemHistoryEntry {
+        user_id: user,
+        delivery_method: DELIVERY,
+        item_id: 1,
+        site_id: SITE,
+        timestamp: 0,
+    }])
+    .await
+    .unwrap();
+    let history = t.db.fetch_item_history().await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].user_id, user);
+}
@@ -0,0 +1,54 @@
+use crate::geo::{centroid, distance, is_postal_code, parse_postal_codes, postal_code_distance};
+
+#[test]
+fn postal_codes() {
+    assert!(is_postal_code("00630"));
+    assert!(!is_postal_code("0063"));
+    assert!(!is_postal_code("0063a"));
+
+    // Unknown codes are located by the longest known prefix of the code
+    let ivalo = centroid("99800").unwrap();
+    assert!(distance(ivalo, centroid("99899").unwrap()) < 50.0);
+    assert!(distance(ivalo, centroid("99100").unwrap()) > 50.0);
+    assert!(centroid("00630").is_some());
+    assert!(centroid("helsinki").is_none());
+}
+
+#[test]
+fn postal_code_prefixes() {
+    let codes = parse_postal_codes(
+        "postal_code,latitude,longitude,name\n\
+         00100,60.0,24.0,Helsinki\n\
+         00120,60.2,24.2,Helsinki\n\
+         00200,61.0,25.0,Helsinki\n\
+         003,62.0,26.0,Helsinki\n\
+         invalid,row\n",
+    );
+
+    assert_eq!(codes.get("00100"), Some(&(60.0, 24.0)));
+    assert_eq!(codes.get("0010"), Some(&(60.0, 24.0)));
+    let (lat, lon) = codes["001"];
+    assert!((lat - 60.1).abs() < 1e-9 && (lon - 24.1).abs() < 1e-9);
+    let (lat, lon) = codes["00"];
+    assert!((lat - 60.8).abs() < 1e-9 && (lon - 24.8).abs() < 1e-9);
+
+    // The areas of the dataset override the averages of the prefixes
+    assert_eq!(codes.get("003"), Some(&(62.0, 26.0)));
+    assert_eq!(codes.get("invalid"), None);
+}
+
+#[test]
+fn distances() {
+    let helsinki = centroid("00100").unwrap();
+    let tampere = centroid("33100").unwrap();
+    let oulu = centroid("90100").unwrap();
+
+    assert_eq!(distance(helsinki, helsinki), 0.0);
+    assert!((155.0..175.0).contains(&distance(helsinki, tampere)));
+    assert!((500.0..560.0).contains(&distance(helsinki, oulu)));
+    assert_eq!(
+        postal_code_distance("00100", "33100"),
+        postal_code_distance("33100", "00100")
+    );
+    assert_eq!(postal_code_distance("00100", "abcde"), None);
+}
@@ -0,0 +1,35 @@
+use crate::history::{parse_date, HistoryQuery};
+
+#[test]
+fn parse_history_query() {
+    let query =
+        HistoryQuery::parse("sähkö polkupyörä min=50 max=200 alkaen=1.10.2026 sivu=2").unwrap();
+    assert_eq!(
+        query,
+        HistoryQuery {
+            keyword: Some(String::from("sähkö polkupyörä")),
+            min_price: Some(50),
+            max_price: Some(200),
+            since: Some(parse_date("2026-10-01", false).unwrap()),
+            page: 2,
+            ..Default::default()
+        }
+    );
+
+    assert_eq!(HistoryQuery::parse("").unwrap(), HistoryQuery::default());
+    assert!(HistoryQuery::parse("min=halpa").is_err());
+    assert!(HistoryQuery::parse("sivu=0").is_err());
+    assert!(HistoryQuery::parse("sivusto=ebay").is_err());
+}
+
+#[test]
+fn parse_dates() {
+    let start = parse_date("15.10.2026", false).unwrap();
+    assert_eq!(parse_date("2026-10-15", false).unwrap(), start);
+    // The end of the day is the start of the next one
+    assert_eq!(
+        parse_date("15.10.2026", true).unwrap(),
+        parse_date("16.10.2026", false).unwrap()
+    );
+    assert!(parse_date("32.10.2026", false).is_err());
+}
@@ -0,0 +1,35 @@
+use std::fs::File;
+use std::io::Read;
+
+use crate::huutonet::item::parse_item;
+use crate::watch::ItemStatus;
+
+fn item_json() -> serde_json::Value {
+    let mut file = File::open("testdata/huutonet/item.json").expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    serde_json::from_str(&contents).unwrap()
+}
+
+#[test]
+fn published_item() {
+    let ItemStatus::Available(item) = parse_item(&item_json().to_string()).unwrap() else {
+        panic!("The item should be available");
+    };
+    assert_eq!(item.title, "Tekniikan Maailma 20/1993");
+    assert_eq!(item.price, 4);
+    assert_eq!(item.listing_id(), Some(575647318));
+    assert_eq!(item.listing_id(), Some(item.ad_id));
+}
+
+#[test]
+fn closed_items() {
+    let mut json = item_json();
+    json["status"] = "closed".into();
+    // Closed without bids is not a sale
+    json["bidderCount"] = 0.into();
+    assert_eq!(parse_item(&json.to_string()).unwrap(), ItemStatus::Removed);
+
+    json["bidderCount"] = 3.into();
+    assert_eq!(parse_item(&json.to_string()).unwrap(), ItemStatus::Sold);
+}
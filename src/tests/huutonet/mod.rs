@@ -1,4 +1,6 @@
 mod api_url;
+mod item;
 mod parse;
+mod seller;
 
 const API_BASE: &str = "https://api.huuto.net/1.1/items?";
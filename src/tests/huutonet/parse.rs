@@ -1,7 +1,7 @@
 use std::fs::File;
 use std::io::Read;
 
-use crate::huutonet::parse::api_parse_after;
+use crate::huutonet::parse::{api_parse_after, api_result_count};
 use crate::vahti::VahtiItem;
 
 #[test]
@@ -22,10 +22,12 @@ fn basic_parse() {
         published: 1674021288,
         price: 4,
         seller_name: "kodin".to_string(),
-        seller_id: 241366,
+        seller_id: Some(241366),
+        postal_code: Some("04920".to_string()),
         location: "SAARENTAUS".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 575647318,
+        ..Default::default()
     };
 
     assert_eq!(
@@ -63,10 +65,12 @@ fn parse_multiple() {
         published: 1678355586,
         price: 13,
         seller_name: "ITJari".to_string(),
-        seller_id: 2732468,
+        seller_id: Some(2732468),
+        postal_code: Some("00170".to_string()),
         location: "HELSINKI".to_string(),
         ad_type: "auction".to_string(),
         ad_id: 578236742,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -79,10 +83,12 @@ fn parse_multiple() {
         published: 1678255722,
         price: 10,
         seller_name: "nick00".to_string(),
-        seller_id: 2914998,
+        seller_id: Some(2914998),
+        postal_code: Some("33720".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 578174408,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -95,10 +101,12 @@ fn parse_multiple() {
         published: 1678114915,
         price: 15,
         seller_name: "prossu1".to_string(),
-        seller_id: 2366051,
+        seller_id: Some(2366051),
+        postal_code: Some("90120".to_string()),
         location: "OULU".to_string(),
         ad_type: "auction".to_string(),
         ad_id: 578086441,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -111,10 +119,12 @@ fn parse_multiple() {
         published: 1678113543,
         price: 82,
         seller_name: "tarsiger".to_string(),
-        seller_id: 808553,
+        seller_id: Some(808553),
+        postal_code: Some("01640".to_string()),
         location: "VANTAA".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 578085280,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -127,10 +137,12 @@ fn parse_multiple() {
         published: 1678111203,
         price: 42,
         seller_name: "tarsiger".to_string(),
-        seller_id: 808553,
+        seller_id: Some(808553),
+        postal_code: Some("01640".to_string()),
         location: "VANTAA".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 578082963,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -143,10 +155,12 @@ fn parse_multiple() {
         published: 1678024148,
         price: 3,
         seller_name: "hnetti".to_string(),
-        seller_id: 1456413,
+        seller_id: Some(1456413),
+        postal_code: Some("00160".to_string()),
         location: "HELSINKI".to_string(),
         ad_type: "auction".to_string(),
         ad_id: 578028877,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -159,10 +173,12 @@ fn parse_multiple() {
         published: 1678022037,
         price: 790,
         seller_name: "attekorte".to_string(),
-        seller_id: 24060,
+        seller_id: Some(24060),
+        postal_code: Some("83900".to_string()),
         location: "JUUKA".to_string(),
         ad_type: "auction".to_string(),
         ad_id: 578026882,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -175,10 +191,12 @@ fn parse_multiple() {
         published: 1678006781,
         price: 125,
         seller_name: "tarsiger".to_string(),
-        seller_id: 808553,
+        seller_id: Some(808553),
+        postal_code: Some("01640".to_string()),
         location: "VANTAA".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 578013764,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -191,10 +209,12 @@ fn parse_multiple() {
         published: 1677736062,
         price: 10,
         seller_name: "nick00".to_string(),
-        seller_id: 2914998,
+        seller_id: Some(2914998),
+        postal_code: Some("33720".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 577859186,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -207,10 +227,12 @@ fn parse_multiple() {
         published: 1677589947,
         price: 50,
         seller_name: "nick00".to_string(),
-        seller_id: 2914998,
+        seller_id: Some(2914998),
+        postal_code: Some("33720".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 577756539,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -223,10 +245,12 @@ fn parse_multiple() {
         published: 1677406913,
         price: 20,
         seller_name: "nick00".to_string(),
-        seller_id: 2914998,
+        seller_id: Some(2914998),
+        postal_code: Some("33720".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 577619805,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -239,10 +263,12 @@ fn parse_multiple() {
         published: 1677406880,
         price: 50,
         seller_name: "nick00".to_string(),
-        seller_id: 2914998,
+        seller_id: Some(2914998),
+        postal_code: Some("33720".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 577619781,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -255,10 +281,12 @@ fn parse_multiple() {
         published: 1677406857,
         price: 50,
         seller_name: "nick00".to_string(),
-        seller_id: 2914998,
+        seller_id: Some(2914998),
+        postal_code: Some("33720".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 577619757,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -271,10 +299,12 @@ fn parse_multiple() {
         published: 1677369494,
         price: 15,
         seller_name: "Melviini".to_string(),
-        seller_id: 2245306,
+        seller_id: Some(2245306),
+        postal_code: Some("00600".to_string()),
         location: "HELSINKI".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 577600532,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -287,10 +317,12 @@ fn parse_multiple() {
         published: 1677231594,
         price: 30,
         seller_name: "Joulubuggi".to_string(),
-        seller_id: 2942,
+        seller_id: Some(2942),
+        postal_code: Some("33100".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 577519756,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -303,10 +335,12 @@ fn parse_multiple() {
         published: 1676145945,
         price: 600,
         seller_name: "hammermann".to_string(),
-        seller_id: 1398678,
+        seller_id: Some(1398678),
+        postal_code: Some("33100".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 576827868,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -319,10 +353,12 @@ fn parse_multiple() {
         published: 1675671542,
         price: 400,
         seller_name: "hammermann".to_string(),
-        seller_id: 1398678,
+        seller_id: Some(1398678),
+        postal_code: Some("33100".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 576564494,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -335,10 +371,12 @@ fn parse_multiple() {
         published: 1675324587,
         price: 250,
         seller_name: "hammermann".to_string(),
-        seller_id: 1398678,
+        seller_id: Some(1398678),
+        postal_code: Some("33100".to_string()),
         location: "TAMPERE".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 576378051,
+        ..Default::default()
     },
     VahtiItem {
         deliver_to: None,
@@ -351,10 +389,12 @@ fn parse_multiple() {
         published: 1670393684,
         price: 9,
         seller_name: "countryguy".to_string(),
-        seller_id: 1304585,
+        seller_id: Some(1304585),
+        postal_code: Some("55120".to_string()),
         location: "IMATRA".to_string(),
         ad_type: "buy-now".to_string(),
         ad_id: 573442000,
+        ..Default::default()
     },
     ];
 
@@ -369,3 +409,13 @@ fn parse_multiple() {
         .map(|(a, b)| assert_eq!(a, b))
         .collect::<Vec<_>>();
 }
+
+#[test]
+fn result_count() {
+    let mut file =
+        File::open("testdata/huutonet/parse_multiple.json").expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+
+    assert_eq!(api_result_count(&contents).unwrap(), 19);
+}
@@ -0,0 +1,16 @@
+use crate::huutonet::seller::parse_seller;
+
+#[test]
+fn username_is_unquoted() {
+    let seller = parse_seller(241366, r#"{"id": 241366, "username": "kodin"}"#)
+        .unwrap()
+        .unwrap();
+    assert_eq!(seller.seller_id, 241366);
+    assert_eq!(seller.site_id, crate::huutonet::ID);
+    assert_eq!(seller.name, "kodin");
+}
+
+#[test]
+fn missing_username() {
+    assert!(parse_seller(241366, "{}").unwrap().is_none());
+}
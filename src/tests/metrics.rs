@@ -0,0 +1,85 @@
+use std::sync::LazyLock;
+
+use super::database::test_database;
+use crate::metrics::*;
+
+#[test]
+fn registration() {
+    // Forcing the metrics registers them, which panics on invalid or duplicate metrics
+    LazyLock::force(&UPDATE_DURATION);
+    LazyLock::force(&ITEMS_BLACKLISTED);
+    LazyLock::force(&ITEM_HISTORY_SIZE);
+    LazyLock::force(&DB_CONNECTION_WAIT);
+    FETCH_DURATION.with_label_values(&["test"]);
+    FETCH_ERRORS.with_label_values(&["test"]);
+    ITEMS_PARSED.with_label_values(&["test"]);
+    ITEMS_DELIVERED.with_label_values(&["test"]);
+    DELIVERIES.with_label_values(&["test", "success"]);
+    ACTIVE_VAHTIS.with_label_values(&["test"]);
+    DB_QUERY_DURATION.with_label_values(&["test"]);
+    DB_ERRORS.with_label_values(&["test"]);
+
+    let metrics = gather();
+    for name in [
+        "torimies_update_duration_seconds",
+        "torimies_fetch_duration_seconds",
+        "torimies_fetch_errors_total",
+        "torimies_items_parsed_total",
+        "torimies_items_blacklisted_total",
+        "torimies_items_delivered_total",
+        "torimies_deliveries_total",
+        "torimies_active_vahtis",
+        "torimies_item_history_size",
+        "torimies_db_query_duration_seconds",
+        "torimies_db_connection_wait_seconds",
+        "torimies_db_errors_total",
+    ] {
+        assert!(
+            metrics.contains(&format!("# TYPE {} ", name)),
+            "{} is not registered",
+            name
+        );
+    }
+}
+
+#[test]
+fn counters() {
+    let errors = FETCH_ERRORS.with_label_values(&["counters"]);
+    let before = errors.get();
+    errors.inc();
+    assert_eq!(errors.get(), before + 1);
+
+    let delivered = ITEMS_DELIVERED.with_label_values(&["counters"]);
+    let before = delivered.get();
+    delivered.inc_by(3);
+    assert_eq!(delivered.get(), before + 3);
+    assert!(gather().contains("torimies_items_delivered_total{delivery_method=\"counters\"}"));
+}
+
+#[test]
+fn label_names() {
+    assert_eq!(site_name(crate::search::ID), crate::search::NAME);
+    #[cfg(feature = "tori")]
+    assert_eq!(site_name(crate::tori::ID), crate::tori::NAME);
+    assert_eq!(site_name(0), "unknown");
+
+    #[cfg(feature = "discord-delivery")]
+    assert_eq!(
+        delivery_name(crate::delivery::discord::ID),
+        crate::delivery::discord::NAME
+    );
+    assert_eq!(delivery_name(0), "unknown");
+}
+
+#[tokio::test]
+async fn database_queries() {
+    let t = test_database().await;
+    let queries = DB_QUERY_DURATION.with_label_values(&["fetch_vahti_by_id"]);
+    let errors = DB_ERRORS.with_label_values(&["fetch_vahti_by_id"]);
+    let (count, failed) = (queries.get_sample_count(), errors.get());
+
+    // A missing Vahti is an error of the query
+    assert!(t.db.fetch_vahti_by_id(-1).await.is_err());
+    assert!(queries.get_sample_count() > count);
+    assert!(errors.get() > failed);
+}
@@ -0,0 +1,62 @@
+#[cfg(not(feature = "postgres"))]
+use diesel::prelude::*;
+#[cfg(not(feature = "postgres"))]
+use diesel::sqlite::SqliteConnection;
+
+#[cfg(not(feature = "postgres"))]
+use crate::migrations::run_migrations;
+use crate::migrations::{backup_path, MIGRATIONS, MIGRATIONS_DIR};
+
+#[test]
+fn all_migrations_embedded() {
+    let mut dirs = std::fs::read_dir(MIGRATIONS_DIR)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|n| !n.starts_with('.'))
+        .collect::<Vec<_>>();
+    dirs.sort();
+
+    let mut embedded = MIGRATIONS.iter().map(|m| m.name).collect::<Vec<_>>();
+    embedded.sort();
+
+    assert_eq!(dirs, embedded);
+}
+
+#[test]
+fn backup_paths() {
+    let path = backup_path("data/database.sqlite", None);
+    assert_eq!(path.parent().unwrap().to_str(), Some("data"));
+    assert!(path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("database.sqlite.backup-"));
+
+    let path = backup_path("database.sqlite", Some("/app/backups"));
+    assert_eq!(path.parent().unwrap().to_str(), Some("/app/backups"));
+}
+
+#[cfg(not(feature = "postgres"))]
+#[test]
+fn migrate_new_database() {
+    let conn = SqliteConnection::establish(":memory:").unwrap();
+    run_migrations(&conn, ":memory:").unwrap();
+    // Running the migrations again should be a no-op
+    run_migrations(&conn, ":memory:").unwrap();
+}
+
+#[cfg(not(feature = "postgres"))]
+#[test]
+fn refuse_newer_schema() {
+    let conn = SqliteConnection::establish(":memory:").unwrap();
+    run_migrations(&conn, ":memory:").unwrap();
+    diesel::sql_query("INSERT INTO __diesel_schema_migrations (version) VALUES ('99990101000000')")
+        .execute(&conn)
+        .unwrap();
+
+    assert!(matches!(
+        run_migrations(&conn, ":memory:"),
+        Err(crate::error::Error::SchemaTooNew(_))
+    ));
+}
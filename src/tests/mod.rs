@@ -1,2 +1,41 @@
+mod account;
+#[cfg(feature = "tori")]
+mod blockrule;
+mod config;
+mod database;
+mod geo;
+mod history;
 pub mod huutonet;
+mod metrics;
+mod migrations;
+#[cfg(feature = "muusikoiden")]
+mod muusikoiden;
+#[cfg(feature = "nettiauto")]
+mod nettiauto;
+mod reload;
+mod repost;
+mod search;
+mod stats;
+mod supervisor;
 pub mod tori;
+mod vahti;
+mod watch;
+
+use crate::vahti::VahtiItem;
+
+/// An item of the test site with a known seller,
+/// the tests override the fields they check
+pub fn item(ad_id: i64, price: i64) -> VahtiItem {
+    VahtiItem {
+        site_id: 1,
+        title: String::from("Polkupyörä"),
+        url: format!("https://example.com/{}", ad_id),
+        price,
+        seller_name: String::from("myyjä"),
+        seller_id: Some(1),
+        location: String::from("Helsinki"),
+        ad_type: String::from("Myydään"),
+        ad_id,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,30 @@
+use crate::muusikoiden::api::vahti_to_search;
+
+#[test]
+fn basic_query() {
+    let url = "https://muusikoiden.net/tori/haku.php?keyword=telecaster";
+    let expected = "https://muusikoiden.net/tori/haku.php?keyword=telecaster&sort=new";
+    assert_eq!(vahti_to_search(url), expected);
+}
+
+#[test]
+fn multiquery() {
+    let url = "https://muusikoiden.net/tori/haku.php?keyword=helix&type=sell&category=15";
+    let expected =
+        "https://muusikoiden.net/tori/haku.php?keyword=helix&type=sell&category=15&sort=new";
+    assert_eq!(vahti_to_search(url), expected);
+}
+
+#[test]
+fn replaced_sort_and_page() {
+    let url = "https://muusikoiden.net/tori/haku.php?keyword=boss&sort=price&page=3";
+    let expected = "https://muusikoiden.net/tori/haku.php?keyword=boss&sort=new";
+    assert_eq!(vahti_to_search(url), expected);
+}
+
+#[test]
+fn query_with_non_ascii() {
+    let url = "https://muusikoiden.net/tori/haku.php?keyword=s%C3%A4r%C3%B6";
+    let expected = "https://muusikoiden.net/tori/haku.php?keyword=s%C3%A4r%C3%B6&sort=new";
+    assert_eq!(vahti_to_search(url), expected);
+}
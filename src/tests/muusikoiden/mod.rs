@@ -0,0 +1,2 @@
+mod api_url;
+mod parse;
@@ -0,0 +1,104 @@
+use std::fs::File;
+use std::io::Read;
+
+use chrono::{Local, TimeZone};
+
+use crate::muusikoiden::parse::{parse_after, result_count};
+use crate::vahti::VahtiItem;
+
+fn read_testdata(name: &str) -> String {
+    let mut file =
+        File::open(format!("testdata/muusikoiden/{}", name)).expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    contents
+}
+
+/// The times on the pages are in local time
+fn local_timestamp(y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+    Local
+        .with_ymd_and_hms(y, m, d, h, min, 0)
+        .unwrap()
+        .timestamp()
+}
+
+#[test]
+fn basic_parse() {
+    let contents = read_testdata("basic_parse.html");
+
+    let expected = VahtiItem {
+        deliver_to: None,
+        delivery_method: None,
+        site_id: crate::muusikoiden::ID,
+        title: "Fender Player Telecaster".to_string(),
+        vahti_url: None,
+        url: "https://muusikoiden.net/tori/ilmoitus/3450123".to_string(),
+        img_url: "https://muusikoiden.net/tori/kuvat/3450123_1_thumb.jpg".to_string(),
+        published: local_timestamp(2023, 3, 9, 10, 15),
+        price: 650,
+        seller_name: "kitaristi".to_string(),
+        seller_id: Some(12345),
+        location: "Helsinki".to_string(),
+        ad_type: "Myydään".to_string(),
+        ad_id: 3450123,
+        ..Default::default()
+    };
+
+    assert_eq!(parse_after(&contents, 0).unwrap(), vec![expected]);
+}
+
+#[test]
+fn parse_multiple() {
+    let contents = read_testdata("parse_multiple.html");
+    let items = parse_after(&contents, 0).unwrap();
+    assert_eq!(items.len(), 4);
+
+    // An ad without a price or an image
+    assert_eq!(items[1].title, "Boss DS-1 & SD-1");
+    assert_eq!(items[1].ad_type, "Ostetaan");
+    assert_eq!(items[1].price, 0);
+    assert_eq!(items[1].img_url, "");
+    assert_eq!(items[1].seller_id, Some(67890));
+
+    // Only the type of the ad is removed from the title
+    assert_eq!(items[2].title, "Line 6 Helix: LT");
+    assert_eq!(items[2].ad_type, "Vaihdetaan");
+    assert_eq!(items[2].price, 1150);
+    assert_eq!(items[2].location, "Oulu");
+    assert_eq!(items[2].published, local_timestamp(2023, 3, 7, 9, 1));
+
+    assert_eq!(items[3].price, 0);
+    assert_eq!(items[3].seller_name, "pianisti");
+}
+
+#[test]
+fn parse_after_timestamp() {
+    let contents = read_testdata("parse_multiple.html");
+    let second = local_timestamp(2023, 3, 8, 18, 42);
+
+    assert_eq!(parse_after(&contents, second).unwrap().len(), 1);
+    assert_eq!(parse_after(&contents, second - 1).unwrap().len(), 2);
+}
+
+#[test]
+fn same_minute() {
+    let items = parse_after(&read_testdata("same_minute.html"), 0).unwrap();
+
+    // The times are only shown to the minute
+    assert_eq!(
+        items.iter().map(|i| i.ad_id).collect::<Vec<_>>(),
+        vec![3450202, 3450201]
+    );
+    assert!(items
+        .iter()
+        .all(|i| i.published == local_timestamp(2023, 3, 10, 12, 30)));
+}
+
+#[test]
+fn result_counts() {
+    assert_eq!(result_count(&read_testdata("parse_multiple.html")), 4);
+    assert_eq!(result_count(&read_testdata("empty.html")), 0);
+    assert!(parse_after(&read_testdata("empty.html"), 0)
+        .unwrap()
+        .is_empty());
+}
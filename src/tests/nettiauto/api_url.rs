@@ -0,0 +1,43 @@
+use super::{API_BASE, API_SORT};
+use crate::nettiauto::api::vahti_to_api;
+
+#[test]
+fn no_filters() {
+    let url = "https://www.nettiauto.com/hakutulokset";
+    let expected = API_BASE.to_owned() + API_SORT;
+    assert_eq!(vahti_to_api(url), expected);
+}
+
+#[test]
+fn make_and_model() {
+    let url = "https://www.nettiauto.com/volkswagen/golf";
+    let expected = API_BASE.to_owned() + "make=volkswagen&model=golf&" + API_SORT;
+    assert_eq!(vahti_to_api(url), expected);
+
+    let url = "https://www.nettiauto.com/volkswagen";
+    let expected = API_BASE.to_owned() + "make=volkswagen&" + API_SORT;
+    assert_eq!(vahti_to_api(url), expected);
+}
+
+#[test]
+fn renamed_params() {
+    let url = "https://www.nettiauto.com/volkswagen/golf?yfrom=2012&yto=2018&pto=15000&kmto=200000";
+    let expected = API_BASE.to_owned()
+        + "make=volkswagen&model=golf&yearFrom=2012&yearTo=2018&priceTo=15000&kilometersTo=200000&"
+        + API_SORT;
+    assert_eq!(vahti_to_api(url), expected);
+}
+
+#[test]
+fn passed_and_empty_params() {
+    let url = "https://www.nettiauto.com/hakutulokset?haku=P123&fuel=2&pfrom=";
+    let expected = API_BASE.to_owned() + "haku=P123&fuelType=2&" + API_SORT;
+    assert_eq!(vahti_to_api(url), expected);
+}
+
+#[test]
+fn query_with_non_ascii() {
+    let url = "https://www.nettiauto.com/hakutulokset?tagsearch=per%C3%A4koukku";
+    let expected = API_BASE.to_owned() + "tagsearch=per%C3%A4koukku&" + API_SORT;
+    assert_eq!(vahti_to_api(url), expected);
+}
@@ -0,0 +1,33 @@
+use std::fs::File;
+use std::io::Read;
+
+use crate::nettiauto::item::parse_item;
+use crate::watch::ItemStatus;
+
+fn item_json() -> serde_json::Value {
+    let mut file = File::open("testdata/nettiauto/item.json").expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    serde_json::from_str(&contents).unwrap()
+}
+
+#[test]
+fn item_for_sale() {
+    let ItemStatus::Available(item) = parse_item(&item_json().to_string()).unwrap() else {
+        panic!("The item should be available");
+    };
+    assert_eq!(item.title, "Volkswagen Golf 1.4 TSI Comfortline");
+    assert_eq!(item.price, 11900);
+    assert_eq!(item.listing_id(), Some(12845631));
+    assert_eq!(item.listing_id(), Some(item.ad_id));
+}
+
+#[test]
+fn sold_and_removed_items() {
+    let mut json = item_json();
+    json["status"] = "sold".into();
+    assert_eq!(parse_item(&json.to_string()).unwrap(), ItemStatus::Sold);
+
+    json["status"] = "deleted".into();
+    assert_eq!(parse_item(&json.to_string()).unwrap(), ItemStatus::Removed);
+}
@@ -0,0 +1,6 @@
+mod api_url;
+mod item;
+mod parse;
+
+const API_BASE: &str = "https://api.nettix.fi/rest/car/search?";
+const API_SORT: &str = "sortBy=dateCreated&sortOrder=desc";
@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io::Read;
+
+use crate::nettiauto::parse::{api_parse_after, api_result_count};
+use crate::vahti::{VahtiItem, Vehicle};
+
+fn read_testdata(name: &str) -> String {
+    let mut file = File::open(format!("testdata/nettiauto/{}", name)).expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    contents
+}
+
+#[test]
+fn basic_parse() {
+    let contents = read_testdata("basic_parse.json");
+
+    let expected = VahtiItem {
+        deliver_to: None,
+        delivery_method: None,
+        site_id: crate::nettiauto::ID,
+        title: "Volkswagen Golf 1.4 TSI Comfortline".to_string(),
+        vahti_url: None,
+        url: "https://www.nettiauto.com/volkswagen/golf/12845631".to_string(),
+        img_url: "https://images.nettix.fi/nettiauto/a1b2c3/12845631_1-large.jpg".to_string(),
+        published: 1678349730,
+        price: 11900,
+        seller_name: "Autotalo Helsinki Oy".to_string(),
+        seller_id: Some(55501),
+        company_ad: true,
+        postal_code: Some("00560".to_string()),
+        vehicle: Some(Vehicle {
+            year: Some(2015),
+            mileage: Some(148000),
+        }),
+        location: "Helsinki, Uusimaa".to_string(),
+        ad_type: "Liike".to_string(),
+        ad_id: 12845631,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        *api_parse_after(&contents, 0).unwrap().first().unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn parse_multiple() {
+    let contents = read_testdata("parse_multiple.json");
+    let items = api_parse_after(&contents, 0).unwrap();
+    assert_eq!(items.len(), 3);
+
+    assert_eq!(items[1].title, "Volkswagen Golf Variant 1.6 TDI");
+    assert_eq!(items[1].price, 7450);
+    assert!(!items[1].company_ad);
+    assert_eq!(items[1].ad_type, "Yksityinen");
+    assert_eq!(
+        items[1].vehicle,
+        Some(Vehicle {
+            year: Some(2012),
+            mileage: Some(231500),
+        })
+    );
+
+    // The optional details are missing from the last ad
+    assert_eq!(items[2].title, "Volkswagen Golf");
+    assert_eq!(items[2].price, 0);
+    assert_eq!(items[2].img_url, "");
+    assert_eq!(items[2].postal_code, None);
+    assert_eq!(
+        items[2].vehicle,
+        Some(Vehicle {
+            year: Some(2009),
+            mileage: None,
+        })
+    );
+}
+
+#[test]
+fn parse_after() {
+    let contents = read_testdata("parse_multiple.json");
+
+    assert_eq!(api_parse_after(&contents, 1678349730).unwrap().len(), 0);
+    assert_eq!(api_parse_after(&contents, 1678349729).unwrap().len(), 1);
+    assert_eq!(api_parse_after(&contents, 1678172504).unwrap().len(), 2);
+}
+
+#[test]
+fn result_count() {
+    let contents = read_testdata("parse_multiple.json");
+    assert_eq!(api_result_count(&contents).unwrap(), 3);
+    assert_eq!(api_result_count("[]").unwrap(), 0);
+}
@@ -0,0 +1,59 @@
+use crate::config::Config;
+use crate::reload::{diff_config, ConfigChanges};
+
+fn config() -> Config {
+    Config {
+        database_url: String::from("database.sqlite"),
+        http_address: Some(String::from("127.0.0.1:8080")),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn unchanged_config() {
+    let (new, changes) = diff_config(&config(), config());
+    assert_eq!(changes, ConfigChanges::default());
+    assert_eq!(new.database_url, "database.sqlite");
+}
+
+#[test]
+fn changed_platforms() {
+    let mut new = config();
+    new.discord.token = Some(String::from("new-token"));
+    new.update_interval = 60;
+    let (new, changes) = diff_config(&config(), new);
+    assert_eq!(
+        changes,
+        ConfigChanges {
+            discord: true,
+            telegram: false,
+        }
+    );
+    // Settings read on use are taken as they are
+    assert_eq!(new.update_interval, 60);
+
+    let mut new = config();
+    new.telegram.enabled = false;
+    let (_, changes) = diff_config(&config(), new);
+    assert_eq!(
+        changes,
+        ConfigChanges {
+            discord: false,
+            telegram: true,
+        }
+    );
+}
+
+#[test]
+fn restart_required() {
+    let mut new = config();
+    new.database_url = String::from("other.sqlite");
+    new.http_address = None;
+    new.shutdown_timeout = 30;
+
+    let (new, changes) = diff_config(&config(), new);
+    assert_eq!(changes, ConfigChanges::default());
+    assert_eq!(new.database_url, "database.sqlite");
+    assert_eq!(new.http_address.as_deref(), Some("127.0.0.1:8080"));
+    assert_eq!(new.shutdown_timeout, 30);
+}
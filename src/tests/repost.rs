@@ -0,0 +1,24 @@
+use crate::repost::{normalize_title, RepostMode};
+
+#[test]
+fn normalized_titles() {
+    assert_eq!(
+        normalize_title("  Audio Pro G10 - älykäs  monihuonekaiutin!!"),
+        "audio pro g10 älykäs monihuonekaiutin"
+    );
+    assert_eq!(
+        normalize_title("IPHONE 12, 64GB"),
+        normalize_title("iPhone 12 64gb")
+    );
+}
+
+#[test]
+fn repost_modes() {
+    assert_eq!(RepostMode::parse("merkitse"), Some(RepostMode::Label));
+    assert_eq!(RepostMode::parse("label"), None);
+
+    for mode in [RepostMode::Off, RepostMode::Label, RepostMode::Suppress] {
+        assert_eq!(RepostMode::from(i32::from(mode)), mode);
+    }
+    assert_eq!(RepostMode::from(42), RepostMode::Off);
+}
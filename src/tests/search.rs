@@ -0,0 +1,86 @@
+use crate::search::query::{canonical_url, merge_results, KeywordQuery};
+use crate::vahti::VahtiItem;
+
+#[test]
+fn parse_query() {
+    let query = KeywordQuery::parse("ThinkPad  T14 hinta:100-500 sijainti:Helsinki").unwrap();
+    assert_eq!(query.keywords, "thinkpad t14");
+    assert_eq!(query.min_price, Some(100));
+    assert_eq!(query.max_price, Some(500));
+    assert_eq!(query.location.as_deref(), Some("helsinki"));
+    assert_eq!(
+        query.to_string(),
+        "thinkpad t14 hinta:100-500 sijainti:helsinki"
+    );
+    assert_eq!(KeywordQuery::parse(&query.to_string()), Some(query));
+
+    let query = KeywordQuery::parse("kitara hinta:-200").unwrap();
+    assert_eq!(query.min_price, None);
+    assert_eq!(query.max_price, Some(200));
+    assert_eq!(query.to_string(), "kitara hinta:-200");
+
+    assert!(KeywordQuery::parse("https://www.tori.fi/koko_suomi?q=kitara").is_none());
+    // Mistyped urls are not keyword searches
+    assert!(KeywordQuery::parse("tori.fi/koko_suomi?q=kitara").is_none());
+    assert!(KeywordQuery::parse("www.huuto.net/haku?words=kitara").is_none());
+    assert!(KeywordQuery::parse("kitara huuto.net").is_none());
+    assert!(KeywordQuery::parse("kitara example.co.uk/haku").is_none());
+    assert!(KeywordQuery::parse("htps://tori.fi").is_none());
+    assert!(KeywordQuery::parse("3.5mm kuulokkeet node.js").is_some());
+    assert!(KeywordQuery::parse("hinta:100-200").is_none());
+    assert!(KeywordQuery::parse("kitara hinta:500-100").is_none());
+    assert!(KeywordQuery::parse("kitara hinta:halpa").is_none());
+    assert!(KeywordQuery::parse("").is_none());
+}
+
+#[test]
+fn canonical_urls() {
+    assert_eq!(
+        canonical_url(" Kitara  sijainti:Turku "),
+        "kitara sijainti:turku"
+    );
+    let url = "https://www.huuto.net/haku?words=Kitara";
+    assert_eq!(canonical_url(url), url);
+}
+
+#[cfg(feature = "tori")]
+#[test]
+fn site_urls() {
+    let query = KeywordQuery::parse("audio pro hinta:100-").unwrap();
+    let urls = query.site_urls();
+    assert!(urls.contains(&(
+        crate::tori::ID,
+        String::from("https://www.tori.fi/recommerce/forsale/search?q=audio+pro&price_from=100")
+    )));
+}
+
+#[test]
+fn merge() {
+    let query = KeywordQuery::parse("kaiutin hinta:100-200 sijainti:vaasa").unwrap();
+    let item = |site_id, title: &str, price, location: &str| VahtiItem {
+        site_id,
+        title: title.to_string(),
+        location: location.to_string(),
+        ..super::item(1, price)
+    };
+    let results = vec![
+        vec![
+            item(1, "Audio Pro kaiutin", 150, "Vaasa, Pohjanmaa"),
+            item(1, "Halpa kaiutin", 50, "Vaasa, Pohjanmaa"),
+            item(1, "Kaiutin Oulussa", 150, "Oulu"),
+        ],
+        vec![
+            item(2, "audio pro kaiutin ", 150, "Vaasa"),
+            item(2, "Audio Pro kaiutin", 160, "Vaasa"),
+        ],
+    ];
+
+    let merged = merge_results(&query, results);
+    assert_eq!(
+        merged
+            .iter()
+            .map(|i| (i.site_id, i.price))
+            .collect::<Vec<_>>(),
+        vec![(1, 150), (2, 160)]
+    );
+}
@@ -0,0 +1,46 @@
+use crate::stats::PriceStats;
+
+const DAY: i64 = 86400;
+const NOW: i64 = 1700000000;
+
+#[test]
+fn price_stats() {
+    // Older items and items without a price are ignored
+    let items = [
+        (100, NOW - 25 * DAY),
+        (200, NOW - 20 * DAY),
+        (300, NOW - 16 * DAY),
+        (150, NOW - 10 * DAY),
+        (250, NOW - 5 * DAY),
+        (350, NOW - DAY),
+        (0, NOW - DAY),
+        (5000, NOW - 40 * DAY),
+    ];
+
+    let stats = PriceStats::new(&items, 30, NOW).unwrap();
+    assert_eq!(
+        stats,
+        PriceStats {
+            days: 30,
+            count: 6,
+            median: 225,
+            p25: 150,
+            p75: 300,
+            min: 100,
+            max: 350,
+            trend: Some(25),
+        }
+    );
+}
+
+#[test]
+fn price_stats_without_items() {
+    assert_eq!(
+        PriceStats::new(&[(0, NOW), (100, NOW - 8 * DAY)], 7, NOW),
+        None
+    );
+
+    let stats = PriceStats::new(&[(100, NOW)], 7, NOW).unwrap();
+    assert_eq!(stats.median, 100);
+    assert_eq!(stats.trend, None);
+}
@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::database::test_database;
+use crate::command::{Command, Manager};
+use crate::error::Error;
+use crate::health::CommanderStatus;
+use crate::supervisor::{supervise_commander, Backoff, MAX_BACKOFF, MIN_BACKOFF};
+use crate::Torimies;
+
+#[test]
+fn backoff() {
+    let mut backoff = Backoff::new();
+    assert_eq!(backoff.next_delay(), MIN_BACKOFF);
+    assert_eq!(backoff.next_delay(), MIN_BACKOFF * 2);
+    assert_eq!(backoff.next_delay(), MIN_BACKOFF * 4);
+    for _ in 0..10 {
+        backoff.next_delay();
+    }
+    assert_eq!(backoff.next_delay(), MAX_BACKOFF);
+
+    // The backoff is reset once the task has stayed up long enough
+    backoff.started = Instant::now() - MAX_BACKOFF - Duration::from_secs(1);
+    assert_eq!(backoff.next_delay(), MIN_BACKOFF);
+    backoff.started();
+    assert_eq!(backoff.next_delay(), MIN_BACKOFF * 2);
+}
+
+struct TestManager;
+
+#[async_trait]
+impl Manager for TestManager {
+    async fn shutdown(&self) {}
+}
+
+/// A commander that runs the given behaviour on each start
+struct TestCommander {
+    starts: Arc<AtomicUsize>,
+    on_start: Box<dyn Fn(usize) -> Result<(), Error> + Send + Sync>,
+}
+
+#[async_trait]
+impl Command for TestCommander {
+    async fn start(&mut self) -> Result<(), Error> {
+        let start = self.starts.fetch_add(1, Ordering::SeqCst);
+        (self.on_start)(start)
+    }
+
+    fn manager(&self) -> Box<dyn Manager + Send + Sync> {
+        Box::new(TestManager)
+    }
+}
+
+async fn test_torimies(name: &str) -> Torimies {
+    let t = test_database().await;
+    let man = Torimies::new(t.db.clone());
+    man.command_manager
+        .insert(name.to_string(), Box::new(TestManager));
+    man
+}
+
+#[tokio::test]
+async fn restart_failed_commander() {
+    let man = test_torimies("test").await;
+    let starts = Arc::new(AtomicUsize::new(0));
+    let shutdown = man.shutdown.clone();
+    let commander = TestCommander {
+        starts: starts.clone(),
+        on_start: Box::new(move |start| {
+            if start == 0 {
+                panic!("commander crashed");
+            }
+            shutdown.cancel();
+            Ok(())
+        }),
+    };
+
+    supervise_commander(man.clone(), String::from("test"), Box::new(commander)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        man.health.commanders.get("test").map(|s| *s),
+        Some(CommanderStatus::Stopped)
+    );
+}
+
+#[tokio::test]
+async fn replace_commander() {
+    let man = test_torimies("test").await;
+    let starts = Arc::new(AtomicUsize::new(0));
+    let replacement_starts = Arc::new(AtomicUsize::new(0));
+
+    let shutdown = man.shutdown.clone();
+    let replacement = TestCommander {
+        starts: replacement_starts.clone(),
+        on_start: Box::new(move |_| {
+            shutdown.cancel();
+            Ok(())
+        }),
+    };
+    man.command
+        .insert(String::from("test"), Box::new(replacement));
+
+    // The replacement is started right away when the current commander exits
+    let started = Instant::now();
+    let commander = TestCommander {
+        starts: starts.clone(),
+        on_start: Box::new(|_| Ok(())),
+    };
+    supervise_commander(man.clone(), String::from("test"), Box::new(commander)).await;
+    assert!(started.elapsed() < MIN_BACKOFF);
+    assert_eq!(starts.load(Ordering::SeqCst), 1);
+    assert_eq!(replacement_starts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn removed_commander() {
+    let man = test_torimies("test").await;
+    let command_manager = man.command_manager.clone();
+    let commander = TestCommander {
+        starts: Arc::new(AtomicUsize::new(0)),
+        on_start: Box::new(move |_| {
+            command_manager.remove("test");
+            Err(Error::Tori)
+        }),
+    };
+
+    // A removed commander is not restarted and its status is dropped
+    supervise_commander(man.clone(), String::from("test"), Box::new(commander)).await;
+    assert!(!man.health.commanders.contains_key("test"));
+}
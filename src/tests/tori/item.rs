@@ -0,0 +1,34 @@
+use std::fs::File;
+use std::io::Read;
+
+use crate::tori::item::parse_item;
+use crate::watch::ItemStatus;
+
+fn item_json() -> serde_json::Value {
+    let mut file = File::open("testdata/tori/item.json").expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    serde_json::from_str(&contents).unwrap()
+}
+
+#[test]
+fn active_item() {
+    let ItemStatus::Available(item) = parse_item(&item_json().to_string()).unwrap() else {
+        panic!("The item should be available");
+    };
+    assert_eq!(item.title, "Maalaisromanttinen peltipurkki ja eläimiä");
+    assert_eq!(item.price, 7);
+    assert_eq!(item.url, "https://www.tori.fi/vi/81076530.htm");
+    assert_eq!(item.listing_id(), Some(81076530));
+}
+
+#[test]
+fn sold_and_removed_items() {
+    let mut json = item_json();
+    json["ad"]["status"] = "sold".into();
+    assert_eq!(parse_item(&json.to_string()).unwrap(), ItemStatus::Sold);
+
+    json["ad"]["status"] = "deleted".into();
+    assert_eq!(parse_item(&json.to_string()).unwrap(), ItemStatus::Removed);
+    assert_eq!(parse_item("{}").unwrap(), ItemStatus::Removed);
+}
@@ -1,4 +1,7 @@
 mod api_url;
+mod item;
 mod parse;
+mod recommerce;
+mod seller;
 
 const API_BASE: &str = "https://api.tori.fi/api/v1.2/public/ads?";
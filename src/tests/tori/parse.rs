@@ -1,7 +1,7 @@
 use std::fs::File;
 use std::io::Read;
 
-use crate::tori::parse::api_parse_after;
+use crate::tori::parse::{api_parse_after, api_result_count};
 use crate::vahti::VahtiItem;
 
 #[test]
@@ -22,10 +22,14 @@ fn basic_parse() {
         published: 1614890870,
         price: 7,
         seller_name: "H.S.M".to_string(),
-        seller_id: 188169,
+        seller_id: Some(188169),
+        seller_created: Some("tammikuusta 2014".to_string()),
+        seller_ads: Some(82),
+        postal_code: Some("00630".to_string()),
         location: "Maunula-Suursuo, Helsinki, Uusimaa".to_string(),
         ad_type: "Myydään".to_string(),
         ad_id: 79217488,
+        ..Default::default()
     };
 
     assert_eq!(
@@ -64,10 +68,14 @@ fn parse_multiple() {
             published: 1674035937,
             price: 25,
             seller_name: "Erja Latva".to_string(),
-            seller_id: 289139,
+            seller_id: Some(289139),
+            seller_created: Some("huhtikuusta 2014".to_string()),
+            seller_ads: Some(3),
+            postal_code: Some("65350".to_string()),
             location: "Suvilahti, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 107463388,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -82,10 +90,15 @@ fn parse_multiple() {
             published: 1673531834,
             price: 339,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 103120642,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -100,10 +113,15 @@ fn parse_multiple() {
             published: 1675057180,
             price: 143,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 107987389,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -118,10 +136,15 @@ fn parse_multiple() {
             published: 1674365101,
             price: 16990,
             seller_name: "Rinta-Joupin Autoliike, Tervajoki".to_string(),
-            seller_id: 2349504,
+            seller_id: Some(2349504),
+            seller_created: Some("tammikuusta 2020".to_string()),
+            seller_ads: Some(219),
+            company_ad: true,
+            postal_code: Some("66440".to_string()),
             location: "Tervajoki, Laihia, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 105715838,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -136,10 +159,15 @@ fn parse_multiple() {
             published: 1675853738,
             price: 268,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 106730945,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -154,10 +182,15 @@ fn parse_multiple() {
             published: 1675842778,
             price: 174,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 106414054,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -170,10 +203,15 @@ fn parse_multiple() {
             published: 1675869730,
             price: 3329,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 106144962,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -188,10 +226,15 @@ fn parse_multiple() {
             published: 1675853818,
             price: 402,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 101130082,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -206,10 +249,15 @@ fn parse_multiple() {
             published: 1676283023,
             price: 3700,
             seller_name: "Rinta-Joupin Autoliike, Tervajoki".to_string(),
-            seller_id: 2349504,
+            seller_id: Some(2349504),
+            seller_created: Some("tammikuusta 2020".to_string()),
+            seller_ads: Some(219),
+            company_ad: true,
+            postal_code: Some("66440".to_string()),
             location: "Tervajoki, Laihia, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 108584455,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -222,10 +270,15 @@ fn parse_multiple() {
             published: 1675873122,
             price: 299,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 101212772,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -240,10 +293,14 @@ fn parse_multiple() {
             published: 1677350539,
             price: 20,
             seller_name: "moternimies".to_string(),
-            seller_id: 2695759,
+            seller_id: Some(2695759),
+            seller_created: Some("lokakuusta 2020".to_string()),
+            seller_ads: Some(21),
+            postal_code: Some("65380".to_string()),
             location: "Vanha Vaasa, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 109060376,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -258,10 +315,15 @@ fn parse_multiple() {
             published: 1676294880,
             price: 297,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 98836530,
+            ..Default::default()
         },
         VahtiItem {
             deliver_to: None,
@@ -276,10 +338,15 @@ fn parse_multiple() {
             published: 1677846660,
             price: 167,
             seller_name: "Gigantti outlet Vaasa".to_string(),
-            seller_id: 3237298,
+            seller_id: Some(3237298),
+            seller_created: Some("lokakuusta 2021".to_string()),
+            seller_ads: Some(302),
+            company_ad: true,
+            postal_code: Some("65300".to_string()),
             location: "Asevelikylä, Vaasa, Pohjanmaa".to_string(),
             ad_type: "Myydään".to_string(),
             ad_id: 90554189,
+            ..Default::default()
         },
     ];
 
@@ -294,3 +361,12 @@ fn parse_multiple() {
         .map(|(a, b)| assert_eq!(a, b))
         .collect::<Vec<_>>();
 }
+
+#[test]
+fn result_count() {
+    let mut file = File::open("testdata/tori/parse_multiple.json").expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+
+    assert_eq!(api_result_count(&contents).unwrap(), 13);
+}
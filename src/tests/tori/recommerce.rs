@@ -0,0 +1,179 @@
+use std::fs::File;
+use std::io::Read;
+
+use crate::tori::recommerce::api::{legacy_to_recommerce, vahti_to_api};
+use crate::tori::recommerce::item::{parse_item, resolve_seller, RECOMMERCE_ITEM_REGEX};
+use crate::tori::recommerce::parse::{api_parse_after, api_result_count};
+use crate::tori::vahti::{RECOMMERCE_REGEX, TORI_REGEX};
+use crate::vahti::VahtiItem;
+use crate::watch::ItemStatus;
+
+const API_BASE: &str =
+    "https://www.tori.fi/recommerce-search-page/api/search/SEARCH_ID_BAP_COMMON?";
+const SEARCH_BASE: &str = "https://www.tori.fi/recommerce/forsale/search?";
+
+fn read_testdata() -> String {
+    let mut file = File::open("testdata/tori/recommerce_parse.json").expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    contents
+}
+
+fn item_json() -> serde_json::Value {
+    let mut file = File::open("testdata/tori/recommerce_item.json").expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    serde_json::from_str(&contents).unwrap()
+}
+
+#[test]
+fn url_shapes() {
+    let new = "https://www.tori.fi/recommerce/forsale/search?q=thinkpad";
+    let legacy = "https://www.tori.fi/koko_suomi?q=thinkpad";
+    assert!(TORI_REGEX.is_match(new) && RECOMMERCE_REGEX.is_match(new));
+    assert!(TORI_REGEX.is_match(legacy) && !RECOMMERCE_REGEX.is_match(legacy));
+}
+
+#[test]
+fn api_urls() {
+    let url = "https://www.tori.fi/recommerce/forsale/search?q=thinkpad&price_to=500";
+    let expected = API_BASE.to_owned() + "q=thinkpad&price_to=500&sort=PUBLISHED_DESC";
+    assert_eq!(vahti_to_api(url), expected);
+
+    let url = "https://www.tori.fi/recommerce/forsale/search?q=th%C3%B6nkpad&sort=PRICE_ASC&page=2";
+    let expected = API_BASE.to_owned() + "q=th%C3%B6nkpad&sort=PUBLISHED_DESC";
+    assert_eq!(vahti_to_api(url), expected);
+}
+
+#[test]
+fn legacy_urls() {
+    let url = "https://www.tori.fi/koko_suomi?q=thinkpad&cg=0&w=3&st=s&ca=18";
+    assert_eq!(legacy_to_recommerce(url), None);
+
+    let url = "https://www.tori.fi/koko_suomi?q=thinkpad&cg=0&w=3&st=s";
+    let expected = SEARCH_BASE.to_owned() + "q=thinkpad&trade_type=1";
+    assert_eq!(legacy_to_recommerce(url).unwrap(), expected);
+
+    let url = "https://www.tori.fi/koko_suomi?q=th%F6nkpad&ps=1&pe=4";
+    let expected = SEARCH_BASE.to_owned() + "q=th%C3%B6nkpad&price_from=25&price_to=100";
+    assert_eq!(legacy_to_recommerce(url).unwrap(), expected);
+
+    // The legacy categories and regions have no known equivalents
+    assert_eq!(
+        legacy_to_recommerce("https://www.tori.fi/koko_suomi?q=&cg=2030"),
+        None
+    );
+    assert_eq!(
+        legacy_to_recommerce("https://www.tori.fi/uusimaa?q=thinkpad"),
+        None
+    );
+    assert_eq!(
+        legacy_to_recommerce("https://www.tori.fi/koko_suomi?q=thinkpad&st=u"),
+        None
+    );
+}
+
+#[test]
+fn basic_parse() {
+    let contents = read_testdata();
+
+    let expected = VahtiItem {
+        deliver_to: None,
+        delivery_method: None,
+        site_id: crate::tori::ID,
+        title: "Lenovo ThinkPad T14 Gen 2".to_string(),
+        vahti_url: None,
+        url: "https://www.tori.fi/recommerce/forsale/item/22209811".to_string(),
+        img_url: "https://images.tori.fi/dynamic/default/item/22209811/a1b2c3d4-0000-4000-8000-000022209811".to_string(),
+        published: 1730280000,
+        price: 450,
+        seller_name: "Yksityinen".to_string(),
+        seller_id: None,
+        location: "Helsinki".to_string(),
+        coordinates: Some((60.1699, 24.9384)),
+        ad_type: "Myydään".to_string(),
+        ad_id: 22209811,
+        ..Default::default()
+    };
+
+    assert_eq!(api_parse_after(&contents, 1730200000).unwrap()[0], expected);
+}
+
+#[test]
+fn parse_multiple() {
+    let contents = read_testdata();
+    let items = api_parse_after(&contents, 0).unwrap();
+    assert_eq!(items.len(), 4);
+
+    assert_eq!(items[2].price, 0);
+    assert_eq!(items[2].img_url, "");
+    assert_eq!(items[2].ad_type, "Annetaan");
+
+    assert!(items[3].company_ad);
+    assert_eq!(items[3].seller_name, "Tietokonekauppa Oy");
+    assert_eq!(items[3].listing_id(), Some(22207320));
+    assert_eq!(items[3].coordinates, Some((65.0121, 25.4651)));
+}
+
+#[test]
+fn parse_after_promoted() {
+    let contents = read_testdata();
+
+    // The older promoted ad is skipped without ending the parsing
+    assert_eq!(api_parse_after(&contents, 1730200000).unwrap().len(), 3);
+    assert_eq!(api_parse_after(&contents, 1730270000).unwrap().len(), 1);
+    assert_eq!(api_parse_after(&contents, 1730280000).unwrap().len(), 0);
+}
+
+#[test]
+fn result_count() {
+    assert_eq!(api_result_count(&read_testdata()).unwrap(), 37);
+}
+
+#[test]
+fn active_item() {
+    let ItemStatus::Available(item) = parse_item(&item_json().to_string()).unwrap() else {
+        panic!("The item should be available");
+    };
+    assert_eq!(item.title, "Lenovo ThinkPad T14 Gen 2");
+    assert_eq!(item.price, 450);
+    assert_eq!(item.seller_id, Some(1837465));
+    assert_eq!(item.seller_name, "Mikko K");
+    assert_eq!(item.postal_code.as_deref(), Some("00530"));
+    assert_eq!(item.listing_id(), Some(22209811));
+    assert!(RECOMMERCE_ITEM_REGEX.is_match(&item.url));
+}
+
+#[test]
+fn sold_and_removed_items() {
+    let mut json = item_json();
+    json["ad"]["state"] = "SOLD".into();
+    assert_eq!(parse_item(&json.to_string()).unwrap(), ItemStatus::Sold);
+
+    json["ad"]["state"] = "DISPOSED".into();
+    assert_eq!(parse_item(&json.to_string()).unwrap(), ItemStatus::Removed);
+    assert_eq!(parse_item("{}").unwrap(), ItemStatus::Removed);
+}
+
+#[test]
+fn resolved_sellers() {
+    let mut item = api_parse_after(&read_testdata(), 1730200000).unwrap()[0].clone();
+    assert!(item.seller().is_none());
+
+    let ItemStatus::Available(ad) = parse_item(&item_json().to_string()).unwrap() else {
+        panic!("The item should be available");
+    };
+    resolve_seller(&mut item, &ad);
+
+    let seller = item.seller().expect("The seller should be resolved");
+    assert_eq!(seller.seller_id, 1837465);
+    assert_eq!(seller.site_id, crate::tori::ID);
+    assert_eq!(seller.name, "Mikko K");
+    assert_eq!(seller.account_created.as_deref(), Some("2016"));
+    assert_eq!(seller.ad_count, Some(12));
+    assert_eq!(
+        item.seller_url().unwrap(),
+        "https://www.tori.fi/profile/ads?userId=1837465"
+    );
+    assert_eq!(item.postal_code.as_deref(), Some("00530"));
+}
@@ -0,0 +1,23 @@
+use std::fs::File;
+use std::io::Read;
+
+use crate::tori::seller::parse_seller;
+
+#[test]
+fn basic_seller() {
+    let mut file = File::open("testdata/tori/basic_parse.json").expect("Test data not found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+
+    let seller = parse_seller(&contents).unwrap().unwrap();
+    assert_eq!(seller.seller_id, 188169);
+    assert_eq!(seller.site_id, crate::tori::ID);
+    assert_eq!(seller.name, "H.S.M");
+    assert_eq!(seller.account_created.as_deref(), Some("tammikuusta 2014"));
+    assert_eq!(seller.ad_count, Some(82));
+}
+
+#[test]
+fn no_ads() {
+    assert!(parse_seller(r#"{"list_ads": []}"#).unwrap().is_none());
+}
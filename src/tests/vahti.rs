@@ -0,0 +1,716 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use super::database::{test_database, test_user, PENDING};
+use super::item;
+use crate::admin::parse_admin;
+use crate::config::VahtiConfig;
+use crate::database::Database;
+use crate::delivery::{Delivery, Notification, NotificationAction};
+use crate::error::Error;
+use crate::itemhistory::{ItemHistory, ItemHistoryStorage};
+use crate::models::{DbVahti, NewPendingDelivery};
+use crate::vahti::{
+    address_items, check_quota, extend_vahti, new_vahti, pause_vahti, persist_pending,
+    process_results, remove_vahti, resume_vahti, SearchResult, VahtiItem,
+};
+use crate::Torimies;
+
+#[tokio::test]
+async fn destinations() {
+    let t = test_database().await;
+    let owner = (test_user(), 1);
+    let linked = (test_user(), 2);
+    let url = format!("polkupyörä {}", owner.0);
+
+    new_vahti(t.db.clone(), &url, owner.0 as u64, owner.1, None)
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, owner.0).await.unwrap();
+
+    // Linked identities receive the existing Vahti instead of a second one
+    t.db.link_identities(owner, linked).await.unwrap();
+    assert_eq!(
+        new_vahti(t.db.clone(), &url, linked.0 as u64, linked.1, None)
+            .await
+            .unwrap(),
+        "Vahti of a linked account will now be delivered here as well"
+    );
+    assert!(matches!(
+        new_vahti(t.db.clone(), &url, linked.0 as u64, linked.1, None).await,
+        Err(Error::VahtiExists)
+    ));
+
+    let destinations = t.db.fetch_all_vahti_destinations().await.unwrap();
+    assert_eq!(destinations.get(&vahti.id), Some(&vec![linked]));
+    assert_eq!(
+        t.db.fetch_vahti_entries_by_destination(linked.0, linked.1)
+            .await
+            .unwrap()
+            .iter()
+            .map(|v| v.id)
+            .collect::<Vec<_>>(),
+        vec![vahti.id]
+    );
+
+    // Removing the Vahti from a destination leaves the Vahti of the owner
+    remove_vahti(t.db.clone(), &url, linked.0 as u64, linked.1)
+        .await
+        .unwrap();
+    assert!(!t
+        .db
+        .fetch_all_vahti_destinations()
+        .await
+        .unwrap()
+        .contains_key(&vahti.id));
+    assert!(t.db.fetch_vahti(&url, owner.0).await.is_ok());
+
+    t.db.remove_vahti_entry(&url, owner.0, owner.1)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn unreachable_destinations() {
+    let t = test_database().await;
+    let recipient = (test_user(), 2);
+    let url = format!("https://example.com/{}", recipient.0);
+    let owner = test_user();
+
+    t.db.add_vahti_entry(&url, owner, 1, 1, None).await.unwrap();
+    let id = t.db.fetch_vahti(&url, owner).await.unwrap().id;
+    t.db.add_vahti_destination(id, recipient.0, recipient.1)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        t.db.remove_destinations_of_recipient(recipient.0, recipient.1)
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(!t
+        .db
+        .fetch_all_vahti_destinations()
+        .await
+        .unwrap()
+        .contains_key(&id));
+}
+
+#[tokio::test]
+async fn address_destinations() {
+    let t = test_database().await;
+    let owner = (test_user(), 1);
+    let recipient = (test_user(), 2);
+    let mut vahti = DbVahti::new(
+        1,
+        String::from("polkupyörä"),
+        owner.0,
+        crate::search::ID,
+        owner.1,
+        0,
+    );
+    vahti.max_distance = Some(50);
+
+    t.db.set_home_location(owner.0, owner.1, Some(String::from("00100")))
+        .await
+        .unwrap();
+    t.db.set_home_location(recipient.0, recipient.1, Some(String::from("33100")))
+        .await
+        .unwrap();
+
+    let owned = |i: VahtiItem| VahtiItem {
+        deliver_to: Some(owner.0 as u64),
+        delivery_method: Some(owner.1),
+        ..i
+    };
+    let items = vec![
+        owned(VahtiItem {
+            postal_code: Some(String::from("00630")),
+            ..item(1, 100)
+        }),
+        owned(VahtiItem {
+            postal_code: Some(String::from("33100")),
+            ..item(2, 100)
+        }),
+        owned(VahtiItem {
+            postal_code: Some(String::from("00630")),
+            previous_price: Some(150),
+            ..item(3, 100)
+        }),
+    ];
+
+    let addressed = address_items(&t.db, &vahti, &[recipient], items)
+        .await
+        .unwrap();
+    let delivered = |to: (i64, i32)| {
+        addressed
+            .iter()
+            .filter(|i| i.deliver_to == Some(to.0 as u64) && i.delivery_method == Some(to.1))
+            .collect::<Vec<_>>()
+    };
+
+    // The distance limit of the owner only applies to the owner
+    let own = delivered(owner);
+    assert_eq!(own.iter().map(|i| i.ad_id).collect::<Vec<_>>(), vec![1, 3]);
+    assert!(own[0].distance.is_some_and(|d| d < 50));
+
+    // The recipient gets no price drops and the distances from their own home
+    let copies = delivered(recipient);
+    assert_eq!(
+        copies.iter().map(|i| i.ad_id).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert!(copies[0].distance.is_some_and(|d| d > 50));
+    assert!(copies[1].distance.is_some_and(|d| d < 50));
+}
+
+#[tokio::test]
+async fn newest_items() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+    t.db.add_vahti_entry(&url, user, 1, 1, None).await.unwrap();
+    let vahti = DbVahti {
+        last_updated: 100,
+        ..t.db.fetch_vahti(&url, user).await.unwrap()
+    };
+
+    let mut history = ItemHistory::new();
+    history.add_item(2, 1, 0);
+    let ihs = ItemHistoryStorage::default();
+    ihs.insert((user as u64, 1), Arc::new(Mutex::new(history)));
+
+    let published = |ad_id, published| VahtiItem {
+        published,
+        ..item(ad_id, 100)
+    };
+    let results = SearchResult {
+        count: 3,
+        items: vec![published(1, 150), published(2, 200), published(3, 50)],
+    };
+    let (newest, items) = process_results(&t.db, &ihs, &vahti, results).await.unwrap();
+
+    // The next search continues after the newest item even if it is not delivered
+    assert_eq!(items.iter().map(|i| i.ad_id).collect::<Vec<_>>(), vec![1]);
+    assert_eq!(newest, Some(200));
+}
+
+#[cfg(feature = "muusikoiden")]
+#[tokio::test]
+async fn same_minute_items() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://muusikoiden.net/tori/haku.php?keyword={}", user);
+    t.db.add_vahti_entry(&url, user, crate::muusikoiden::ID, 1, None)
+        .await
+        .unwrap();
+    let mut vahti = DbVahti {
+        last_updated: 0,
+        ..t.db.fetch_vahti(&url, user).await.unwrap()
+    };
+    let ihs = ItemHistoryStorage::default();
+    ihs.insert((user as u64, 1), Arc::new(Mutex::new(ItemHistory::new())));
+
+    let page = std::fs::read_to_string("testdata/muusikoiden/same_minute.html").unwrap();
+    let ads = crate::muusikoiden::parse::parse_after(&page, 0).unwrap();
+    let ids = |items: Vec<VahtiItem>| items.iter().map(|i| i.ad_id).collect::<Vec<_>>();
+
+    // The first search only finds the older of the ads published during the same minute
+    let results = SearchResult {
+        count: 1,
+        items: ads[1..].to_vec(),
+    };
+    let (newest, items) = process_results(&t.db, &ihs, &vahti, results).await.unwrap();
+    assert_eq!(ids(items), vec![3450201]);
+    vahti.last_updated = newest.unwrap();
+
+    // The newer one is found by the next search without delivering the older one again
+    let results = SearchResult {
+        count: 2,
+        items: ads,
+    };
+    let (_, items) = process_results(&t.db, &ihs, &vahti, results).await.unwrap();
+    assert_eq!(ids(items), vec![3450202]);
+}
+
+#[tokio::test]
+async fn pause_and_resume() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+    let dm = 1;
+
+    t.db.add_vahti_entry(&url, user, 1, dm, None).await.unwrap();
+    assert_eq!(
+        resume_vahti(t.db.clone(), &url, user as u64, dm, false)
+            .await
+            .unwrap(),
+        "The Vahti is not paused"
+    );
+
+    let now = chrono::Local::now().timestamp();
+    assert_eq!(
+        pause_vahti(t.db.clone(), &url, user as u64, dm, Some(2))
+            .await
+            .unwrap(),
+        "Vahti paused for 2 days!"
+    );
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert!(vahti.paused);
+    assert!(vahti
+        .resume_at
+        .is_some_and(|r| r >= now + 2 * 86400 && r <= now + 2 * 86400 + 5));
+
+    // The items posted while the Vahti was paused are skipped
+    let future = now + 1000;
+    t.db.vahtis_updated(vec![(vahti.id, future)]).await.unwrap();
+    resume_vahti(t.db.clone(), &url, user as u64, dm, false)
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert!(!vahti.paused);
+    assert_eq!(vahti.resume_at, None);
+    assert!(vahti.last_updated >= now && vahti.last_updated < future);
+
+    // Unless the user asks to catch up on them
+    pause_vahti(t.db.clone(), &url, user as u64, dm, None)
+        .await
+        .unwrap();
+    t.db.vahtis_updated(vec![(vahti.id, future)]).await.unwrap();
+    resume_vahti(t.db.clone(), &url, user as u64, dm, true)
+        .await
+        .unwrap();
+    assert_eq!(
+        t.db.fetch_vahti(&url, user).await.unwrap().last_updated,
+        future
+    );
+
+    t.db.remove_vahti_entry(&url, user, dm).await.unwrap();
+}
+
+#[tokio::test]
+async fn resume_due_vahtis() {
+    let t = test_database().await;
+    let user = test_user();
+    let now = chrono::Local::now().timestamp();
+    let due = format!("https://example.com/{}/due", user);
+    let later = format!("https://example.com/{}/later", user);
+    let indefinite = format!("https://example.com/{}/indefinite", user);
+
+    for (url, resume) in [
+        (&due, Some(now - 1)),
+        (&later, Some(now + 86400)),
+        (&indefinite, None),
+    ] {
+        t.db.add_vahti_entry(url, user, 1, 1, None).await.unwrap();
+        t.db.pause_vahti(url, user, 1, resume).await.unwrap();
+    }
+
+    assert!(t.db.resume_due_vahtis().await.unwrap() >= 1);
+    let due = t.db.fetch_vahti(&due, user).await.unwrap();
+    assert!(!due.paused);
+    assert!(due.last_updated >= now);
+    assert!(t.db.fetch_vahti(&later, user).await.unwrap().paused);
+    assert!(t.db.fetch_vahti(&indefinite, user).await.unwrap().paused);
+}
+
+#[tokio::test]
+async fn resume_expired_vahti() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, 1, 1, Some(1))
+        .await
+        .unwrap();
+    t.db.pause_vahti(&url, user, 1, None).await.unwrap();
+
+    // Resuming removes the expiry that paused the Vahti
+    resume_vahti(t.db.clone(), &url, user as u64, 1, false)
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert!(!vahti.paused);
+    assert_eq!(vahti.expires_at, None);
+}
+
+#[tokio::test]
+async fn expired_vahtis() {
+    let t = test_database().await;
+    let (man, delivery) = test_torimies(&t.db);
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+    let now = chrono::Local::now().timestamp();
+
+    t.db.add_vahti_entry(&url, user, 1, 1, Some(now - 1))
+        .await
+        .unwrap();
+    man.update_vahti_states().await.unwrap();
+    assert!(t.db.fetch_vahti(&url, user).await.unwrap().paused);
+    let notified = delivery.notified_to(user);
+    assert_eq!(notified.len(), 1);
+    assert!(notified[0].message.contains(&url) && notified[0].message.contains("/jatka"));
+
+    // Resuming with /jatka removes the expiry, so the Vahti is not paused again
+    resume_vahti(t.db.clone(), &url, user as u64, 1, false)
+        .await
+        .unwrap();
+    man.update_vahti_states().await.unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert!(!vahti.paused);
+    assert_eq!(vahti.expires_at, None);
+    assert_eq!(delivery.notified_to(user).len(), 1);
+}
+
+#[tokio::test]
+async fn expiry_reminders() {
+    let t = test_database().await;
+    let (man, delivery) = test_torimies(&t.db);
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+    let expires_at = chrono::Local::now().timestamp() + 86400;
+    let extension = i64::from(crate::config::get().vahti.extend_days) * 86400;
+
+    t.db.add_vahti_entry(&url, user, 1, 1, Some(expires_at))
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+
+    // The reminder is only sent once
+    man.update_vahti_states().await.unwrap();
+    man.update_vahti_states().await.unwrap();
+    let notified = delivery.notified_to(user);
+    assert_eq!(notified.len(), 1);
+    assert_eq!(
+        notified[0].action,
+        Some(NotificationAction::ExtendVahti(vahti.id))
+    );
+    let reminded = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert!(reminded.reminded && !reminded.paused);
+
+    // Only the owner can extend the Vahti
+    assert_eq!(
+        extend_vahti(t.db.clone(), vahti.id, test_user() as u64, 1)
+            .await
+            .unwrap(),
+        "A Vahti is not defined with that id"
+    );
+
+    // Extending moves the expiry forward and sends a new reminder before it
+    assert!(extend_vahti(t.db.clone(), vahti.id, user as u64, 1)
+        .await
+        .unwrap()
+        .starts_with("Vahti extended until"));
+    let extended = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert_eq!(extended.expires_at, Some(expires_at + extension));
+    assert!(!extended.reminded);
+}
+
+#[tokio::test]
+async fn extend_expired_vahti() {
+    let t = test_database().await;
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+    let now = chrono::Local::now().timestamp();
+    let extension = i64::from(crate::config::get().vahti.extend_days) * 86400;
+
+    t.db.add_vahti_entry(&url, user, 1, 1, Some(now - 86400))
+        .await
+        .unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+
+    // An expired Vahti is extended from the current time
+    extend_vahti(t.db.clone(), vahti.id, user as u64, 1)
+        .await
+        .unwrap();
+    assert!(t
+        .db
+        .fetch_vahti(&url, user)
+        .await
+        .unwrap()
+        .expires_at
+        .is_some_and(|e| e >= now + extension && e <= now + extension + 5));
+
+    t.db.set_vahti_expiry(vahti.id, None).await.unwrap();
+    assert_eq!(
+        extend_vahti(t.db.clone(), vahti.id, user as u64, 1)
+            .await
+            .unwrap(),
+        "The Vahti does not expire"
+    );
+}
+
+#[tokio::test]
+async fn empty_vahtis() {
+    let t = test_database().await;
+    let (man, delivery) = test_torimies(&t.db);
+    let user = test_user();
+    let now = chrono::Local::now().timestamp();
+    let pause_after = i64::from(crate::config::get().vahti.empty_pause_days) * 86400;
+    let empty = format!("https://example.com/{}/empty", user);
+    let recent = format!("https://example.com/{}/recent", user);
+
+    for (url, since) in [(&empty, now - pause_after - 60), (&recent, now - 60)] {
+        t.db.add_vahti_entry(url, user, 1, 1, None).await.unwrap();
+        let vahti = t.db.fetch_vahti(url, user).await.unwrap();
+        t.db.vahti_searched(vahti.id, false, since).await.unwrap();
+    }
+
+    man.update_vahti_states().await.unwrap();
+    assert!(t.db.fetch_vahti(&empty, user).await.unwrap().paused);
+    assert!(!t.db.fetch_vahti(&recent, user).await.unwrap().paused);
+    let notified = delivery.notified_to(user);
+    assert_eq!(notified.len(), 1);
+    assert!(notified[0].message.contains(&empty));
+
+    // Resuming forgets how long the Vahti has been empty
+    resume_vahti(t.db.clone(), &empty, user as u64, 1, false)
+        .await
+        .unwrap();
+    man.update_vahti_states().await.unwrap();
+    assert!(!t.db.fetch_vahti(&empty, user).await.unwrap().paused);
+}
+
+#[tokio::test]
+async fn timed_pauses() {
+    let t = test_database().await;
+    let (man, _) = test_torimies(&t.db);
+    let user = test_user();
+    let url = format!("https://example.com/{}", user);
+
+    t.db.add_vahti_entry(&url, user, 1, 1, None).await.unwrap();
+    t.db.pause_vahti(&url, user, 1, Some(chrono::Local::now().timestamp() - 1))
+        .await
+        .unwrap();
+
+    man.update_vahti_states().await.unwrap();
+    let vahti = t.db.fetch_vahti(&url, user).await.unwrap();
+    assert!(!vahti.paused);
+    assert_eq!(vahti.resume_at, None);
+}
+
+#[tokio::test]
+async fn quota() {
+    let t = test_database().await;
+    let first = (test_user(), 1);
+    let second = (test_user(), 2);
+    let limits = VahtiConfig {
+        max_per_user: Some(2),
+        min_interval: 0,
+        ..Default::default()
+    };
+    let check = |user: (i64, i32), limits: &VahtiConfig| {
+        let (db, limits) = (t.db.clone(), limits.clone());
+        async move { check_quota(&db, &limits, user.0 as u64, user.1).await }
+    };
+
+    assert!(check(first, &limits).await.is_ok());
+    t.db.add_vahti_entry("https://example.com/1", first.0, 1, first.1, None)
+        .await
+        .unwrap();
+    assert!(check(first, &limits).await.is_ok());
+
+    // The limit is shared by the linked identities
+    t.db.link_identities(first, second).await.unwrap();
+    t.db.add_vahti_entry("https://example.com/2", second.0, 1, second.1, None)
+        .await
+        .unwrap();
+    assert!(matches!(
+        check(first, &limits).await,
+        Err(Error::VahtiLimit(2))
+    ));
+    assert!(matches!(
+        check(second, &limits).await,
+        Err(Error::VahtiLimit(2))
+    ));
+
+    let limits = VahtiConfig {
+        max_per_user: None,
+        min_interval: 600,
+        ..Default::default()
+    };
+    assert!(matches!(
+        check(second, &limits).await,
+        Err(Error::VahtiRateLimit(w)) if w > 590 && w <= 600
+    ));
+
+    let limits = VahtiConfig {
+        max_per_user: None,
+        min_interval: 0,
+        ..Default::default()
+    };
+    assert!(check(second, &limits).await.is_ok());
+}
+
+#[tokio::test]
+async fn banned_users() {
+    let t = test_database().await;
+    let first = (test_user(), 1);
+    let second = (test_user(), 2);
+    t.db.link_identities(first, second).await.unwrap();
+    t.db.add_ban(first.0, first.1).await.unwrap();
+
+    // The ban of a linked identity keeps the user from adding Vahtis
+    let url = format!("https://example.com/{}", second.0);
+    assert!(matches!(
+        new_vahti(t.db.clone(), &url, second.0 as u64, second.1, None).await,
+        Err(Error::Banned)
+    ));
+    assert!(t.db.fetch_vahti(&url, second.0).await.is_err());
+}
+
+#[cfg(all(feature = "discord-delivery", feature = "telegram-delivery"))]
+#[test]
+fn admins() {
+    assert_eq!(
+        parse_admin("discord:1234"),
+        Some((1234, crate::delivery::discord::ID))
+    );
+    assert_eq!(
+        parse_admin(" Telegram:5678 "),
+        Some((5678, crate::delivery::telegram::ID))
+    );
+    assert_eq!(parse_admin("discord:abc"), None);
+    assert_eq!(parse_admin("matrix:1234"), None);
+    assert_eq!(parse_admin("1234"), None);
+}
+
+/// Collects the delivered items and notifications
+#[derive(Clone, Default)]
+struct TestDelivery {
+    delivered: Arc<Mutex<Vec<Vec<VahtiItem>>>>,
+    notified: Arc<Mutex<Vec<Notification>>>,
+}
+
+impl TestDelivery {
+    fn notified_to(&self, user: i64) -> Vec<Notification> {
+        self.notified
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| n.deliver_to == user as u64)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Delivery for TestDelivery {
+    async fn deliver(&self, vs: Vec<VahtiItem>) -> Result<(), Error> {
+        self.delivered.lock().unwrap().push(vs);
+        Ok(())
+    }
+
+    async fn notify(&self, n: Notification) -> Result<(), Error> {
+        self.notified.lock().unwrap().push(n);
+        Ok(())
+    }
+}
+
+/// A Torimies delivering through a TestDelivery with the delivery method 1
+fn test_torimies(db: &Database) -> (Torimies, TestDelivery) {
+    let man = Torimies::new(db.clone());
+    let delivery = TestDelivery::default();
+    man.delivery.insert(1, Box::new(delivery.clone()));
+    (man, delivery)
+}
+
+#[tokio::test]
+async fn pending_deliveries() {
+    let t = test_database().await;
+    let _guard = PENDING.lock().await;
+    let (first, second) = (test_user() as u64, test_user() as u64);
+    let dm = 1;
+    let pending = |user, ad_id| VahtiItem {
+        deliver_to: Some(user),
+        delivery_method: Some(dm),
+        ..item(ad_id, 100)
+    };
+
+    let items = vec![pending(first, 1), pending(second, 2), pending(first, 3)];
+    persist_pending(&t.db, items.clone()).await;
+
+    // The pending items are delivered to their recipients after a restart
+    let man = Torimies::new(t.db.clone());
+    let delivery = TestDelivery::default();
+    man.delivery.insert(dm, Box::new(delivery.clone()));
+    man.deliver_pending().await.unwrap();
+
+    let delivered = delivery.delivered.lock().unwrap().clone();
+    let to = |user| {
+        delivered
+            .iter()
+            .filter(|vs| vs.iter().all(|i| i.deliver_to == Some(user)))
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(to(first), vec![items[0].clone(), items[2].clone()]);
+    assert_eq!(to(second), vec![items[1].clone()]);
+
+    // and only once
+    assert!(!t
+        .db
+        .take_pending_deliveries()
+        .await
+        .unwrap()
+        .iter()
+        .any(|p| [first, second].contains(&(p.deliver_to as u64))));
+}
+
+/// Fails every delivery as if the recipient had blocked the bot
+struct UnreachableDelivery;
+
+#[async_trait]
+impl Delivery for UnreachableDelivery {
+    async fn deliver(&self, vs: Vec<VahtiItem>) -> Result<(), Error> {
+        Err(Error::RecipientUnreachable(vs[0].deliver_to.unwrap()))
+    }
+
+    async fn notify(&self, n: Notification) -> Result<(), Error> {
+        Err(Error::RecipientUnreachable(n.deliver_to))
+    }
+}
+
+#[tokio::test]
+async fn failed_pending_deliveries() {
+    let t = test_database().await;
+    let _guard = PENDING.lock().await;
+    let (unreachable, disabled) = (test_user() as u64, test_user() as u64);
+    let pending = |user, dm, ad_id| VahtiItem {
+        deliver_to: Some(user),
+        delivery_method: Some(dm),
+        ..item(ad_id, 100)
+    };
+
+    persist_pending(
+        &t.db,
+        vec![pending(unreachable, 1, 1), pending(disabled, 2, 2)],
+    )
+    .await;
+    t.db.add_pending_deliveries(&[NewPendingDelivery {
+        deliver_to: disabled as i64,
+        delivery_method: 2,
+        item: String::from("{"),
+        created: 0,
+    }])
+    .await
+    .unwrap();
+
+    let man = Torimies::new(t.db.clone());
+    man.delivery.insert(1, Box::new(UnreachableDelivery));
+    man.deliver_pending().await.unwrap();
+
+    // Only the readable items of reachable recipients are kept for the next round
+    let kept =
+        t.db.take_pending_deliveries()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|p| [unreachable, disabled].contains(&(p.deliver_to as u64)))
+            .map(|p| serde_json::from_str::<VahtiItem>(&p.item).unwrap())
+            .collect::<Vec<_>>();
+    assert_eq!(kept, vec![pending(disabled, 2, 2)]);
+}
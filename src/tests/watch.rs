@@ -0,0 +1,96 @@
+use super::item;
+use crate::models::WatchedItem;
+use crate::vahti::VahtiItem;
+use crate::watch::{change_message, parse_watch_action_id};
+
+#[test]
+fn listing_ids() {
+    let listing = |url: &str| {
+        VahtiItem {
+            url: url.to_string(),
+            ..item(1, 0)
+        }
+        .listing_id()
+    };
+    assert_eq!(
+        listing("https://www.tori.fi/vi/81076530.htm"),
+        Some(81076530)
+    );
+    assert_eq!(
+        listing("https://www.huuto.net/kohteet/tekniikan-maailma-20_1993/575647318"),
+        Some(575647318)
+    );
+    assert_eq!(
+        listing("https://www.tori.fi/vi/81076530.htm?ca=18"),
+        Some(81076530)
+    );
+    assert_eq!(listing("https://www.tori.fi/"), None);
+}
+
+#[test]
+fn watch_action_ids() {
+    let id = VahtiItem {
+        url: "https://www.tori.fi/vi/81076530.htm".to_string(),
+        ..item(1, 0)
+    }
+    .watch_action_id()
+    .unwrap();
+    assert_eq!(id, "watch_item_1_81076530");
+    assert_eq!(parse_watch_action_id(&id), Some((1, 81076530, false)));
+    assert_eq!(parse_watch_action_id("unwatch_item_1"), None);
+    assert_eq!(parse_watch_action_id("watch_item_1"), None);
+
+    // The listings of the new Tori platform are looked up with their own API
+    let id = VahtiItem {
+        url: "https://www.tori.fi/recommerce/forsale/item/22140567".to_string(),
+        ..item(1, 0)
+    }
+    .watch_action_id()
+    .unwrap();
+    assert_eq!(id, "watch_item_1_22140567_r");
+    assert_eq!(parse_watch_action_id(&id), Some((1, 22140567, true)));
+}
+
+#[cfg(feature = "muusikoiden")]
+#[test]
+fn unwatchable_sites() {
+    // Muusikoiden.net listings can't be looked up, so they have no watch buttons
+    let muusikoiden = VahtiItem {
+        site_id: crate::muusikoiden::ID,
+        url: "https://muusikoiden.net/tori/ilmoitus/3450123".to_string(),
+        ..item(1, 0)
+    };
+    assert_eq!(muusikoiden.listing_id(), Some(3450123));
+    assert_eq!(muusikoiden.watch_action_id(), None);
+}
+
+#[test]
+fn changes() {
+    let watched = WatchedItem {
+        id: 1,
+        user_id: 1,
+        delivery_method: 1,
+        site_id: 1,
+        listing_id: 81076530,
+        title: String::from("Polkupyörä"),
+        url: String::from("https://www.tori.fi/vi/81076530.htm"),
+        price: 100,
+        created: 0,
+        checked: 0,
+    };
+    let current = |title: &str, price| VahtiItem {
+        title: title.to_string(),
+        url: watched.url.clone(),
+        ..item(1, price)
+    };
+
+    assert_eq!(change_message(&watched, &current("Polkupyörä", 100)), None);
+    assert_eq!(
+        change_message(&watched, &current("Polkupyörä", 80)).unwrap(),
+        "Seuraamasi ilmoitus https://www.tori.fi/vi/81076530.htm muuttui: hinta 100 € → 80 €"
+    );
+    assert_eq!(
+        change_message(&watched, &current("Sähköpolkupyörä", 80)).unwrap(),
+        "Seuraamasi ilmoitus https://www.tori.fi/vi/81076530.htm muuttui: otsikko \"Polkupyörä\" → \"Sähköpolkupyörä\", hinta 100 € → 80 €"
+    );
+}
@@ -3,10 +3,11 @@ use encoding::{DecoderTrap, Encoding};
 use serde_json::Value;
 use url::Url;
 
-const TORI_PRICES: [&str; 9] = ["0", "25", "50", "75", "100", "250", "500", "1000", "2000"];
+pub(super) const TORI_PRICES: [&str; 9] =
+    ["0", "25", "50", "75", "100", "250", "500", "1000", "2000"];
 
 // NOTE: Couldn't find a good crate to do this
-fn url_decode(url: &str) -> String {
+pub(super) fn url_decode(url: &str) -> String {
     let mut result = String::new();
     let mut chars = url.chars().peekable();
 
@@ -0,0 +1,30 @@
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use super::models::FullToriItem;
+use crate::error::Error;
+use crate::vahti::VahtiItem;
+use crate::watch::ItemStatus;
+
+/// Parses the status of an ad from the public ad endpoint
+pub fn parse_item(json: &str) -> Result<ItemStatus, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    let ad = &response_json["ad"];
+    match ad["status"].as_str() {
+        Some("active") => {
+            let fullitem: FullToriItem = serde_json::from_value(ad.to_owned())?;
+            Ok(ItemStatus::Available(Box::new(VahtiItem::from(fullitem))))
+        }
+        Some("sold") => Ok(ItemStatus::Sold),
+        _ => Ok(ItemStatus::Removed),
+    }
+}
+
+pub async fn fetch_item(listingid: i64) -> Result<ItemStatus, Error> {
+    let url = format!("https://api.tori.fi/api/v1.2/public/ads/{}", listingid);
+    let response = reqwest::get(&url).await?;
+    if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+        return Ok(ItemStatus::Removed);
+    }
+    parse_item(&response.error_for_status()?.text().await?)
+}
@@ -1,6 +1,8 @@
 pub mod api;
+pub mod item;
 mod models;
 pub mod parse;
+pub mod recommerce;
 pub mod seller;
 pub mod vahti;
 
@@ -133,9 +133,13 @@ impl From<FullToriItem> for VahtiItem {
         };
 
         let mut location_vec: Vec<String> = vec![];
+        let mut postal_code = None;
         let mut loc = &t.locations[0];
         loop {
             location_vec.push(loc.label.clone());
+            if loc.key == "zipcode" {
+                postal_code = Some(loc.code.clone());
+            }
             if loc.locations.is_empty() {
                 break;
             }
@@ -167,7 +171,17 @@ impl From<FullToriItem> for VahtiItem {
             published: t.list_time.value,
             price: t.list_price.price_value,
             seller_name: t.user.account.name,
-            seller_id: t.account.code.parse().unwrap(),
+            seller_id: Some(t.account.code.parse().unwrap()),
+            seller_created: Some(t.user.account.created).filter(|c| !c.is_empty()),
+            seller_ads: t.account_ads.code.parse().ok(),
+            company_ad: t.company_ad,
+            previous_price: None,
+            repost: None,
+            median_price: None,
+            postal_code,
+            coordinates: None,
+            distance: None,
+            vehicle: None,
             location,
             ad_type: t.r#type.label,
             ad_id: t.ad_id[t.ad_id.rfind('/').unwrap() + 1..].parse().unwrap(),
@@ -30,3 +30,11 @@ pub fn api_parse_after(json: &str, after: i64) -> Result<Vec<VahtiItem>, Error>
     debug!("Parsed {} items", items.len());
     Ok(items)
 }
+
+/// Returns the total amount of ads matching the search
+pub fn api_result_count(json: &str) -> Result<i64, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    response_json["counter_map"]["all"]
+        .as_i64()
+        .ok_or(Error::Tori)
+}
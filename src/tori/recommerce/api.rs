@@ -0,0 +1,74 @@
+use url::form_urlencoded::Serializer;
+use url::Url;
+
+use super::super::api::{url_decode, TORI_PRICES};
+use crate::error::Error;
+
+const API_BASE: &str =
+    "https://www.tori.fi/recommerce-search-page/api/search/SEARCH_ID_BAP_COMMON?";
+const SEARCH_BASE: &str = "https://www.tori.fi/recommerce/forsale/search?";
+
+/// The legacy `st` ad types and the corresponding `trade_type`s
+const TRADE_TYPES: [(&str, &str); 3] = [("s", "1"), ("k", "2"), ("g", "3")];
+
+/// The search page and the API share the parameters, the results are only
+/// sorted by the newest first and start from the first page
+pub fn vahti_to_api(vahti: &str) -> String {
+    let mut query = Serializer::for_suffix(String::from(API_BASE), API_BASE.len());
+    if let Ok(url) = Url::parse(vahti) {
+        query.extend_pairs(
+            url.query_pairs()
+                .filter(|(k, v)| k != "sort" && k != "page" && !v.is_empty()),
+        );
+    }
+    query.append_pair("sort", "PUBLISHED_DESC").finish()
+}
+
+/// Converts a legacy search url to a search url of the new platform.
+/// Only searches of the whole country with a search term, a price range or an ad type
+/// can be converted, the legacy categories and regions have no known equivalents
+pub fn legacy_to_recommerce(legacy: &str) -> Option<String> {
+    let url = Url::parse(&url_decode(legacy)).ok()?;
+    if !matches!(
+        url.host_str(),
+        Some("tori.fi" | "www.tori.fi" | "m.tori.fi")
+    ) || url.path() != "/koko_suomi"
+    {
+        return None;
+    }
+
+    let mut query = Serializer::for_suffix(String::from(SEARCH_BASE), SEARCH_BASE.len());
+    for (key, value) in url.query_pairs() {
+        match (&*key, &*value) {
+            // NOTE: The page, the sorting and the filters matching everything are ignored
+            (_, "") | ("cg", "0") | ("w", "3") | ("f", "a") | ("o" | "sp", _) => {}
+            ("q", q) => {
+                query.append_pair("q", q);
+            }
+            ("ps", i) => match TORI_PRICES.get(i.parse::<usize>().ok()?)? {
+                &"0" => {}
+                p => {
+                    query.append_pair("price_from", p);
+                }
+            },
+            ("pe", i) => {
+                query.append_pair("price_to", TORI_PRICES.get(i.parse::<usize>().ok()?)?);
+            }
+            ("st", t) => {
+                let (_, trade_type) = TRADE_TYPES.iter().find(|(st, _)| *st == t)?;
+                query.append_pair("trade_type", trade_type);
+            }
+            _ => return None,
+        }
+    }
+    Some(query.finish())
+}
+
+pub async fn is_valid_url(url: &str) -> Result<bool, Error> {
+    let response = reqwest::get(vahti_to_api(url))
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    Ok(super::parse::api_result_count(&response)? > 0)
+}
@@ -0,0 +1,73 @@
+use std::sync::LazyLock;
+
+use futures::stream::{self, StreamExt};
+use regex::Regex;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use super::models::FullRecommerceAd;
+use crate::error::Error;
+use crate::vahti::VahtiItem;
+use crate::watch::ItemStatus;
+
+const ITEM_API_BASE: &str = "https://www.tori.fi/recommerce-item-page/api/item/";
+
+/// The item urls of the new Tori platform, the listings of the legacy urls use the legacy API
+pub static RECOMMERCE_ITEM_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^https://(www\.)?tori\.fi/recommerce/forsale/item/\d+").unwrap());
+
+/// Parses the status of an ad from the item page API
+pub fn parse_item(json: &str) -> Result<ItemStatus, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    let ad = &response_json["ad"];
+    match ad["state"].as_str() {
+        Some("ACTIVE") => {
+            let fullitem: FullRecommerceAd = serde_json::from_value(ad.to_owned())?;
+            Ok(ItemStatus::Available(Box::new(VahtiItem::from(fullitem))))
+        }
+        Some("SOLD") => Ok(ItemStatus::Sold),
+        _ => Ok(ItemStatus::Removed),
+    }
+}
+
+pub async fn fetch_item(listingid: i64) -> Result<ItemStatus, Error> {
+    let response = reqwest::get(format!("{}{}", ITEM_API_BASE, listingid)).await?;
+    if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+        return Ok(ItemStatus::Removed);
+    }
+    parse_item(&response.error_for_status()?.text().await?)
+}
+
+/// Copies the seller and the postal code of the ad, which the search results lack, to the item
+pub fn resolve_seller(item: &mut VahtiItem, ad: &VahtiItem) {
+    item.seller_id = ad.seller_id;
+    item.seller_name.clone_from(&ad.seller_name);
+    item.seller_created.clone_from(&ad.seller_created);
+    item.seller_ads = ad.seller_ads;
+    if item.postal_code.is_none() {
+        item.postal_code.clone_from(&ad.postal_code);
+    }
+}
+
+/// Looks up the sellers of the items published after `after` from their item pages.
+/// Items that fail to be looked up are kept without a seller
+pub async fn resolve_sellers(items: Vec<VahtiItem>, after: i64) -> Vec<VahtiItem> {
+    stream::iter(items)
+        .map(|mut item| async move {
+            if item.seller_id.is_some() || item.published <= after {
+                return item;
+            }
+            let Some(listingid) = item.listing_id() else {
+                return item;
+            };
+            match fetch_item(listingid).await {
+                Ok(ItemStatus::Available(ad)) => resolve_seller(&mut item, &ad),
+                Ok(_) => debug!("The item {} is no longer available", listingid),
+                Err(e) => debug!("Failed to fetch the seller of {}: {}", listingid, e),
+            }
+            item
+        })
+        .buffered(crate::config::get().futures_max_buffer_size)
+        .collect()
+        .await
+}
@@ -0,0 +1,29 @@
+//! The search of the new Tori platform, `tori.fi/recommerce/forsale/search` urls are
+//! searched with it while the legacy urls keep using the `api.tori.fi` API
+
+pub mod api;
+pub mod item;
+mod models;
+pub mod parse;
+
+use super::vahti::RECOMMERCE_REGEX;
+use crate::database::Database;
+use crate::error::Error;
+
+/// Rewrites the stored legacy Tori urls, including the ones of paused vahtis, that have an
+/// equivalent search on the new platform. Returns the amount of vahtis migrated
+pub async fn migrate_legacy_urls(db: &Database) -> Result<usize, Error> {
+    let mut migrated = 0;
+    for vahti in db.fetch_all_vahtis_with_paused().await? {
+        if vahti.site_id != super::ID || RECOMMERCE_REGEX.is_match(&vahti.url) {
+            continue;
+        }
+        if let Some(url) = api::legacy_to_recommerce(&vahti.url) {
+            migrated += db.set_vahti_url(vahti.id, &url).await?;
+        }
+    }
+    if migrated > 0 {
+        info!("Migrated {} legacy Tori urls", migrated);
+    }
+    Ok(migrated)
+}
@@ -0,0 +1,156 @@
+use serde::Deserialize;
+
+use crate::vahti::VahtiItem;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct RecommerceImage {
+    url: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct RecommercePrice {
+    amount: i64,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct RecommerceCoordinates {
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FullRecommerceItem {
+    ad_id: i64,
+    heading: String,
+    #[serde(default)]
+    location: String,
+    image: Option<RecommerceImage>,
+    /// Milliseconds since the epoch
+    timestamp: i64,
+    price: Option<RecommercePrice>,
+    #[serde(default)]
+    trade_type: String,
+    canonical_url: String,
+    coordinates: Option<RecommerceCoordinates>,
+    organisation_name: Option<String>,
+}
+
+impl From<FullRecommerceItem> for VahtiItem {
+    fn from(t: FullRecommerceItem) -> VahtiItem {
+        // NOTE: The search results don't include the seller, only the name of a company.
+        // The sellers of new items are looked up from their item pages
+        let company_ad = t.organisation_name.is_some();
+        VahtiItem {
+            vahti_url: None,
+            site_id: super::super::ID,
+            deliver_to: None,
+            delivery_method: None,
+            title: t.heading,
+            url: t.canonical_url,
+            img_url: t.image.map(|i| i.url).unwrap_or_default(),
+            published: t.timestamp / 1000,
+            price: t.price.map(|p| p.amount).unwrap_or_default(),
+            seller_name: t
+                .organisation_name
+                .unwrap_or_else(|| String::from("Yksityinen")),
+            seller_id: None,
+            seller_created: None,
+            seller_ads: None,
+            company_ad,
+            previous_price: None,
+            repost: None,
+            median_price: None,
+            postal_code: None,
+            coordinates: t.coordinates.map(|c| (c.lat, c.lon)),
+            distance: None,
+            vehicle: None,
+            location: t.location,
+            ad_type: if t.trade_type.is_empty() {
+                String::from("Myydään")
+            } else {
+                t.trade_type
+            },
+            ad_id: t.ad_id,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct RecommerceAdLocation {
+    #[serde(default)]
+    postal_code: String,
+    #[serde(default)]
+    postal_name: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct RecommerceSeller {
+    user_id: i32,
+    #[serde(default)]
+    name: String,
+    member_since: Option<String>,
+    ad_count: Option<i32>,
+}
+
+/// An ad of the item page API, unlike the search results it includes the seller
+#[derive(Deserialize, Debug, Clone)]
+pub struct FullRecommerceAd {
+    ad_id: i64,
+    heading: String,
+    pub state: String,
+    price: Option<RecommercePrice>,
+    #[serde(default)]
+    trade_type: String,
+    /// Milliseconds since the epoch
+    timestamp: i64,
+    canonical_url: String,
+    #[serde(default)]
+    images: Vec<RecommerceImage>,
+    #[serde(default)]
+    location: RecommerceAdLocation,
+    coordinates: Option<RecommerceCoordinates>,
+    organisation_name: Option<String>,
+    seller: Option<RecommerceSeller>,
+}
+
+impl From<FullRecommerceAd> for VahtiItem {
+    fn from(t: FullRecommerceAd) -> VahtiItem {
+        let company_ad = t.organisation_name.is_some();
+        let seller = t.seller.unwrap_or_default();
+        VahtiItem {
+            vahti_url: None,
+            site_id: super::super::ID,
+            deliver_to: None,
+            delivery_method: None,
+            title: t.heading,
+            url: t.canonical_url,
+            img_url: t
+                .images
+                .into_iter()
+                .next()
+                .map(|i| i.url)
+                .unwrap_or_default(),
+            published: t.timestamp / 1000,
+            price: t.price.map(|p| p.amount).unwrap_or_default(),
+            seller_name: t.organisation_name.unwrap_or(seller.name),
+            seller_id: Some(seller.user_id).filter(|id| *id != 0),
+            seller_created: seller.member_since,
+            seller_ads: seller.ad_count,
+            company_ad,
+            previous_price: None,
+            repost: None,
+            median_price: None,
+            postal_code: Some(t.location.postal_code).filter(|c| !c.is_empty()),
+            coordinates: t.coordinates.map(|c| (c.lat, c.lon)),
+            distance: None,
+            vehicle: None,
+            location: t.location.postal_name,
+            ad_type: if t.trade_type.is_empty() {
+                String::from("Myydään")
+            } else {
+                t.trade_type
+            },
+            ad_id: t.ad_id,
+        }
+    }
+}
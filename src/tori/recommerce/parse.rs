@@ -0,0 +1,40 @@
+use serde_json::Value;
+
+use super::models::FullRecommerceItem;
+use crate::error::Error;
+use crate::vahti::VahtiItem;
+
+pub fn api_parse_after(json: &str, after: i64) -> Result<Vec<VahtiItem>, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    let mut items = vec![];
+    let mut past_promoted = false;
+
+    if let Some(ads) = response_json["docs"].as_array() {
+        for ad in ads {
+            let fullitem: FullRecommerceItem = serde_json::from_value(ad.to_owned())?;
+            let item = VahtiItem::from(fullitem);
+
+            // NOTE: Like on the legacy API, older promoted ads may come first
+            if item.published <= after {
+                if past_promoted {
+                    break;
+                }
+                continue;
+            } else {
+                past_promoted = true;
+            }
+
+            items.push(item);
+        }
+    }
+    debug!("Parsed {} items", items.len());
+    Ok(items)
+}
+
+/// Returns the total amount of ads matching the search
+pub fn api_result_count(json: &str) -> Result<i64, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    response_json["metadata"]["result_size"]["match_count"]
+        .as_i64()
+        .ok_or(Error::Tori)
+}
@@ -1,22 +1,29 @@
 use serde_json::Value;
 
+use super::models::FullToriItem;
 use crate::error::Error;
+use crate::models::NewSeller;
+use crate::vahti::VahtiItem;
 
-pub async fn get_seller_name_from_id(sellerid: i32) -> Result<String, Error> {
+/// Parses the seller from an ad listing of the seller's ads
+pub fn parse_seller(json: &str) -> Result<Option<NewSeller>, Error> {
+    let response_json: Value = serde_json::from_str(json)?;
+    let Some(ad) = response_json["list_ads"]
+        .as_array()
+        .and_then(|ads| ads.first())
+    else {
+        return Ok(None);
+    };
+
+    let fullitem: FullToriItem = serde_json::from_value(ad["ad"].to_owned())?;
+    Ok(VahtiItem::from(fullitem).seller())
+}
+
+pub async fn fetch_seller(sellerid: i32) -> Result<Option<NewSeller>, Error> {
     let url = format!(
         "https://api.tori.fi/api/v1.2/public/ads?account={}&lim=1",
         sellerid
     );
     let response = reqwest::get(&url).await?.text().await?;
-    let response_json: Value = serde_json::from_str(&response)?;
-    if let Some(ads) = response_json["list_ads"].as_array() {
-        if ads.is_empty() {
-            return Ok(String::from("Unknown Seller"));
-        }
-        return Ok(ads[0].as_object().unwrap()["ad"]["user"]["account"]["name"]
-            .as_str()
-            .unwrap_or("Unknown Seller")
-            .to_string());
-    }
-    Ok(String::from("Unknown Seller"))
+    parse_seller(&response)
 }
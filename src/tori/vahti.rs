@@ -3,17 +3,21 @@ use std::sync::LazyLock;
 use async_trait::async_trait;
 use regex::Regex;
 
-use crate::database::Database;
 use crate::error::Error;
-use crate::itemhistory::ItemHistoryStorage;
 use crate::models::DbVahti;
 use crate::tori::api::*;
 use crate::tori::parse::*;
+use crate::tori::recommerce;
 
 pub static TORI_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^https://(m\.|www\.)?tori\.fi/.*\?.*$").unwrap());
 
-use crate::vahti::{Vahti, VahtiItem};
+/// The search urls of the new Tori platform, the rest of the urls use the legacy API
+pub static RECOMMERCE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^https://(www\.)?tori\.fi/recommerce/forsale/search\?.*$").unwrap()
+});
+
+use crate::vahti::{SearchResult, Vahti, VahtiItem};
 
 #[derive(Debug, Clone)]
 pub struct ToriVahti {
@@ -25,58 +29,50 @@ pub struct ToriVahti {
     pub site_id: i32,
 }
 
-#[async_trait]
-impl Vahti for ToriVahti {
-    async fn update(
-        &mut self,
-        db: &Database,
-        ihs: ItemHistoryStorage,
-    ) -> Result<Vec<VahtiItem>, Error> {
-        debug!("Updating {}", self.url);
-        let ihref = ihs
-            .get(&(self.user_id, self.delivery_method))
-            .expect("bug: impossible");
-
-        let res = reqwest::get(vahti_to_api(&self.url))
-            .await?
-            .text()
-            .await?
-            .to_string();
-
-        let mut ih = ihref.lock().unwrap().clone();
-        let ret = api_parse_after(&res, self.last_updated)?
-            .iter()
-            .filter_map(|i| {
-                if !ih.contains(i.ad_id, i.site_id) {
-                    ih.add_item(i.ad_id, i.site_id, chrono::Local::now().timestamp());
-
-                    // FIXME: Somewhat sketchy
-                    let mut newi = i.clone();
-                    newi.vahti_url = Some(self.url.clone());
-                    newi.deliver_to = Some(self.user_id);
-                    newi.delivery_method = Some(self.delivery_method);
-
-                    Some(newi)
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-
-        {
-            let mut locked = ihref.lock().unwrap();
-            ih.extend(&locked);
-            *locked = ih;
+impl ToriVahti {
+    fn is_recommerce(&self) -> bool {
+        RECOMMERCE_REGEX.is_match(&self.url)
+    }
+
+    fn api_url(&self) -> String {
+        if self.is_recommerce() {
+            recommerce::api::vahti_to_api(&self.url)
+        } else {
+            vahti_to_api(&self.url)
         }
+    }
 
-        if ret.is_empty() {
-            return Ok(vec![]);
+    fn parse_after(&self, res: &str, after: i64) -> Result<Vec<VahtiItem>, Error> {
+        if self.is_recommerce() {
+            recommerce::parse::api_parse_after(res, after)
+        } else {
+            api_parse_after(res, after)
         }
+    }
 
-        db.vahti_updated(self.to_db(), ret.iter().map(|i| i.published).max())
-            .await?;
+    fn result_count(&self, res: &str) -> Result<i64, Error> {
+        if self.is_recommerce() {
+            recommerce::parse::api_result_count(res)
+        } else {
+            api_result_count(res)
+        }
+    }
+}
 
-        Ok(ret)
+#[async_trait]
+impl Vahti for ToriVahti {
+    async fn fetch(&self) -> Result<SearchResult, Error> {
+        debug!("Updating {}", self.url);
+        let res = reqwest::get(self.api_url()).await?.text().await?;
+        let mut items = self.parse_after(&res, 0)?;
+        if self.is_recommerce() {
+            items = recommerce::item::resolve_sellers(items, self.last_updated).await;
+        }
+
+        Ok(SearchResult {
+            count: self.result_count(&res)?,
+            items,
+        })
     }
 
     fn is_valid_url(&self, url: &str) -> bool {
@@ -84,7 +80,11 @@ impl Vahti for ToriVahti {
     }
 
     async fn validate_url(&self) -> Result<bool, Error> {
-        Ok(is_valid_url(&self.url).await)
+        if self.is_recommerce() {
+            recommerce::api::is_valid_url(&self.url).await
+        } else {
+            Ok(is_valid_url(&self.url).await)
+        }
     }
 
     fn from_db(v: DbVahti) -> Result<Self, Error> {
@@ -101,13 +101,13 @@ impl Vahti for ToriVahti {
     }
 
     fn to_db(&self) -> DbVahti {
-        DbVahti {
-            delivery_method: self.delivery_method,
-            id: self.id,
-            url: self.url.clone(),
-            user_id: self.user_id as i64,
-            last_updated: self.last_updated,
-            site_id: self.site_id,
-        }
+        DbVahti::new(
+            self.id,
+            self.url.clone(),
+            self.user_id as i64,
+            self.site_id,
+            self.delivery_method,
+            self.last_updated,
+        )
     }
 }
@@ -1,20 +1,35 @@
 use std::sync::{Arc, LazyLock, Mutex};
 
 use async_trait::async_trait;
+use chrono::{Local, TimeZone};
 use futures::stream::{self, StreamExt};
 use itertools::Itertools;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
+use crate::blockrule::BlockRule;
+use crate::config::VahtiConfig;
 use crate::database::Database;
 use crate::delivery::perform_delivery;
 use crate::error::Error;
+use crate::geo::apply_distances;
+use crate::history::record_deliveries;
 #[cfg(feature = "huutonet")]
 use crate::huutonet::vahti::HuutonetVahti;
 use crate::itemhistory::{ItemHistory, ItemHistoryStorage};
-use crate::models::DbVahti;
+use crate::models::{DbVahti, NewPendingDelivery};
+#[cfg(feature = "muusikoiden")]
+use crate::muusikoiden::vahti::MuusikoidenVahti;
+#[cfg(feature = "nettiauto")]
+use crate::nettiauto::vahti::NettiautoVahti;
+use crate::pricedrop::check_price_drops;
+use crate::repost::{check_reposts, Repost, RepostMode};
+use crate::search::query::{canonical_url, KeywordQuery};
+use crate::search::vahti::SearchVahti;
+use crate::stats::{mark_bargains, record_matches};
 #[cfg(feature = "tori")]
 use crate::tori::vahti::ToriVahti;
-use crate::Torimies;
+use crate::{metrics, Torimies};
 
 static SITES: LazyLock<Vec<(&LazyLock<Regex>, i32)>> = LazyLock::new(|| {
     vec![
@@ -22,6 +37,16 @@ static SITES: LazyLock<Vec<(&LazyLock<Regex>, i32)>> = LazyLock::new(|| {
         (&crate::tori::vahti::TORI_REGEX, crate::tori::ID),
         #[cfg(feature = "huutonet")]
         (&crate::huutonet::vahti::HUUTONET_REGEX, crate::huutonet::ID),
+        #[cfg(feature = "nettiauto")]
+        (
+            &crate::nettiauto::vahti::NETTIAUTO_REGEX,
+            crate::nettiauto::ID,
+        ),
+        #[cfg(feature = "muusikoiden")]
+        (
+            &crate::muusikoiden::vahti::MUUSIKOIDEN_REGEX,
+            crate::muusikoiden::ID,
+        ),
     ]
 });
 
@@ -32,18 +57,63 @@ pub trait Vahti
 where
     Self: Sized + Send + Sync,
 {
-    async fn update(
-        &mut self,
-        db: &Database,
-        ihs: ItemHistoryStorage,
-    ) -> Result<Vec<VahtiItem>, Error>;
+    /// Fetches and parses the search of the Vahti, the new items are picked
+    /// and post-processed by `Torimies::update_vahtis`
+    async fn fetch(&self) -> Result<SearchResult, Error>;
     async fn validate_url(&self) -> Result<bool, Error>;
     fn is_valid_url(&self, url: &str) -> bool;
     fn from_db(v: DbVahti) -> Result<Self, Error>;
+    // NOTE: The site modules only keep track of the fields needed for updating,
+    // the lifecycle of a Vahti is handled elsewhere and paused Vahtis are never updated
     fn to_db(&self) -> DbVahti;
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl DbVahti {
+    /// An active Vahti with the default lifecycle and options
+    pub fn new(
+        id: i32,
+        url: String,
+        user_id: i64,
+        site_id: i32,
+        delivery_method: i32,
+        last_updated: i64,
+    ) -> Self {
+        Self {
+            id,
+            url,
+            user_id,
+            last_updated,
+            site_id,
+            delivery_method,
+            paused: false,
+            resume_at: None,
+            expires_at: None,
+            empty_since: None,
+            reminded: false,
+            created: 0,
+            price_drops: false,
+            reposts: RepostMode::default().into(),
+            max_distance: None,
+        }
+    }
+}
+
+/// The results of a single search of a Vahti
+pub struct SearchResult {
+    /// The total amount of items matching the search
+    pub count: i64,
+    /// The items on the first page of the results
+    pub items: Vec<VahtiItem>,
+}
+
+/// The details of vehicle ads, e.g. on Nettiauto
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vehicle {
+    pub year: Option<i32>,
+    pub mileage: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct VahtiItem {
     pub deliver_to: Option<u64>,
     pub delivery_method: Option<i32>,
@@ -55,23 +125,96 @@ pub struct VahtiItem {
     pub published: i64,
     pub price: i64,
     pub seller_name: String,
-    pub seller_id: i32,
+    /// `None` when the site does not tell who the seller is
+    pub seller_id: Option<i32>,
+    #[serde(default)]
+    pub seller_created: Option<String>,
+    #[serde(default)]
+    pub seller_ads: Option<i32>,
+    #[serde(default)]
+    pub company_ad: bool,
+    /// The previously seen price when the item is delivered because its price dropped
+    #[serde(default)]
+    pub previous_price: Option<i64>,
+    /// The previous post of the item when the seller has reposted it
+    #[serde(default)]
+    pub repost: Option<Repost>,
+    /// The recent median price of the Vahti when the item is priced well below it
+    #[serde(default)]
+    pub median_price: Option<i64>,
+    #[serde(default)]
+    pub postal_code: Option<String>,
+    /// The (latitude, longitude) of the item when the site tells it instead of a postal code
+    #[serde(default)]
+    pub coordinates: Option<(f64, f64)>,
+    /// The distance from the home of the recipient in kilometers, if both are known
+    #[serde(default)]
+    pub distance: Option<i64>,
+    /// The year and the mileage of vehicles
+    #[serde(default)]
+    pub vehicle: Option<Vehicle>,
     pub location: String,
     pub ad_type: String,
     pub ad_id: i64,
 }
 
+/// Checks the per-user limits shared by all the linked identities of the user
+pub async fn check_quota(
+    db: &Database,
+    limits: &VahtiConfig,
+    userid: u64,
+    delivery_method: i32,
+) -> Result<(), Error> {
+    let linked = db
+        .fetch_linked_identities(userid as i64, delivery_method)
+        .await?;
+
+    let mut vahdit = vec![];
+    for (uid, dm) in &linked {
+        vahdit.extend(
+            db.fetch_vahti_entries_by_user_id(*uid)
+                .await?
+                .into_iter()
+                .filter(|v| v.delivery_method == *dm),
+        );
+    }
+
+    if let Some(max) = limits.max_per_user {
+        if vahdit.len() >= max {
+            return Err(Error::VahtiLimit(max));
+        }
+    }
+
+    if let Some(latest) = vahdit.iter().map(|v| v.created).max() {
+        let wait = latest + limits.min_interval - chrono::Local::now().timestamp();
+        if wait > 0 {
+            return Err(Error::VahtiRateLimit(wait));
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn new_vahti(
     db: Database,
     url: &str,
     userid: u64,
     delivery_method: i32,
+    expires_in_days: Option<u32>,
 ) -> Result<String, Error> {
-    let Some(site_id) = SITES
-        .iter()
-        .find(|(r, _)| r.is_match(url))
-        .map(|(_, sid)| *sid)
-    else {
+    if db.is_banned(userid as i64, delivery_method).await? {
+        return Err(Error::Banned);
+    }
+
+    let url = &canonical_url(url);
+    let site_id = match KeywordQuery::parse(url) {
+        Some(_) => Some(crate::search::ID),
+        None => SITES
+            .iter()
+            .find(|(r, _)| r.is_match(url))
+            .map(|(_, sid)| *sid),
+    };
+    let Some(site_id) = site_id.filter(|sid| crate::config::get().site_enabled(*sid)) else {
         return Err(Error::UnknownUrl(url.to_string()));
     };
 
@@ -80,8 +223,45 @@ pub async fn new_vahti(
         return Err(Error::VahtiExists);
     }
 
+    // If a linked account already has this Vahti, deliver its items here as well
+    // instead of creating a second Vahti that would be fetched separately
+    let linked = db
+        .fetch_linked_identities(userid as i64, delivery_method)
+        .await?;
+    if let Some(v) = db
+        .fetch_vahti_entries_by_url(url)
+        .await?
+        .into_iter()
+        .find(|v| linked.contains(&(v.user_id, v.delivery_method)))
+    {
+        if db
+            .fetch_vahti_entries_by_destination(userid as i64, delivery_method)
+            .await?
+            .iter()
+            .any(|d| d.id == v.id)
+        {
+            return Err(Error::VahtiExists);
+        }
+
+        db.add_vahti_destination(v.id, userid as i64, delivery_method)
+            .await?;
+        return Ok(String::from(
+            "Vahti of a linked account will now be delivered here as well",
+        ));
+    }
+
+    if !crate::admin::is_admin(userid, delivery_method) {
+        check_quota(&db, &crate::config::get().vahti, userid, delivery_method).await?;
+    }
+
     match db
-        .add_vahti_entry(url, userid as i64, site_id, delivery_method)
+        .add_vahti_entry(
+            url,
+            userid as i64,
+            site_id,
+            delivery_method,
+            expires_in_days.map(|d| chrono::Local::now().timestamp() + i64::from(d) * 86400),
+        )
         .await
     {
         Ok(_) => Ok(String::from("Vahti added succesfully")),
@@ -95,6 +275,18 @@ pub async fn remove_vahti(
     userid: u64,
     delivery_method: i32,
 ) -> Result<String, Error> {
+    let url = &canonical_url(url);
+    if let Some(v) = db
+        .fetch_vahti_entries_by_destination(userid as i64, delivery_method)
+        .await?
+        .into_iter()
+        .find(|v| v.url == *url)
+    {
+        db.remove_vahti_destination(v.id, userid as i64, delivery_method)
+            .await?;
+        return Ok("Vahti removed!".to_string());
+    }
+
     if db.fetch_vahti(url, userid as i64).await.is_err() {
         info!("Not removing a nonexistant vahti!");
         return Ok(
@@ -110,13 +302,423 @@ pub async fn remove_vahti(
     }
 }
 
+pub async fn pause_vahti(
+    db: Database,
+    url: &str,
+    userid: u64,
+    delivery_method: i32,
+    days: Option<u32>,
+) -> Result<String, Error> {
+    let url = &canonical_url(url);
+    if db.fetch_vahti(url, userid as i64).await.is_err() {
+        info!("Not pausing a nonexistant vahti!");
+        return Ok(
+            "A Vahti is not defined with that url. Make sure the url is correct".to_string(),
+        );
+    }
+
+    let resume_at = days.map(|d| chrono::Local::now().timestamp() + i64::from(d) * 86400);
+    db.pause_vahti(url, userid as i64, delivery_method, resume_at)
+        .await?;
+
+    Ok(match days {
+        Some(d) => format!("Vahti paused for {} days!", d),
+        None => "Vahti paused!".to_string(),
+    })
+}
+
+pub async fn resume_vahti(
+    db: Database,
+    url: &str,
+    userid: u64,
+    delivery_method: i32,
+    catch_up: bool,
+) -> Result<String, Error> {
+    let url = &canonical_url(url);
+    match db.fetch_vahti(url, userid as i64).await {
+        Ok(v) if v.paused => {
+            // Resuming an expired Vahti removes its expiry
+            if v.expires_at
+                .is_some_and(|e| e <= chrono::Local::now().timestamp())
+            {
+                db.set_vahti_expiry(v.id, None).await?;
+            }
+        }
+        Ok(_) => return Ok("The Vahti is not paused".to_string()),
+        Err(_) => {
+            info!("Not resuming a nonexistant vahti!");
+            return Ok(
+                "A Vahti is not defined with that url. Make sure the url is correct".to_string(),
+            );
+        }
+    }
+
+    db.resume_vahti(url, userid as i64, delivery_method, catch_up)
+        .await?;
+    Ok("Vahti resumed!".to_string())
+}
+
+/// Enables or disables the price drop alerts of the Vahti. When enabled,
+/// the already delivered items are re-checked and delivered again if their price drops
+pub async fn set_price_drops(
+    db: Database,
+    url: &str,
+    userid: u64,
+    delivery_method: i32,
+    enabled: bool,
+) -> Result<String, Error> {
+    let url = &canonical_url(url);
+    if db
+        .set_vahti_price_drops(url, userid as i64, delivery_method, enabled)
+        .await?
+        == 0
+    {
+        return Ok(
+            "A Vahti is not defined with that url. Make sure the url is correct".to_string(),
+        );
+    }
+
+    Ok(if enabled {
+        "Price drop alerts enabled!".to_string()
+    } else {
+        "Price drop alerts disabled!".to_string()
+    })
+}
+
+/// Sets the maximum distance of the items of the Vahti from the home of the user,
+/// `None` removes the limit
+pub async fn set_max_distance(
+    db: Database,
+    url: &str,
+    userid: u64,
+    delivery_method: i32,
+    distance: Option<i32>,
+) -> Result<String, Error> {
+    let url = &canonical_url(url);
+    if db
+        .set_vahti_max_distance(url, userid as i64, delivery_method, distance)
+        .await?
+        == 0
+    {
+        return Ok(
+            "A Vahti is not defined with that url. Make sure the url is correct".to_string(),
+        );
+    }
+
+    let Some(distance) = distance else {
+        return Ok("Distance limit removed!".to_string());
+    };
+    let mut msg = format!("Only items within {} km are delivered!", distance);
+    if db
+        .fetch_home_location(userid as i64, delivery_method)
+        .await?
+        .is_none()
+    {
+        msg.push_str(" Set your home postal code with /koti for the limit to take effect");
+    }
+    Ok(msg)
+}
+
+/// Sets how the Vahti handles the ads its sellers have deleted and posted again
+pub async fn set_repost_mode(
+    db: Database,
+    url: &str,
+    userid: u64,
+    delivery_method: i32,
+    mode: RepostMode,
+) -> Result<String, Error> {
+    let url = &canonical_url(url);
+    if db
+        .set_vahti_reposts(url, userid as i64, delivery_method, mode.into())
+        .await?
+        == 0
+    {
+        return Ok(
+            "A Vahti is not defined with that url. Make sure the url is correct".to_string(),
+        );
+    }
+
+    Ok(match mode {
+        RepostMode::Off => "Reposts are delivered as new items".to_string(),
+        RepostMode::Label => {
+            "Reposts are delivered with a note about the previous post".to_string()
+        }
+        RepostMode::Suppress => "Reposts are no longer delivered".to_string(),
+    })
+}
+
+/// Extends the expiry of the Vahti by the configured `vahti.extend_days`
+pub async fn extend_vahti(
+    db: Database,
+    vahti_id: i32,
+    userid: u64,
+    delivery_method: i32,
+) -> Result<String, Error> {
+    let v = db.fetch_vahti_by_id(vahti_id).await?;
+    let linked = db
+        .fetch_linked_identities(userid as i64, delivery_method)
+        .await?;
+
+    if !linked.contains(&(v.user_id, v.delivery_method)) {
+        return Ok(String::from("A Vahti is not defined with that id"));
+    }
+
+    let Some(expires_at) = v.expires_at else {
+        return Ok(String::from("The Vahti does not expire"));
+    };
+
+    let expires_at = expires_at.max(chrono::Local::now().timestamp())
+        + i64::from(crate::config::get().vahti.extend_days) * 86400;
+    db.set_vahti_expiry(v.id, Some(expires_at)).await?;
+
+    Ok(format!(
+        "Vahti extended until {}",
+        Local
+            .timestamp_opt(expires_at, 0)
+            .unwrap()
+            .format("%d/%m/%Y")
+    ))
+}
+
+/// Moves a Vahti with the given url from one of the linked identities
+/// of the user to the given delivery method
+pub async fn move_vahti(
+    db: Database,
+    url: &str,
+    userid: u64,
+    delivery_method: i32,
+) -> Result<String, Error> {
+    let url = &canonical_url(url);
+    let to = (userid as i64, delivery_method);
+    let linked = db.fetch_linked_identities(to.0, to.1).await?;
+    let vahdit = db.fetch_vahti_entries_by_url(url).await?;
+
+    if vahdit.iter().any(|v| (v.user_id, v.delivery_method) == to) {
+        return Err(Error::VahtiExists);
+    }
+
+    let Some(v) = vahdit
+        .iter()
+        .find(|v| linked.contains(&(v.user_id, v.delivery_method)))
+    else {
+        return Ok(String::from(
+            "None of your linked accounts have a Vahti with that url. Link your accounts first",
+        ));
+    };
+
+    db.move_vahti_entry(url, (v.user_id, v.delivery_method), to)
+        .await?;
+    db.remove_vahti_destination(v.id, to.0, to.1).await?;
+    Ok(String::from("Vahti moved!"))
+}
+
+async fn fetch<V: Vahti>(v: &DbVahti) -> Result<SearchResult, Error> {
+    V::from_db(v.clone())?.fetch().await
+}
+
+/// How many seconds the publication times of the items of the site are rounded down to
+fn published_precision(site_id: i32) -> i64 {
+    match site_id {
+        #[cfg(feature = "muusikoiden")]
+        crate::muusikoiden::ID => crate::muusikoiden::PUBLISHED_PRECISION,
+        _ => 1,
+    }
+}
+
+/// Picks the items not yet delivered by the Vahti from the search results and runs
+/// them through the repost, statistics and price drop checks of the Vahti.
+/// Also returns the publication time of the newest item published after the `last_updated`
+/// of the Vahti, whether or not it is delivered, for the next search to continue from
+pub async fn process_results(
+    db: &Database,
+    ihs: &ItemHistoryStorage,
+    v: &DbVahti,
+    results: SearchResult,
+) -> Result<(Option<i64>, Vec<VahtiItem>), Error> {
+    db.vahti_searched(v.id, results.count > 0, chrono::Local::now().timestamp())
+        .await?;
+
+    let recipient = |mut i: VahtiItem| {
+        i.vahti_url = Some(v.url.clone());
+        i.deliver_to = Some(v.user_id as u64);
+        i.delivery_method = Some(v.delivery_method);
+        i
+    };
+
+    let items = results
+        .items
+        .iter()
+        .filter(|i| i.published > v.last_updated)
+        .cloned()
+        .collect::<Vec<_>>();
+    // NOTE: The items published later during the same rounded down time must pass the filter
+    // on the next search too, the ItemHistory keeps the already seen ones from being redelivered
+    let newest = items
+        .iter()
+        .map(|i| i.published + 1 - published_precision(i.site_id))
+        .max();
+
+    let ret = {
+        let ihref = ihs
+            .get(&(v.user_id as u64, v.delivery_method))
+            .expect("bug: impossible");
+        let mut ih = ihref.lock().unwrap().clone();
+        let ret = items
+            .iter()
+            .filter_map(|i| {
+                if !ih.contains(i.ad_id, i.site_id) {
+                    ih.add_item(i.ad_id, i.site_id, chrono::Local::now().timestamp());
+                    Some(recipient(i.clone()))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut locked = ihref.lock().unwrap();
+        ih.extend(&locked);
+        *locked = ih;
+        ret
+    };
+
+    let mut ret = check_reposts(db, v.id, RepostMode::from(v.reposts), ret).await?;
+    mark_bargains(db, v.id, &mut ret).await?;
+    record_matches(db, v.id, &items).await?;
+
+    if v.price_drops {
+        let drops = check_price_drops(db, v.id, &ret, results.items).await?;
+        ret.extend(drops.into_iter().map(recipient));
+    }
+
+    Ok((newest, ret))
+}
+
+/// Copies the items of the Vahti to its additional destinations and applies the home
+/// location of each recipient. The price drops and the distance limit of the Vahti
+/// are settings of its owner, so they only apply to the items of the owner
+pub async fn address_items(
+    db: &Database,
+    v: &DbVahti,
+    destinations: &[(i64, i32)],
+    items: Vec<VahtiItem>,
+) -> Result<Vec<VahtiItem>, Error> {
+    let mut ret = apply_distances(
+        db,
+        v.user_id as u64,
+        v.delivery_method,
+        v.max_distance,
+        items.clone(),
+    )
+    .await?;
+
+    for (uid, dm) in destinations {
+        let copies = items
+            .iter()
+            .filter(|i| i.previous_price.is_none())
+            .cloned()
+            .map(|mut i| {
+                i.deliver_to = Some(*uid as u64);
+                i.delivery_method = Some(*dm);
+                i
+            })
+            .collect();
+        ret.extend(apply_distances(db, *uid as u64, *dm, None, copies).await?);
+    }
+
+    Ok(ret)
+}
+
+/// Completes once the shutdown timeout has passed after a shutdown was started
+async fn shutdown_deadline(shutdown: tokio_util::sync::CancellationToken) {
+    shutdown.cancelled().await;
+    tokio::time::sleep(std::time::Duration::from_secs(
+        crate::config::get().shutdown_timeout,
+    ))
+    .await;
+}
+
+/// Persists the items of an unfinished delivery to be delivered after a restart
+pub async fn persist_pending(db: &Database, items: Vec<VahtiItem>) {
+    let deliveries = items
+        .into_iter()
+        .filter_map(|i| {
+            Some(NewPendingDelivery {
+                deliver_to: i.deliver_to? as i64,
+                delivery_method: i.delivery_method?,
+                item: serde_json::to_string(&i).ok()?,
+                created: chrono::Local::now().timestamp(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if let Err(e) = db.add_pending_deliveries(&deliveries).await {
+        error!("Failed to persist pending deliveries: {}", e);
+    }
+}
+
 impl Torimies {
     pub async fn update_all_vahtis(&mut self) -> Result<(), Error> {
-        let vahtis = self.database.fetch_all_vahtis().await?;
+        self.update_vahti_lifecycles().await?;
+        if let Err(e) = self.check_watched_items().await {
+            error!("Failed to check the watched items: {}", e);
+        }
+        self.deliver_pending().await?;
+        let config = crate::config::get();
+        let vahtis = self
+            .database
+            .fetch_all_vahtis()
+            .await?
+            .into_iter()
+            .filter(|v| config.site_enabled(v.site_id))
+            .collect();
         self.update_vahtis(vahtis).await?;
         Ok(())
     }
 
+    /// Delivers the items that were left undelivered during the previous shutdown.
+    /// The items that fail to be delivered are kept for the next round,
+    /// unless their recipient can no longer be reached
+    pub async fn deliver_pending(&self) -> Result<(), Error> {
+        let pending = self.database.take_pending_deliveries().await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        info!("Delivering {} pending items", pending.len());
+        let groups = pending
+            .into_iter()
+            .filter_map(|p| match serde_json::from_str::<VahtiItem>(&p.item) {
+                Ok(i) => Some(i),
+                Err(e) => {
+                    error!(
+                        "Dropping an unreadable pending item of the user {}: {}: {}",
+                        p.deliver_to, e, p.item
+                    );
+                    None
+                }
+            })
+            .into_group_map_by(|i| (i.deliver_to, i.delivery_method));
+
+        for (_, items) in groups {
+            match perform_delivery(self.delivery.clone(), items.clone()).await {
+                Ok(()) => record_deliveries(&self.database, &items).await,
+                Err(Error::RecipientUnreachable(uid)) => {
+                    warn!(
+                        "Dropping {} pending items of the unreachable recipient {}",
+                        items.len(),
+                        uid
+                    );
+                }
+                Err(e) => {
+                    error!("Failed to deliver pending items, retrying later: {}", e);
+                    persist_pending(&self.database, items).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn update_vahtis(&mut self, vahtis: Vec<DbVahti>) -> Result<(), Error> {
         info!("Updating {} vahtis", vahtis.len());
         let start = std::time::Instant::now();
@@ -138,36 +740,89 @@ impl Torimies {
         let db = self.database.clone();
         let dm = self.delivery.clone();
 
+        // NOTE: If db fails, items are only delivered to the owners of the Vahtis
+        let destinations = db.fetch_all_vahti_destinations().await.unwrap_or_default();
+        let destinations = &destinations;
+
+        // NOTE: No new fetches are started after shutdown, the ongoing ones are finished
         let items = stream::iter(vahtis.iter().cloned())
+            .take_until(self.shutdown.clone().cancelled_owned())
             .map(|v| (v, ihs.clone(), db.clone()))
-            .map(async move |(v, ihs, db)| match v.site_id {
-                #[cfg(feature = "tori")]
-                crate::tori::ID => {
-                    let Ok(mut tv) = ToriVahti::from_db(v) else {
-                        return vec![];
-                    };
+            .map(async move |(v, ihs, db)| {
+                let id = v.id;
+                let site = metrics::site_name(v.site_id);
+                let timer = metrics::FETCH_DURATION
+                    .with_label_values(&[site])
+                    .start_timer();
+                let res = match v.site_id {
+                    crate::search::ID => fetch::<SearchVahti>(&v).await,
+                    #[cfg(feature = "tori")]
+                    crate::tori::ID => fetch::<ToriVahti>(&v).await,
+                    #[cfg(feature = "huutonet")]
+                    crate::huutonet::ID => fetch::<HuutonetVahti>(&v).await,
+                    #[cfg(feature = "nettiauto")]
+                    crate::nettiauto::ID => fetch::<NettiautoVahti>(&v).await,
+                    #[cfg(feature = "muusikoiden")]
+                    crate::muusikoiden::ID => fetch::<MuusikoidenVahti>(&v).await,
+                    i => Err(Error::UnsupportedSite(i)),
+                };
+                timer.observe_duration();
 
-                    tv.update(&db, ihs.clone()).await.unwrap_or_default()
-                }
-                #[cfg(feature = "huutonet")]
-                crate::huutonet::ID => {
-                    if let Ok(mut hv) = HuutonetVahti::from_db(v) {
-                        hv.update(&db, ihs.clone()).await.unwrap_or_default()
-                    } else {
-                        vec![]
+                let res = match res {
+                    Ok(results) => process_results(&db, &ihs, &v, results).await,
+                    Err(e) => Err(e),
+                };
+
+                let res = match res {
+                    Ok((newest, items)) => {
+                        metrics::ITEMS_PARSED
+                            .with_label_values(&[site])
+                            .inc_by(items.len() as u64);
+                        let dests = destinations.get(&id).map_or(&[][..], |d| &d[..]);
+                        address_items(&db, &v, dests, items)
+                            .await
+                            .map(|items| (newest, items))
+                    }
+                    Err(e) => Err(e),
+                };
+
+                match res {
+                    Ok((newest, items)) => (id, newest, items),
+                    Err(e) => {
+                        debug!("Failed to update Vahti {}: {}", id, e);
+                        metrics::FETCH_ERRORS.with_label_values(&[site]).inc();
+                        (id, None, vec![])
                     }
                 }
-                i => panic!("Unsupported site_id {}", i),
             })
-            .buffer_unordered(*crate::FUTURES_MAX_BUFFER_SIZE)
+            .buffer_unordered(crate::config::get().futures_max_buffer_size)
             .collect::<Vec<_>>()
             .await;
 
         info!("Recieving items took {}ms", start.elapsed().as_millis());
 
+        // NOTE: The timestamps of the whole round are written in a single transaction.
+        // If that fails, the items are still delivered and the ItemHistory keeps them
+        // from being delivered again on the next round
+        let updates = items
+            .iter()
+            .filter_map(|(id, newest, _)| Some((*id, (*newest)?)))
+            .collect();
+        if let Err(e) = db.vahtis_updated(updates).await {
+            error!("Failed to update the Vahti timestamps: {}", e);
+        }
+
+        let mut items = items
+            .into_iter()
+            .flat_map(|(_, _, items)| items)
+            .sorted_by_key(|v| (v.deliver_to, v.delivery_method))
+            .collect::<Vec<_>>();
+
+        crate::seller::cache_sellers(&db, &items).await;
+        crate::seller::fill_seller_info(&db, &mut items).await;
+
         let groups: Vec<Vec<VahtiItem>> = items
             .iter()
-            .flatten()
             .group_by(|v| {
                 (
                     v.deliver_to.expect("bug: impossible"),
@@ -188,24 +843,102 @@ impl Torimies {
                     let mut v = v.clone();
 
                     if let Some(fst) = v.first() {
+                        let uid = fst.deliver_to.expect("bug: impossible") as i64;
+                        let dm = fst.delivery_method.expect("bug: impossible");
+                        let before = v.len();
+
                         // NOTE: If db fails, blacklisted sellers are not filtered out
-                        if let Ok(bl) = db
-                            .fetch_user_blacklist(fst.deliver_to.expect("bug: impossible") as i64)
-                            .await
-                        {
-                            v.retain(|i| !bl.contains(&(i.seller_id, i.site_id)));
+                        if let Ok(bl) = db.fetch_user_blacklist(uid, dm).await {
+                            v.retain(|i| {
+                                !i.seller_id.is_some_and(|s| bl.contains(&(s, i.site_id)))
+                            });
+                        }
+
+                        // NOTE: If db fails, the block rules are not applied
+                        if let Ok(rules) = db.fetch_user_block_rules(uid, dm).await {
+                            let rules = rules
+                                .iter()
+                                .filter_map(BlockRule::from_db)
+                                .collect::<Vec<_>>();
+                            v.retain(|i| !rules.iter().any(|r| r.matches(i)));
                         }
+
+                        metrics::ITEMS_BLACKLISTED.inc_by((before - v.len()) as u64);
                     }
                     v
                 })
                 .map(|v| (v, dm.clone())),
         )
-        .then(|(v, dm)| async move { perform_delivery(dm.clone(), v.await) })
-        .for_each_concurrent(*crate::FUTURES_MAX_BUFFER_SIZE, |d| async move {
-            d.await.ok();
+        .then(|(v, dm)| async move {
+            let v = v.await;
+            let recipient = v
+                .first()
+                .map(|i| (i.deliver_to.unwrap(), i.delivery_method.unwrap()));
+            let count = v.len();
+            (recipient, count, v.clone(), perform_delivery(dm.clone(), v))
         })
+        .for_each_concurrent(
+            crate::config::get().futures_max_buffer_size,
+            |(recipient, count, items, d)| {
+                let db = db.clone();
+                let shutdown = self.shutdown.clone();
+                async move {
+                    let Some((uid, dm)) = recipient else {
+                        return;
+                    };
+
+                    let res = tokio::select! {
+                        res = d => res,
+                        _ = shutdown_deadline(shutdown) => {
+                            warn!("Delivery to {} missed the shutdown deadline", uid);
+                            persist_pending(&db, items).await;
+                            return;
+                        }
+                    };
+
+                    let method = metrics::delivery_name(dm);
+                    if res.is_ok() {
+                        record_deliveries(&db, &items).await;
+                        metrics::DELIVERIES
+                            .with_label_values(&[method, "success"])
+                            .inc();
+                        metrics::ITEMS_DELIVERED
+                            .with_label_values(&[method])
+                            .inc_by(count as u64);
+                    } else {
+                        metrics::DELIVERIES
+                            .with_label_values(&[method, "failure"])
+                            .inc();
+                    }
+
+                    if let Err(Error::RecipientUnreachable(_)) = res {
+                        warn!(
+                            "Recipient {} is unreachable, pausing their Vahtis and destinations",
+                            uid
+                        );
+                        db.pause_vahtis_of_recipient(uid as i64, dm).await.ok();
+                        db.remove_destinations_of_recipient(uid as i64, dm)
+                            .await
+                            .ok();
+                    }
+                }
+            },
+        )
         .await;
 
+        metrics::ACTIVE_VAHTIS.reset();
+        for (site_id, count) in vahtis.iter().counts_by(|v| v.site_id) {
+            metrics::ACTIVE_VAHTIS
+                .with_label_values(&[metrics::site_name(site_id)])
+                .set(count as i64);
+        }
+        metrics::ITEM_HISTORY_SIZE.set(
+            ihs.iter()
+                .map(|ih| ih.value().lock().unwrap().len())
+                .sum::<usize>() as i64,
+        );
+        metrics::UPDATE_DURATION.observe(start.elapsed().as_secs_f64());
+
         info!("Update took {}ms", start.elapsed().as_millis());
         Ok(())
     }
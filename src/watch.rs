@@ -0,0 +1,268 @@
+use futures::stream::{self, StreamExt};
+
+use crate::database::Database;
+use crate::delivery::{perform_notification, Notification, NotificationAction};
+use crate::error::Error;
+use crate::models::{NewWatchedItem, WatchedItem};
+use crate::vahti::VahtiItem;
+use crate::Torimies;
+
+/// The prefix of the Discord custom_ids and Telegram callback data of the watch buttons
+pub const WATCH_ITEM_PREFIX: &str = "watch_item_";
+
+/// Marks the watch buttons of the listings of the new Tori platform,
+/// which are looked up with a different API than the legacy listings
+const RECOMMERCE_SUFFIX: &str = "_r";
+
+/// The current state of a listing on its site
+#[derive(Clone, Debug, PartialEq)]
+pub enum ItemStatus {
+    Available(Box<VahtiItem>),
+    Sold,
+    Removed,
+}
+
+impl VahtiItem {
+    /// The public id of the listing, the trailing number in its url.
+    /// Unlike `ad_id`, it can be used to look the listing up on the site
+    pub fn listing_id(&self) -> Option<i64> {
+        let segment = self.url.split(['?', '#']).next()?.rsplit('/').next()?;
+        let digits = segment
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(segment, |end| &segment[..end]);
+        digits.parse().ok()
+    }
+
+    /// The identifier of the button watching the item, if the item can be watched
+    pub fn watch_action_id(&self) -> Option<String> {
+        if !is_watchable(self.site_id) {
+            return None;
+        }
+        let suffix = if is_recommerce_listing(&self.url) {
+            RECOMMERCE_SUFFIX
+        } else {
+            ""
+        };
+        self.listing_id()
+            .map(|l| format!("{}{}_{}{}", WATCH_ITEM_PREFIX, self.site_id, l, suffix))
+    }
+}
+
+/// Whether the listing is on the new Tori platform, chosen by the shape of its url
+/// like the search backend of the Vahtis
+pub fn is_recommerce_listing(url: &str) -> bool {
+    #[cfg(feature = "tori")]
+    {
+        crate::tori::recommerce::item::RECOMMERCE_ITEM_REGEX.is_match(url)
+    }
+    #[cfg(not(feature = "tori"))]
+    {
+        let _ = url;
+        false
+    }
+}
+
+/// Parses the (site_id, listing_id, is_recommerce_listing) of a watch button identifier
+pub fn parse_watch_action_id(id: &str) -> Option<(i32, i64, bool)> {
+    let (site, listing) = id.strip_prefix(WATCH_ITEM_PREFIX)?.split_once('_')?;
+    let (listing, recommerce) = match listing.strip_suffix(RECOMMERCE_SUFFIX) {
+        Some(listing) => (listing, true),
+        None => (listing, false),
+    };
+    Some((site.parse().ok()?, listing.parse().ok()?, recommerce))
+}
+
+/// Whether the listings of the site can be looked up with `fetch_listing`
+fn is_watchable(siteid: i32) -> bool {
+    match siteid {
+        #[cfg(feature = "tori")]
+        crate::tori::ID => true,
+        #[cfg(feature = "huutonet")]
+        crate::huutonet::ID => true,
+        #[cfg(feature = "nettiauto")]
+        crate::nettiauto::ID => true,
+        _ => false,
+    }
+}
+
+async fn fetch_listing(siteid: i32, listingid: i64, recommerce: bool) -> Result<ItemStatus, Error> {
+    match siteid {
+        #[cfg(feature = "tori")]
+        crate::tori::ID if recommerce => crate::tori::recommerce::item::fetch_item(listingid).await,
+        #[cfg(feature = "tori")]
+        crate::tori::ID => crate::tori::item::fetch_item(listingid).await,
+        #[cfg(feature = "huutonet")]
+        crate::huutonet::ID => crate::huutonet::item::fetch_item(listingid).await,
+        #[cfg(feature = "nettiauto")]
+        crate::nettiauto::ID => crate::nettiauto::item::fetch_item(listingid).await,
+        _ => Err(Error::InvalidItem),
+    }
+}
+
+/// Describes how the title and the price of the watched item have changed, if at all
+pub fn change_message(watched: &WatchedItem, current: &VahtiItem) -> Option<String> {
+    let mut changes = vec![];
+    if current.title != watched.title {
+        changes.push(format!(
+            "otsikko \"{}\" → \"{}\"",
+            watched.title, current.title
+        ));
+    }
+    if current.price != watched.price {
+        changes.push(format!("hinta {} € → {} €", watched.price, current.price));
+    }
+
+    (!changes.is_empty()).then(|| {
+        format!(
+            "Seuraamasi ilmoitus {} muuttui: {}",
+            watched.url,
+            changes.join(", ")
+        )
+    })
+}
+
+pub async fn watch_item(
+    db: &Database,
+    userid: u64,
+    delivery_method: i32,
+    siteid: i32,
+    listingid: i64,
+    recommerce: bool,
+) -> Result<String, Error> {
+    let item = match fetch_listing(siteid, listingid, recommerce).await? {
+        ItemStatus::Available(item) => item,
+        ItemStatus::Sold | ItemStatus::Removed => {
+            return Ok(String::from("The item is no longer available"));
+        }
+    };
+
+    let now = chrono::Local::now().timestamp();
+    let added = db
+        .add_watched_item(NewWatchedItem {
+            user_id: userid as i64,
+            delivery_method,
+            site_id: siteid,
+            listing_id: listingid,
+            title: item.title.clone(),
+            url: item.url.clone(),
+            price: item.price,
+            created: now,
+            checked: now,
+        })
+        .await?;
+
+    Ok(if added == 0 {
+        format!("You are already watching {}", item.title)
+    } else {
+        format!(
+            "Watching {}, you will be notified when it is sold, removed or changed",
+            item.title
+        )
+    })
+}
+
+pub async fn unwatch_item(
+    db: &Database,
+    watchid: i32,
+    userid: u64,
+    delivery_method: i32,
+) -> Result<String, Error> {
+    if db
+        .remove_watched_item(watchid, userid as i64, delivery_method)
+        .await?
+        == 0
+    {
+        return Ok(String::from("You are not watching that item"));
+    }
+    Ok(String::from("No longer watching the item"))
+}
+
+impl Torimies {
+    /// Checks the watched items that are due and notifies their watchers about
+    /// sold, removed and changed items. Sold and removed items are no longer watched
+    pub async fn check_watched_items(&self) -> Result<(), Error> {
+        let config = crate::config::get();
+        let now = chrono::Local::now().timestamp();
+        let due = self
+            .database
+            .fetch_due_watched_items(now - config.vahti.watch_interval)
+            .await?
+            .into_iter()
+            .filter(|w| config.site_enabled(w.site_id))
+            .collect::<Vec<_>>();
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        debug!("Checking {} watched items", due.len());
+        let db = &self.database;
+        let notifications = stream::iter(due)
+            .map(|w| async move {
+                let status =
+                    match fetch_listing(w.site_id, w.listing_id, is_recommerce_listing(&w.url))
+                        .await
+                    {
+                        Ok(s) => s,
+                        Err(e) => {
+                            debug!("Failed to check watched item {}: {}", w.listing_id, e);
+                            db.update_watched_item(w.id, &w.title, w.price, now).await?;
+                            return Ok(None);
+                        }
+                    };
+
+                let (message, action) = match status {
+                    ItemStatus::Available(item) => {
+                        db.update_watched_item(w.id, &item.title, item.price, now)
+                            .await?;
+                        let Some(message) = change_message(&w, &item) else {
+                            return Ok(None);
+                        };
+                        (message, Some(NotificationAction::UnwatchItem(w.id)))
+                    }
+                    ItemStatus::Sold => {
+                        db.remove_watched_item(w.id, w.user_id, w.delivery_method)
+                            .await?;
+                        (
+                            format!("Seuraamasi ilmoitus {} on myyty: {}", w.title, w.url),
+                            None,
+                        )
+                    }
+                    ItemStatus::Removed => {
+                        db.remove_watched_item(w.id, w.user_id, w.delivery_method)
+                            .await?;
+                        (
+                            format!(
+                                "Seuraamasi ilmoitus {} ei ole enää saatavilla: {}",
+                                w.title, w.url
+                            ),
+                            None,
+                        )
+                    }
+                };
+
+                Ok::<_, Error>(Some(Notification {
+                    deliver_to: w.user_id as u64,
+                    delivery_method: w.delivery_method,
+                    message,
+                    action,
+                }))
+            })
+            .buffer_unordered(config.futures_max_buffer_size)
+            .collect::<Vec<_>>()
+            .await;
+
+        for n in notifications {
+            match n {
+                Ok(Some(n)) => {
+                    if let Err(e) = perform_notification(self.delivery.clone(), n).await {
+                        error!("Failed to send a notification: {}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => error!("Failed to check a watched item: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+#!/bin/sh
+# Regenerates data/postal_codes.csv from the GeoNames postal code dump of Finland
+# (https://download.geonames.org/export/zip/, CC BY 4.0)
+set -eu
+
+out="$(dirname "$0")/../data/postal_codes.csv"
+tmp="$(mktemp -d)"
+trap 'rm -rf "$tmp"' EXIT
+
+curl -fsSL -o "$tmp/FI.zip" https://download.geonames.org/export/zip/FI.zip
+unzip -q -o "$tmp/FI.zip" FI.txt -d "$tmp"
+
+# NOTE: The columns are tab-separated: country, postal code, place name, 3 x (admin name, admin code),
+# latitude, longitude and accuracy. A code may be listed for several places, the first one is used
+{
+    echo "postal_code,latitude,longitude,name"
+    awk -F '\t' '$2 ~ /^[0-9][0-9][0-9][0-9][0-9]$/ && !seen[$2]++ {
+        gsub(",", " ", $3)
+        printf "%s,%.4f,%.4f,%s\n", $2, $10, $11, $3
+    }' "$tmp/FI.txt" | sort
+} > "$out"
+
+echo "Wrote $(($(wc -l < "$out") - 1)) postal codes to $out"